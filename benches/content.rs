@@ -0,0 +1,38 @@
+//! Typed against raw page content, run with `cargo bench --features raw-content`
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use telegraph_rs::{Page, PageRaw};
+
+const PARAGRAPH: &str = r#"{"tag":"p","children":["Lorem ipsum dolor sit amet, ",{"tag":"em","children":["consectetur"]}," adipiscing elit, ",{"tag":"a","attrs":{"href":"https://example.com/"},"children":["sed do"]}," eiusmod tempor incididunt ut labore et dolore magna aliqua.",{"tag":"br"}]}"#;
+
+fn content(c: &mut Criterion) {
+    let paragraphs = vec![PARAGRAPH; 50 * 1024 / PARAGRAPH.len()];
+    let page = format!(
+        r#"{{"path":"Sample-Page-12-15","url":"https://telegra.ph/Sample-Page-12-15","title":"Sample Page","description":"","content":[{}],"views":0}}"#,
+        paragraphs.join(",")
+    );
+
+    let mut group = c.benchmark_group("content");
+    group.throughput(Throughput::Bytes(page.len() as u64));
+    group.bench_function("page (50 KB), typed", |b| {
+        b.iter(|| serde_json::from_str::<Page>(black_box(&page)).unwrap())
+    });
+    group.bench_function("page (50 KB), raw", |b| {
+        b.iter(|| serde_json::from_str::<PageRaw>(black_box(&page)).unwrap())
+    });
+    group.bench_function("page (50 KB), typed republish", |b| {
+        b.iter(|| {
+            let page = serde_json::from_str::<Page>(black_box(&page)).unwrap();
+            serde_json::to_string(&page.content).unwrap()
+        })
+    });
+    group.bench_function("page (50 KB), raw republish", |b| {
+        b.iter(|| {
+            let page = serde_json::from_str::<PageRaw>(black_box(&page)).unwrap();
+            page.content.unwrap().get().len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, content);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Conversion throughput, run with `cargo bench`
+//!
+//! The `HtmlConverter` cases convert the same documents with a reused converter,
+//! the measured results are listed in its documentation.
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use telegraph_rs::{
+    html_to_node_with_options, html_to_nodes_with_options, ConversionOptions, HtmlConverter,
+};
+
+const FRAGMENT: &str =
+    r#"<p>Hello, <b>world</b>! See <a href="https://example.com">this link</a>.</p>"#;
+
+const SECTION: &str = r#"<section>
+  <h2 id="section">A section title</h2>
+  <p>Lorem ipsum dolor sit amet, <em>consectetur</em> adipiscing elit, sed do eiusmod tempor
+  incididunt ut labore et dolore magna aliqua. <a href="/relative/link.html">Ut enim</a> ad minim
+  veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat.</p>
+  <figure><img src="/images/photo.jpg" alt="A photo"><figcaption>A photo</figcaption></figure>
+  <ul>
+    <li>First item with <code>code</code></li>
+    <li>Second item with <span class="x">a span</span></li>
+  </ul>
+  <blockquote>Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore.</blockquote>
+  <script>console.log("<p>not html</p>")</script>
+</section>
+"#;
+
+fn conversion(c: &mut Criterion) {
+    let article = format!(
+        "<article>{}</article>",
+        SECTION.repeat(200 * 1024 / SECTION.len())
+    );
+    let default = ConversionOptions::default();
+    let sanitized = ConversionOptions::sanitized()
+        .map_containers(true)
+        .normalize_whitespace(true)
+        .clean(true);
+    let converter = HtmlConverter::new(default.clone());
+
+    let mut group = c.benchmark_group("conversion");
+    group.throughput(Throughput::Bytes(FRAGMENT.len() as u64));
+    group.bench_function("fragment", |b| {
+        b.iter(|| html_to_nodes_with_options(black_box(FRAGMENT), &default).unwrap())
+    });
+    group.bench_function("fragment, HtmlConverter", |b| {
+        b.iter(|| converter.convert(black_box(FRAGMENT)).unwrap())
+    });
+
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(article.len() as u64));
+    group.bench_function("article (200 KB)", |b| {
+        b.iter(|| html_to_nodes_with_options(black_box(&article), &default).unwrap())
+    });
+    group.bench_function("article (200 KB), sanitized", |b| {
+        b.iter(|| html_to_nodes_with_options(black_box(&article), &sanitized).unwrap())
+    });
+    group.bench_function("article (200 KB), to string", |b| {
+        b.iter(|| html_to_node_with_options(black_box(&article), &default).unwrap())
+    });
+    group.bench_function("article (200 KB), to string, HtmlConverter", |b| {
+        b.iter(|| converter.convert_to_string(black_box(&article)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, conversion);
+criterion_main!(benches);
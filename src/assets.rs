@@ -0,0 +1,270 @@
+//! Upload local files referenced by content
+use crate::{visit_mut, Error, ImageInfo, Node, NodePath, Page, Result, Telegraph, Visit};
+use reqwest::{Client, Url};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Tags whose `src` can be a local file
+const ASSET_TAGS: &[&str] = &["img", "video"];
+
+impl Telegraph {
+    /// Upload the local files referenced by `nodes`, then create a page with them, see [`Telegraph::upload_assets`]
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use std::path::Path;
+    /// use telegraph_rs::{file_to_nodes, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let nodes = file_to_nodes(Path::new("post/index.md"))?;
+    /// let page = telegraph
+    ///     .create_page_with_assets("Post", nodes, Path::new("post"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_page_with_assets(
+        &self,
+        title: &str,
+        nodes: Vec<Node>,
+        base_dir: &Path,
+    ) -> Result<Page> {
+        self.create_page_with_assets_to("https://telegra.ph/upload", title, nodes, base_dir)
+            .await
+    }
+
+    pub(crate) async fn create_page_with_assets_to(
+        &self,
+        url: &str,
+        title: &str,
+        mut nodes: Vec<Node>,
+        base_dir: &Path,
+    ) -> Result<Page> {
+        Self::upload_assets_to(url, &mut nodes, base_dir, &self.client).await?;
+        self.create_page_nodes(title, &nodes, false).await
+    }
+
+    /// Upload the local files referenced by the `src` of `img` and `video` elements in `nodes`
+    /// and replace their `src` with the uploaded ones
+    ///
+    /// Local files are `file://` urls and relative paths like `./img/cat.png`, resolved against `base_dir`.
+    /// Paths starting with `/` are left untouched since they refer to files hosted by Telegraph.
+    /// A file referenced several times is uploaded once.
+    ///
+    /// Returns the uploaded files. Fails with [`Error::AssetError`] naming the file and the node
+    /// if a file can't be read, before uploading anything.
+    pub async fn upload_assets(
+        nodes: &mut [Node],
+        base_dir: &Path,
+        client: &Client,
+    ) -> Result<Vec<ImageInfo>> {
+        Self::upload_assets_to("https://telegra.ph/upload", nodes, base_dir, client).await
+    }
+
+    pub(crate) async fn upload_assets_to(
+        url: &str,
+        nodes: &mut [Node],
+        base_dir: &Path,
+        client: &Client,
+    ) -> Result<Vec<ImageInfo>> {
+        let mut found: Vec<(NodePath, String)> = vec![];
+        visit_mut(nodes, |node, path| {
+            if let Some(src) = asset_src(node).filter(|src| local_path(src).is_some()) {
+                found.push((path.to_vec(), src.to_owned()));
+            }
+            Visit::Children
+        });
+
+        let mut files: Vec<PathBuf> = vec![];
+        let mut uploaded: HashMap<String, usize> = HashMap::new();
+        for (node, src) in found {
+            let path = base_dir.join(local_path(&src).unwrap());
+            let file = std::fs::canonicalize(&path)
+                .and_then(|file| {
+                    std::fs::File::open(&file)?;
+                    Ok(file)
+                })
+                .map_err(|source| Error::AssetError { path, node, source })?;
+            let i = files.iter().position(|f| *f == file).unwrap_or_else(|| {
+                files.push(file);
+                files.len() - 1
+            });
+            uploaded.insert(src, i);
+        }
+        if files.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let images = Self::upload_to(url, &files, client).await?;
+        visit_mut(nodes, |node, _| {
+            if let Node::NodeElement(element) = node {
+                if let Some(src) = element.attrs.as_mut().and_then(|a| a.src.as_mut()) {
+                    if let Some(image) = uploaded.get(src).and_then(|&i| images.get(i)) {
+                        src.clone_from(&image.src);
+                    }
+                }
+            }
+            Visit::Children
+        });
+        Ok(images)
+    }
+}
+
+/// The `src` of an `img` or `video` element
+fn asset_src(node: &Node) -> Option<&str> {
+    match node {
+        Node::NodeElement(element) if ASSET_TAGS.contains(&&*element.tag) => {
+            element.attrs.as_ref()?.src.as_deref()
+        }
+        _ => None,
+    }
+}
+
+/// The local file referenced by `src`, a `file://` url or a relative path
+fn local_path(src: &str) -> Option<PathBuf> {
+    if src.starts_with("file:") {
+        return Url::parse(src).ok()?.to_file_path().ok();
+    }
+    let relative = !src.is_empty()
+        && !src.starts_with('/')
+        && !src.starts_with('\\')
+        && !src.starts_with('#')
+        && Url::parse(src).is_err();
+    if !relative {
+        return None;
+    }
+    let path = percent_encoding::percent_decode_str(src)
+        .decode_utf8()
+        .ok()?;
+    Some(PathBuf::from(&*path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::client;
+    use crate::{
+        node_to_html,
+        tests::{form_field, mock_server, page_response},
+        NodeElement,
+    };
+
+    /// A directory with `img/cat.png`, `img/dog cat.png` and `clip.mp4`
+    fn fixture() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("telegraph-rs-assets-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("img")).unwrap();
+        std::fs::write(dir.join("img/cat.png"), b"\x89PNG cat").unwrap();
+        std::fs::write(dir.join("img/dog cat.png"), b"\x89PNG dog").unwrap();
+        std::fs::write(dir.join("clip.mp4"), b"mp4").unwrap();
+        dir
+    }
+
+    fn img(src: &str) -> Node {
+        NodeElement::img(src).into()
+    }
+
+    #[test]
+    fn local_paths() {
+        assert_eq!(local_path("./img/cat.png"), Some("./img/cat.png".into()));
+        assert_eq!(
+            local_path("img/dog%20cat.png"),
+            Some("img/dog cat.png".into())
+        );
+        assert_eq!(local_path("../cat.png"), Some("../cat.png".into()));
+        #[cfg(unix)]
+        assert_eq!(
+            local_path("file:///tmp/cat.png"),
+            Some("/tmp/cat.png".into())
+        );
+        for src in [
+            "/file/cat.png",
+            "https://example.com/cat.png",
+            "//example.com/cat.png",
+            "data:image/png;base64,iVBORw0KGgo=",
+            "",
+        ] {
+            assert_eq!(local_path(src), None, "{}", src);
+        }
+    }
+
+    #[tokio::test]
+    async fn create_page_with_assets() {
+        let dir = fixture();
+        let (addr, requests) = mock_server(vec![
+            (
+                "POST /upload",
+                "application/json",
+                br#"[{"src":"/file/1.png"},{"src":"/file/2.png"},{"src":"/file/3.mp4"}]"#.to_vec(),
+            ),
+            (
+                "POST /createPage",
+                "application/json",
+                page_response("Hi-10-16", "Hi"),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let file_url = Url::from_file_path(dir.join("img/cat.png")).unwrap();
+        let nodes = vec![
+            img("./img/cat.png"),
+            NodeElement::new("figure")
+                .child(img("img/dog%20cat.png"))
+                .child(NodeElement::new("video").with_src("clip.mp4"))
+                .into(),
+            img(file_url.as_str()),
+            img("/file/old.png"),
+            img("https://example.com/cat.png"),
+        ];
+        telegraph
+            .create_page_with_assets_to(&format!("{}/upload", addr), "Hi", nodes, &dir)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("POST /upload"));
+        assert_eq!(requests[0].matches("filename=").count(), 3);
+        assert_eq!(requests[0].matches("PNG cat").count(), 1);
+        let content = form_field(&requests[1], "content").unwrap();
+        let content: Vec<Node> = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            node_to_html(&content),
+            concat!(
+                r#"<img src="/file/1.png">"#,
+                r#"<figure><img src="/file/2.png"><video src="/file/3.mp4"></video></figure>"#,
+                r#"<img src="/file/1.png"><img src="/file/old.png"><img src="https://example.com/cat.png">"#
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_asset() {
+        let dir = fixture();
+        let mut nodes = vec![
+            img("img/cat.png"),
+            NodeElement::new("p").child(img("img/missing.png")).into(),
+        ];
+        let error = Telegraph::upload_assets_to(
+            "http://127.0.0.1:1/upload",
+            &mut nodes,
+            &dir,
+            &Client::new(),
+        )
+        .await
+        .unwrap_err();
+        match &error {
+            Error::AssetError { path, node, .. } => {
+                assert_eq!(path, &dir.join("img/missing.png"));
+                assert_eq!(node, &[1, 0]);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(error
+            .to_string()
+            .contains("missing.png of the node at [1, 0]"));
+        assert_eq!(nodes[0], img("img/cat.png"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Publish many pages at once without hitting the flood limit
+use crate::{nodes_to_content_string, CreatePageOptions, Node, Page, Result, Telegraph};
+use futures_util::{stream, StreamExt};
+use std::{sync::Mutex, time::Duration};
+use tokio::time::{sleep_until, Instant};
+
+/// Options for [`Telegraph::create_pages`]
+///
+/// ```
+/// use std::time::Duration;
+/// use telegraph_rs::CreatePagesOptions;
+///
+/// let options = CreatePagesOptions::new().concurrency(2).delay(Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CreatePagesOptions {
+    concurrency: usize,
+    delay: Duration,
+    max_retries: u32,
+    return_content: bool,
+}
+
+impl Default for CreatePagesOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            delay: Duration::ZERO,
+            max_retries: 3,
+            return_content: false,
+        }
+    }
+}
+
+impl CreatePagesOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of pages created at the same time, 1 by default.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Time between the starts of two requests, none by default.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Number of times a page is retried after a `FLOOD_WAIT_X` error, 3 by default.
+    ///
+    /// The page is retried after waiting the time asked by the error, see [`Error::flood_wait`](crate::Error::flood_wait).
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Whether the returned pages have their content, false by default.
+    pub fn return_content(mut self, return_content: bool) -> Self {
+        self.return_content = return_content;
+        self
+    }
+}
+
+impl Telegraph {
+    /// Create a page for each `(title, content)` of `items`, see [`Telegraph::create_pages_with_progress`]
+    pub async fn create_pages(
+        &self,
+        items: Vec<(String, Vec<Node>)>,
+        options: &CreatePagesOptions,
+    ) -> Vec<Result<Page>> {
+        self.create_pages_with_progress(items, options, |_, _| {})
+            .await
+    }
+
+    /// Create a page for each `(title, content)` of `items`, calling `progress` with the index of each item once it's done
+    ///
+    /// Pages are created [`CreatePagesOptions::concurrency`] at a time, and requests are spaced by [`CreatePagesOptions::delay`].
+    /// Returns the result of each item in the order of `items`, a failed item doesn't stop the others.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use std::time::Duration;
+    /// use telegraph_rs::{dsl::*, CreatePagesOptions, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let items = (1..=100)
+    ///     .map(|i| (format!("Chapter {}", i), vec![p([format!("Chapter {}", i).as_str()])]))
+    ///     .collect::<Vec<_>>();
+    /// let options = CreatePagesOptions::new().concurrency(2).delay(Duration::from_millis(500));
+    /// let total = items.len();
+    /// let pages = telegraph
+    ///     .create_pages_with_progress(items, &options, |i, result| {
+    ///         println!("{}/{}: {:?}", i + 1, total, result.as_ref().map(|page| &page.url));
+    ///     })
+    ///     .await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_pages_with_progress(
+        &self,
+        items: Vec<(String, Vec<Node>)>,
+        options: &CreatePagesOptions,
+        progress: impl Fn(usize, &Result<Page>),
+    ) -> Vec<Result<Page>> {
+        let create = CreatePageOptions::new().return_content(options.return_content);
+        let next_start = Mutex::new(Instant::now());
+        let wait_turn = || {
+            let mut next_start = next_start.lock().unwrap();
+            let start = (*next_start).max(Instant::now());
+            *next_start = start + options.delay;
+            sleep_until(start)
+        };
+
+        let mut results = stream::iter(items.into_iter().enumerate())
+            .map(|(i, (title, content))| {
+                let (create, wait_turn) = (&create, &wait_turn);
+                async move {
+                    let content = nodes_to_content_string(&content);
+                    let mut retries = 0;
+                    loop {
+                        wait_turn().await;
+                        let result = self.post_page(None, &title, &content, create).await;
+                        match result.as_ref().err().and_then(|e| e.flood_wait()) {
+                            Some(wait) if retries < options.max_retries => {
+                                retries += 1;
+                                tokio::time::sleep(wait).await;
+                            }
+                            _ => return (i, result),
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(options.concurrency)
+            .inspect(|(i, result)| progress(*i, result))
+            .collect::<Vec<_>>()
+            .await;
+        results.sort_unstable_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::client;
+    use crate::{
+        tests::{form_field, mock_handler, page_response},
+        Error, NodeElement,
+    };
+    use std::sync::Arc;
+
+    /// A server creating pages named after their title, failing the `flood`th request with `FLOOD_WAIT_1`
+    async fn server(flood: usize) -> (Telegraph, Arc<Mutex<Vec<String>>>) {
+        let mut count = 0;
+        let (addr, requests) = mock_handler(move |request| {
+            count += 1;
+            if count == flood {
+                return Some((
+                    "application/json",
+                    br#"{"ok":false,"error":"FLOOD_WAIT_1"}"#.to_vec(),
+                ));
+            }
+            let title = form_field(request, "title").unwrap();
+            let page = page_response(&format!("{}-10-16", title), &title);
+            Some(("application/json", page))
+        })
+        .await;
+        let telegraph = client(&addr).await;
+        (telegraph, requests)
+    }
+
+    fn items(count: usize) -> Vec<(String, Vec<Node>)> {
+        (0..count)
+            .map(|i| {
+                let content = NodeElement::new("p").child(i.to_string()).into_node();
+                (format!("Page{}", i), vec![content])
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn flood_wait_retry() {
+        let (telegraph, requests) = server(3).await;
+        let done = Mutex::new(vec![]);
+        let start = Instant::now();
+        let results = telegraph
+            .create_pages_with_progress(items(5), &CreatePagesOptions::new(), |i, result| {
+                assert!(result.is_ok());
+                done.lock().unwrap().push(i);
+            })
+            .await;
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        let paths = results
+            .into_iter()
+            .map(|result| result.unwrap().path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "Page0-10-16",
+                "Page1-10-16",
+                "Page2-10-16",
+                "Page3-10-16",
+                "Page4-10-16"
+            ]
+        );
+        assert_eq!(done.into_inner().unwrap(), [0, 1, 2, 3, 4]);
+        let titles = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| form_field(request, "title").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            ["Page0", "Page1", "Page2", "Page2", "Page3", "Page4"]
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrency_and_delay() {
+        let (telegraph, requests) = server(2).await;
+        let options = CreatePagesOptions::new()
+            .concurrency(3)
+            .delay(Duration::from_millis(200))
+            .max_retries(0);
+        let start = Instant::now();
+        let results = telegraph.create_pages(items(4), &options).await;
+        assert!(start.elapsed() >= Duration::from_millis(600));
+        assert_eq!(requests.lock().unwrap().len(), 4);
+
+        let failed = results.iter().position(Result::is_err).unwrap();
+        assert!(matches!(&results[failed], Err(Error::ApiError(e)) if e == "FLOOD_WAIT_1"));
+        for (i, result) in results.iter().enumerate() {
+            if i != failed {
+                assert_eq!(result.as_ref().unwrap().title, format!("Page{}", i));
+            }
+        }
+        assert_eq!(
+            Error::ApiError("FLOOD_WAIT_7".to_owned()).flood_wait(),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            Error::ApiError("PAGE_NOT_FOUND".to_owned()).flood_wait(),
+            None
+        );
+    }
+}
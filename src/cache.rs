@@ -0,0 +1,267 @@
+//! Cache of fetched pages, enabled by [`AccountBuilder::cache`](crate::AccountBuilder::cache)
+use crate::{Page, Telegraph};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Pages fetched by [`Telegraph::get_own_page`], keyed by path and whether they have content
+///
+/// The lock is never held across an await, so a blocking mutex is enough.
+#[derive(Debug)]
+pub(crate) struct PageCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    pages: HashMap<(String, bool), Entry>,
+    /// Incremented on every use, to find the least recently used entry
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    page: Page,
+    expires: Instant,
+    used: u64,
+}
+
+impl PageCache {
+    pub(crate) fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn get(&self, path: &str, return_content: bool) -> Option<Page> {
+        let mut entries = self.entries.lock().unwrap();
+        let key = (path.to_owned(), return_content);
+        if entries.pages.get(&key)?.expires <= Instant::now() {
+            entries.pages.remove(&key);
+            return None;
+        }
+        entries.clock += 1;
+        let clock = entries.clock;
+        let entry = entries.pages.get_mut(&key)?;
+        entry.used = clock;
+        Some(entry.page.clone())
+    }
+
+    pub(crate) fn insert(&self, path: &str, return_content: bool, page: Page) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let key = (path.to_owned(), return_content);
+        if !entries.pages.contains_key(&key) && entries.pages.len() >= self.capacity {
+            let now = Instant::now();
+            entries.pages.retain(|_, entry| entry.expires > now);
+            if entries.pages.len() >= self.capacity {
+                let oldest = entries
+                    .pages
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.used)
+                    .map(|(key, _)| key.clone());
+                entries.pages.remove(&oldest.unwrap());
+            }
+        }
+        entries.clock += 1;
+        let entry = Entry {
+            page,
+            expires: Instant::now() + self.ttl,
+            used: entries.clock,
+        };
+        entries.pages.insert(key, entry);
+    }
+
+    pub(crate) fn invalidate(&self, path: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.pages.retain(|(cached, _), _| cached != path);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().pages.clear();
+    }
+}
+
+impl Telegraph {
+    /// Drop the cached page at `path`, so it's fetched again the next time
+    ///
+    /// Pages edited with this instance are dropped automatically. Does nothing without a cache.
+    pub fn invalidate(&self, path: &str) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(path);
+        }
+    }
+
+    /// Drop all the cached pages
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{client, mock_server};
+    use std::sync::Arc;
+
+    async fn telegraph(ttl: Duration, capacity: usize) -> (Telegraph, Arc<Mutex<Vec<String>>>) {
+        let page = |path: &str| {
+            format!(
+                r#"{{"ok":true,"result":{{"path":"{}","url":"https://telegra.ph/{}","title":"{}","description":"","content":["a"],"views":0,"can_edit":true}}}}"#,
+                path, path, path
+            )
+            .into_bytes()
+        };
+        let (addr, requests) = mock_server(vec![
+            ("GET /getPage/A", "application/json", page("A")),
+            ("GET /getPage/B", "application/json", page("B")),
+            ("GET /getPage/C", "application/json", page("C")),
+            ("POST /editPage", "application/json", page("A")),
+            (
+                "GET /editAccountInfo",
+                "application/json",
+                br#"{"ok":true,"result":{"short_name":"edited","author_name":"","author_url":""}}"#
+                    .to_vec(),
+            ),
+        ])
+        .await;
+        let telegraph = Telegraph::new("test")
+            .access_token("token")
+            .api_url(&addr)
+            .cache(ttl, capacity)
+            .create()
+            .await
+            .unwrap();
+        (telegraph, requests)
+    }
+
+    /// Fetch each of `paths` and return the paths which were requested from the server
+    async fn fetch(
+        telegraph: &Telegraph,
+        requests: &Mutex<Vec<String>>,
+        paths: &[&str],
+    ) -> Vec<String> {
+        for path in paths {
+            let page = telegraph.get_own_page(path, true).await.unwrap();
+            assert_eq!(page.path, *path);
+        }
+        let requests = requests.lock().unwrap().split_off(0);
+        requests
+            .iter()
+            .map(|request| request[13..].split('?').next().unwrap().to_owned())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn hits_and_lru() {
+        let (telegraph, requests) = telegraph(Duration::from_secs(60), 2).await;
+        assert_eq!(
+            fetch(&telegraph, &requests, &["A", "B", "A", "B"]).await,
+            ["A", "B"]
+        );
+
+        let content = telegraph.get_own_page_content("A").await.unwrap();
+        assert_eq!(content, vec![crate::Node::text("a")]);
+        telegraph.get_own_page("A", false).await.unwrap();
+        assert_eq!(requests.lock().unwrap().split_off(0).len(), 1);
+
+        // A was used last, so B was dropped for A without content
+        assert_eq!(fetch(&telegraph, &requests, &["A", "B"]).await, ["B"]);
+        assert_eq!(
+            fetch(&telegraph, &requests, &["B", "C", "B", "A"]).await,
+            ["C", "A"]
+        );
+
+        let pages = futures_util::future::join_all(
+            ["A", "B", "C"].map(|path| telegraph.get_own_page(path, true)),
+        )
+        .await;
+        assert!(pages.iter().all(Result::is_ok));
+        assert_eq!(fetch(&telegraph, &requests, &[]).await, ["C"]);
+    }
+
+    #[tokio::test]
+    async fn cached_methods() {
+        let (telegraph, requests) = telegraph(Duration::from_secs(60), 10).await;
+        assert_eq!(fetch(&telegraph, &requests, &["A"]).await, ["A"]);
+        telegraph.get_own_page("A", false).await.unwrap();
+        requests.lock().unwrap().clear();
+
+        telegraph
+            .edit_page_content("A", r#"["b"]"#, false)
+            .await
+            .unwrap();
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].starts_with("POST /editPage"));
+
+        // the edit dropped the page
+        let new = [crate::Node::text("c")];
+        telegraph.append_to_page("A", &new).await.unwrap();
+        telegraph.prepend_to_page("A", &new).await.unwrap();
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 4);
+        assert!(sent[0].starts_with("GET /getPage/A"));
+        assert!(sent[2].starts_with("GET /getPage/A"));
+    }
+
+    #[tokio::test]
+    async fn expiry_and_invalidation() {
+        let (telegraph, requests) = telegraph(Duration::from_millis(100), 10).await;
+        assert_eq!(
+            fetch(&telegraph, &requests, &["A", "B", "A"]).await,
+            ["A", "B"]
+        );
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(fetch(&telegraph, &requests, &["A", "A"]).await, ["A"]);
+
+        telegraph.invalidate("A");
+        assert_eq!(fetch(&telegraph, &requests, &["A", "A"]).await, ["A"]);
+
+        telegraph
+            .edit_page("A", "A", r#"["b"]"#, false)
+            .await
+            .unwrap();
+        assert_eq!(requests.lock().unwrap().split_off(0).len(), 1);
+        assert_eq!(
+            fetch(&telegraph, &requests, &["A", "B", "B"]).await,
+            ["A", "B"]
+        );
+
+        telegraph.clear_cache();
+        assert_eq!(fetch(&telegraph, &requests, &["A", "B"]).await, ["A", "B"]);
+
+        let uncached = client(&telegraph.api_url).await;
+        assert_eq!(fetch(&uncached, &requests, &["A", "A"]).await, ["A", "A"]);
+    }
+
+    #[tokio::test]
+    async fn shared_with_edited_account() {
+        let (telegraph, requests) = telegraph(Duration::from_secs(60), 10).await;
+        let edited = telegraph
+            .clone()
+            .edit_account_info()
+            .short_name("edited")
+            .edit()
+            .await
+            .unwrap();
+        requests.lock().unwrap().clear();
+
+        assert_eq!(fetch(&telegraph, &requests, &["A"]).await, ["A"]);
+        assert!(fetch(&edited, &requests, &["A"]).await.is_empty());
+
+        edited.edit_page("A", "A", r#"["b"]"#, false).await.unwrap();
+        requests.lock().unwrap().clear();
+        assert_eq!(fetch(&telegraph, &requests, &["A"]).await, ["A"]);
+    }
+}
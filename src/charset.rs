@@ -0,0 +1,229 @@
+//! Decode html bytes with the charset declared in the document
+use crate::{Error, Result};
+use std::borrow::Cow;
+
+/// Encodings which can be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Charset {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    SingleByte(&'static [char; 128]),
+}
+
+/// Decode `bytes` of a html document into a string
+///
+/// The charset is taken from `charset` if set, then from the byte order mark,
+/// then from a `<meta charset>` or `<meta http-equiv="Content-Type">` declaration,
+/// and defaults to UTF-8.
+///
+/// Supported charsets are UTF-8, UTF-16, windows-1251, windows-1252 (including ISO-8859-1 and ASCII) and KOI8-R,
+/// others fail with [`Error::UnsupportedCharset`].
+///
+/// ```rust
+/// use telegraph_rs::decode_html;
+///
+/// let html = b"<meta charset=windows-1251><p>\xcf\xf0\xe8\xe2\xe5\xf2</p>";
+/// assert_eq!(decode_html(html, None).unwrap(), "<meta charset=windows-1251><p>Привет</p>");
+/// ```
+pub fn decode_html<'a>(bytes: &'a [u8], charset: Option<&str>) -> Result<Cow<'a, str>> {
+    let (charset, bytes) = match charset {
+        Some(label) => (lookup(label)?, strip_bom(bytes).1),
+        None => match strip_bom(bytes) {
+            (Some(charset), bytes) => (charset, bytes),
+            (None, bytes) => match sniff_meta(bytes) {
+                Some(label) => (lookup(&label)?, bytes),
+                None => (Charset::Utf8, bytes),
+            },
+        },
+    };
+
+    Ok(match charset {
+        Charset::Utf8 => String::from_utf8_lossy(bytes),
+        Charset::Utf16Le | Charset::Utf16Be => {
+            let units = bytes.chunks_exact(2).map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if charset == Charset::Utf16Le {
+                    u16::from_le_bytes(pair)
+                } else {
+                    u16::from_be_bytes(pair)
+                }
+            });
+            Cow::Owned(
+                char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect(),
+            )
+        }
+        Charset::SingleByte(table) => match std::str::from_utf8(bytes) {
+            Ok(ascii) if ascii.is_ascii() => Cow::Borrowed(ascii),
+            _ => Cow::Owned(
+                bytes
+                    .iter()
+                    .map(|&b| {
+                        if b < 0x80 {
+                            b as char
+                        } else {
+                            table[b as usize - 0x80]
+                        }
+                    })
+                    .collect(),
+            ),
+        },
+    })
+}
+
+fn strip_bom(bytes: &[u8]) -> (Option<Charset>, &[u8]) {
+    match bytes {
+        [0xef, 0xbb, 0xbf, rest @ ..] => (Some(Charset::Utf8), rest),
+        [0xff, 0xfe, rest @ ..] => (Some(Charset::Utf16Le), rest),
+        [0xfe, 0xff, rest @ ..] => (Some(Charset::Utf16Be), rest),
+        _ => (None, bytes),
+    }
+}
+
+/// Find the charset declared by a `meta` element in the first 1024 bytes
+fn sniff_meta(bytes: &[u8]) -> Option<String> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_ascii_lowercase();
+    let mut rest = &*head;
+    while let Some(start) = rest.find("<meta") {
+        rest = &rest[start + 5..];
+        let tag = &rest[..rest.find('>').unwrap_or(rest.len())];
+        if let Some(start) = tag.find("charset") {
+            let value = tag[start + 7..].trim_start();
+            if let Some(value) = value.strip_prefix('=') {
+                let label = value
+                    .trim_start()
+                    .trim_start_matches(['"', '\''])
+                    .split(|c: char| c.is_ascii_whitespace() || "\"';/>".contains(c))
+                    .next()
+                    .unwrap_or_default();
+                if !label.is_empty() {
+                    return Some(label.to_owned());
+                }
+            }
+        }
+    }
+    None
+}
+
+fn lookup(label: &str) -> Result<Charset> {
+    Ok(match &*label.trim().to_ascii_lowercase() {
+        "utf-8" | "utf8" | "unicode-1-1-utf-8" => Charset::Utf8,
+        "utf-16" | "utf-16le" => Charset::Utf16Le,
+        "utf-16be" => Charset::Utf16Be,
+        "windows-1251" | "cp1251" | "x-cp1251" => Charset::SingleByte(&WINDOWS_1251),
+        "windows-1252" | "cp1252" | "x-cp1252" | "iso-8859-1" | "iso8859-1" | "latin1" | "l1"
+        | "us-ascii" | "ascii" => Charset::SingleByte(&WINDOWS_1252),
+        "koi8-r" | "koi8" | "koi" | "cskoi8r" => Charset::SingleByte(&KOI8_R),
+        _ => return Err(Error::UnsupportedCharset(label.to_owned())),
+    })
+}
+
+/// Characters of the bytes `0x80..=0xff`
+const WINDOWS_1251: [char; 128] = [
+    '\u{0402}', '\u{0403}', '\u{201A}', '\u{0453}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{20AC}', '\u{2030}', '\u{0409}', '\u{2039}', '\u{040A}', '\u{040C}', '\u{040B}', '\u{040F}',
+    '\u{0452}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{0098}', '\u{2122}', '\u{0459}', '\u{203A}', '\u{045A}', '\u{045C}', '\u{045B}', '\u{045F}',
+    '\u{00A0}', '\u{040E}', '\u{045E}', '\u{0408}', '\u{00A4}', '\u{0490}', '\u{00A6}', '\u{00A7}',
+    '\u{0401}', '\u{00A9}', '\u{0404}', '\u{00AB}', '\u{00AC}', '\u{00AD}', '\u{00AE}', '\u{0407}',
+    '\u{00B0}', '\u{00B1}', '\u{0406}', '\u{0456}', '\u{0491}', '\u{00B5}', '\u{00B6}', '\u{00B7}',
+    '\u{0451}', '\u{2116}', '\u{0454}', '\u{00BB}', '\u{0458}', '\u{0405}', '\u{0455}', '\u{0457}',
+    '\u{0410}', '\u{0411}', '\u{0412}', '\u{0413}', '\u{0414}', '\u{0415}', '\u{0416}', '\u{0417}',
+    '\u{0418}', '\u{0419}', '\u{041A}', '\u{041B}', '\u{041C}', '\u{041D}', '\u{041E}', '\u{041F}',
+    '\u{0420}', '\u{0421}', '\u{0422}', '\u{0423}', '\u{0424}', '\u{0425}', '\u{0426}', '\u{0427}',
+    '\u{0428}', '\u{0429}', '\u{042A}', '\u{042B}', '\u{042C}', '\u{042D}', '\u{042E}', '\u{042F}',
+    '\u{0430}', '\u{0431}', '\u{0432}', '\u{0433}', '\u{0434}', '\u{0435}', '\u{0436}', '\u{0437}',
+    '\u{0438}', '\u{0439}', '\u{043A}', '\u{043B}', '\u{043C}', '\u{043D}', '\u{043E}', '\u{043F}',
+    '\u{0440}', '\u{0441}', '\u{0442}', '\u{0443}', '\u{0444}', '\u{0445}', '\u{0446}', '\u{0447}',
+    '\u{0448}', '\u{0449}', '\u{044A}', '\u{044B}', '\u{044C}', '\u{044D}', '\u{044E}', '\u{044F}',
+];
+
+const WINDOWS_1252: [char; 128] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+    '\u{00A0}', '\u{00A1}', '\u{00A2}', '\u{00A3}', '\u{00A4}', '\u{00A5}', '\u{00A6}', '\u{00A7}',
+    '\u{00A8}', '\u{00A9}', '\u{00AA}', '\u{00AB}', '\u{00AC}', '\u{00AD}', '\u{00AE}', '\u{00AF}',
+    '\u{00B0}', '\u{00B1}', '\u{00B2}', '\u{00B3}', '\u{00B4}', '\u{00B5}', '\u{00B6}', '\u{00B7}',
+    '\u{00B8}', '\u{00B9}', '\u{00BA}', '\u{00BB}', '\u{00BC}', '\u{00BD}', '\u{00BE}', '\u{00BF}',
+    '\u{00C0}', '\u{00C1}', '\u{00C2}', '\u{00C3}', '\u{00C4}', '\u{00C5}', '\u{00C6}', '\u{00C7}',
+    '\u{00C8}', '\u{00C9}', '\u{00CA}', '\u{00CB}', '\u{00CC}', '\u{00CD}', '\u{00CE}', '\u{00CF}',
+    '\u{00D0}', '\u{00D1}', '\u{00D2}', '\u{00D3}', '\u{00D4}', '\u{00D5}', '\u{00D6}', '\u{00D7}',
+    '\u{00D8}', '\u{00D9}', '\u{00DA}', '\u{00DB}', '\u{00DC}', '\u{00DD}', '\u{00DE}', '\u{00DF}',
+    '\u{00E0}', '\u{00E1}', '\u{00E2}', '\u{00E3}', '\u{00E4}', '\u{00E5}', '\u{00E6}', '\u{00E7}',
+    '\u{00E8}', '\u{00E9}', '\u{00EA}', '\u{00EB}', '\u{00EC}', '\u{00ED}', '\u{00EE}', '\u{00EF}',
+    '\u{00F0}', '\u{00F1}', '\u{00F2}', '\u{00F3}', '\u{00F4}', '\u{00F5}', '\u{00F6}', '\u{00F7}',
+    '\u{00F8}', '\u{00F9}', '\u{00FA}', '\u{00FB}', '\u{00FC}', '\u{00FD}', '\u{00FE}', '\u{00FF}',
+];
+
+const KOI8_R: [char; 128] = [
+    '\u{2500}', '\u{2502}', '\u{250C}', '\u{2510}', '\u{2514}', '\u{2518}', '\u{251C}', '\u{2524}',
+    '\u{252C}', '\u{2534}', '\u{253C}', '\u{2580}', '\u{2584}', '\u{2588}', '\u{258C}', '\u{2590}',
+    '\u{2591}', '\u{2592}', '\u{2593}', '\u{2320}', '\u{25A0}', '\u{2219}', '\u{221A}', '\u{2248}',
+    '\u{2264}', '\u{2265}', '\u{00A0}', '\u{2321}', '\u{00B0}', '\u{00B2}', '\u{00B7}', '\u{00F7}',
+    '\u{2550}', '\u{2551}', '\u{2552}', '\u{0451}', '\u{2553}', '\u{2554}', '\u{2555}', '\u{2556}',
+    '\u{2557}', '\u{2558}', '\u{2559}', '\u{255A}', '\u{255B}', '\u{255C}', '\u{255D}', '\u{255E}',
+    '\u{255F}', '\u{2560}', '\u{2561}', '\u{0401}', '\u{2562}', '\u{2563}', '\u{2564}', '\u{2565}',
+    '\u{2566}', '\u{2567}', '\u{2568}', '\u{2569}', '\u{256A}', '\u{256B}', '\u{256C}', '\u{00A9}',
+    '\u{044E}', '\u{0430}', '\u{0431}', '\u{0446}', '\u{0434}', '\u{0435}', '\u{0444}', '\u{0433}',
+    '\u{0445}', '\u{0438}', '\u{0439}', '\u{043A}', '\u{043B}', '\u{043C}', '\u{043D}', '\u{043E}',
+    '\u{043F}', '\u{044F}', '\u{0440}', '\u{0441}', '\u{0442}', '\u{0443}', '\u{0436}', '\u{0432}',
+    '\u{044C}', '\u{044B}', '\u{0437}', '\u{0448}', '\u{044D}', '\u{0449}', '\u{0447}', '\u{044A}',
+    '\u{042E}', '\u{0410}', '\u{0411}', '\u{0426}', '\u{0414}', '\u{0415}', '\u{0424}', '\u{0413}',
+    '\u{0425}', '\u{0418}', '\u{0419}', '\u{041A}', '\u{041B}', '\u{041C}', '\u{041D}', '\u{041E}',
+    '\u{041F}', '\u{042F}', '\u{0420}', '\u{0421}', '\u{0422}', '\u{0423}', '\u{0416}', '\u{0412}',
+    '\u{042C}', '\u{042B}', '\u{0417}', '\u{0428}', '\u{042D}', '\u{0429}', '\u{0427}', '\u{042A}',
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "Привет, мир!" in windows-1251
+    const WINDOWS_1251_HTML: &[u8] =
+        b"<html><head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\"></head>\
+          <body><p>\xcf\xf0\xe8\xe2\xe5\xf2, \xec\xe8\xf0!</p></body></html>";
+
+    #[test]
+    fn sniff_charset() {
+        assert!(decode_html(WINDOWS_1251_HTML, None)
+            .unwrap()
+            .contains("<p>Привет, мир!</p>"));
+        assert_eq!(
+            decode_html(b"<meta charset='KOI8-R'>\xf0\xd2\xc9\xd7\xc5\xd4", None).unwrap(),
+            "<meta charset='KOI8-R'>Привет"
+        );
+        assert_eq!(
+            decode_html(b"\xef\xbb\xbf<p>\xc3\xa9</p>", None).unwrap(),
+            "<p>é</p>"
+        );
+        assert_eq!(
+            decode_html(b"\xff\xfe<\x00p\x00>\x00", None).unwrap(),
+            "<p>"
+        );
+        assert_eq!(
+            decode_html("<p>é</p>".as_bytes(), None).unwrap(),
+            "<p>é</p>"
+        );
+    }
+
+    #[test]
+    fn override_charset() {
+        let html = b"<meta charset=utf-8><p>caf\xe9</p>";
+        assert_eq!(
+            decode_html(html, None).unwrap(),
+            "<meta charset=utf-8><p>caf\u{fffd}</p>"
+        );
+        assert_eq!(
+            decode_html(html, Some("latin1")).unwrap(),
+            "<meta charset=utf-8><p>café</p>"
+        );
+        assert!(matches!(
+            decode_html(b"<meta charset=gbk>", None),
+            Err(Error::UnsupportedCharset(label)) if label == "gbk"
+        ));
+    }
+}
@@ -0,0 +1,322 @@
+//! Copy pages to the current account
+use crate::{
+    nodes_to_content_string, utils::page_url_path, validate_nodes, CreatePageOptions, Error,
+    NodeElement, Page, Result, Telegraph,
+};
+use reqwest::Url;
+
+/// Options for [`Telegraph::clone_page_with_options`]
+///
+/// ```
+/// use telegraph_rs::CloneOptions;
+///
+/// let options = CloneOptions::new().link_original(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CloneOptions {
+    link_original: bool,
+    return_content: bool,
+}
+
+impl CloneOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the author of the copy links to the original page instead of the original author url,
+    /// false by default.
+    pub fn link_original(mut self, link_original: bool) -> Self {
+        self.link_original = link_original;
+        self
+    }
+
+    /// Whether the returned page has its content, false by default.
+    pub fn return_content(mut self, return_content: bool) -> Self {
+        self.return_content = return_content;
+        self
+    }
+}
+
+impl Telegraph {
+    /// Create a copy of a page under this account, see [`Telegraph::clone_page_with_options`]
+    pub async fn clone_page(&self, source: &str) -> Result<Page> {
+        self.clone_page_with_options(source, &CloneOptions::new())
+            .await
+    }
+
+    /// Create a copy of the page at `source` under this account,
+    /// with the same title, author and content
+    ///
+    /// `source` is a page url like `https://telegra.ph/Sample-Page-12-15` or a path like `Sample-Page-12-15`,
+    /// other urls fail with [`Error::InvalidPageUrl`].
+    /// Content failing [`validate_nodes`] fails with [`Error::InvalidContent`],
+    /// and content is checked like in [`Telegraph::create_page`].
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{CloneOptions, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let options = CloneOptions::new().link_original(true);
+    /// let page = telegraph
+    ///     .clone_page_with_options("https://telegra.ph/Sample-Page-12-15", &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clone_page_with_options(
+        &self,
+        source: &str,
+        options: &CloneOptions,
+    ) -> Result<Page> {
+        let path = page_path(source).ok_or_else(|| Error::InvalidPageUrl(source.to_owned()))?;
+        self.copy_page(self, &path, options).await
+    }
+
+    /// Move the page at `path` from the account of `from` to the account of `to`
+    ///
+    /// The page is copied like in [`Telegraph::clone_page`], keeping its author.
+    /// It's read with the client of `from` and created with the client of `to`, which needn't be the same.
+    /// If `unpublish_source` is true, the page of `from` is then blanked like in [`Telegraph::unpublish_page`],
+    /// with a link to the new page. This is checked to be possible before copying,
+    /// failing with [`Error::NotEditable`] if `from` can't edit the page.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::Telegraph;
+    ///
+    /// let from = Telegraph::new("old").access_token("token").create().await?;
+    /// let to = Telegraph::new("new").access_token("other token").create().await?;
+    /// let page = Telegraph::transfer_page(&from, &to, "Sample-Page-12-15", true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transfer_page(
+        from: &Telegraph,
+        to: &Telegraph,
+        path: &str,
+        unpublish_source: bool,
+    ) -> Result<Page> {
+        let path = page_path(path).ok_or_else(|| Error::InvalidPageUrl(path.to_owned()))?;
+        let source = if unpublish_source {
+            Some(from.get_editable_page(&path).await?)
+        } else {
+            None
+        };
+        let page = to.copy_page(from, &path, &CloneOptions::new()).await?;
+        if let Some(source) = source {
+            let placeholder = [NodeElement::new("p")
+                .child("This page has moved to ")
+                .child(NodeElement::new("a").with_href(&page.url).child(&*page.url))
+                .into_node()];
+            from.edit_page_nodes(&path, &source.title, &placeholder, false)
+                .await?;
+        }
+        Ok(page)
+    }
+
+    /// Create a copy of the page at `path`, fetched with the client and token of `source`
+    async fn copy_page(
+        &self,
+        source: &Telegraph,
+        path: &str,
+        options: &CloneOptions,
+    ) -> Result<Page> {
+        let page = source.get_own_page(path, true).await?;
+        let content = match page.content {
+            Some(content) if !content.is_empty() => content,
+            _ => return Err(Error::MissingContent(page.path)),
+        };
+        validate_nodes(&content).map_err(Error::InvalidContent)?;
+
+        let author_url = if options.link_original {
+            Some(&page.url)
+        } else {
+            page.author_url.as_ref()
+        };
+        let create = CreatePageOptions::new()
+            .author_name(page.author_name.as_deref().unwrap_or(""))
+            .author_url(author_url.map_or("", |url| url))
+            .return_content(options.return_content);
+        self.post_page(
+            None,
+            &page.title,
+            &nodes_to_content_string(&content),
+            &create,
+        )
+        .await
+    }
+}
+
+/// Path of a telegraph page url like `https://telegra.ph/Sample-Page-12-15` or `Sample-Page-12-15`
+fn page_path(source: &str) -> Option<String> {
+    let url = Url::parse("https://telegra.ph/").ok()?.join(source).ok()?;
+    page_url_path(&url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{client, form_fields, mock_server};
+
+    fn page(path: &str, content: &str) -> Vec<u8> {
+        format!(
+            r#"{{"ok":true,"result":{{"path":"{}","url":"https://telegra.ph/{}","title":"Sample Page","description":"","author_name":"Alice","author_url":"https://t.me/alice","content":{},"views":3}}}}"#,
+            path, path, content
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn clone_page() {
+        let content = r#"[{"tag":"p","children":["Hello, ",{"tag":"b","children":["world"]}]}]"#;
+        let large = format!(r#"["{}"]"#, "a".repeat(crate::MAX_CONTENT_SIZE));
+        let (addr, requests) = mock_server(vec![
+            (
+                "GET /getPage/Sample",
+                "application/json",
+                page("Sample", content),
+            ),
+            (
+                "GET /getPage/Invalid",
+                "application/json",
+                page("Invalid", r#"[{"tag":"script"}]"#),
+            ),
+            (
+                "GET /getPage/Large",
+                "application/json",
+                page("Large", &large),
+            ),
+            (
+                "POST /createPage",
+                "application/json",
+                page("Sample-2", content),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        let cloned = telegraph
+            .clone_page("https://telegra.ph/Sample")
+            .await
+            .unwrap();
+        assert_eq!(cloned.path, "Sample-2");
+        let options = CloneOptions::new().link_original(true);
+        telegraph
+            .clone_page_with_options("Sample", &options)
+            .await
+            .unwrap();
+        let sent = requests.lock().unwrap().split_off(0);
+        assert!(sent[0].starts_with("GET /getPage/Sample?access_token=token&return_content=true "));
+        let expected = [
+            ("access_token", "token"),
+            ("title", "Sample Page"),
+            ("author_name", "Alice"),
+            ("author_url", "https://t.me/alice"),
+            ("content", content),
+            ("return_content", "false"),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(form_fields(&sent[1]), expected);
+        assert!(sent[2].starts_with("GET /getPage/Sample?"));
+        assert_eq!(
+            form_fields(&sent[3])[3],
+            (
+                "author_url".to_owned(),
+                "https://telegra.ph/Sample".to_owned()
+            )
+        );
+
+        let invalid = telegraph.clone_page("https://graph.org/Invalid").await;
+        assert!(matches!(invalid, Err(Error::InvalidContent(_))));
+        let large = telegraph.clone_page("/Large").await;
+        assert!(matches!(large, Err(Error::ContentTooLarge { .. })));
+        for source in ["https://example.com/Sample", "https://telegra.ph/", "a/b"] {
+            let result = telegraph.clone_page(source).await;
+            assert!(matches!(result, Err(Error::InvalidPageUrl(url)) if url == source));
+        }
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|request| request.starts_with("GET")));
+    }
+
+    #[tokio::test]
+    async fn transfer_page() {
+        let content = r#"[{"tag":"p","children":["Hello"]}]"#;
+        let (addr, requests) = mock_server(vec![
+            (
+                "GET /getPage/Sample?access_token=old&return_content=false",
+                "application/json",
+                br#"{"ok":true,"result":{"path":"Sample","url":"https://telegra.ph/Sample","title":"Sample Page","description":"","views":3,"can_edit":true}}"#.to_vec(),
+            ),
+            (
+                "GET /getPage/Sample?access_token=new&return_content=false",
+                "application/json",
+                br#"{"ok":true,"result":{"path":"Sample","url":"https://telegra.ph/Sample","title":"Sample Page","description":"","views":3,"can_edit":false}}"#.to_vec(),
+            ),
+            ("GET /getPage/Sample?", "application/json", page("Sample", content)),
+            ("POST /createPage", "application/json", page("Sample-2", content)),
+            ("POST /editPage", "application/json", page("Sample", "[]")),
+        ])
+        .await;
+        let account = |token: &'static str, client: reqwest::Client| {
+            Telegraph::new(token)
+                .access_token(token)
+                .api_url(&addr)
+                .client(client)
+                .create()
+        };
+        let shared = reqwest::Client::new();
+        let from = account("old", shared.clone()).await.unwrap();
+        let to = account("new", shared).await.unwrap();
+        let page = Telegraph::transfer_page(&from, &to, "Sample", false)
+            .await
+            .unwrap();
+        assert_eq!(page.path, "Sample-2");
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 2);
+        assert!(sent[0].starts_with("GET /getPage/Sample?access_token=old&return_content=true "));
+        let create = form_fields(&sent[1]);
+        assert_eq!(create[0], ("access_token".to_owned(), "new".to_owned()));
+        assert_eq!(create[2], ("author_name".to_owned(), "Alice".to_owned()));
+        assert_eq!(create[4], ("content".to_owned(), content.to_owned()));
+
+        // each account sends its requests with its own client
+        let client = |name: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert("x-account", name.parse().unwrap());
+            reqwest::Client::builder()
+                .default_headers(headers)
+                .build()
+                .unwrap()
+        };
+        let from = account("old", client("old")).await.unwrap();
+        let to = account("new", client("new")).await.unwrap();
+        let page = Telegraph::transfer_page(&from, &to, "https://telegra.ph/Sample", true)
+            .await
+            .unwrap();
+        assert_eq!(page.url, "https://telegra.ph/Sample-2");
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 4);
+        assert!(sent[0].starts_with("GET /getPage/Sample?access_token=old&return_content=false "));
+        assert!(sent[1].starts_with("GET /getPage/Sample?access_token=old&return_content=true "));
+        for (request, account) in sent.iter().zip(["old", "old", "new", "old"]) {
+            assert!(request.contains(&format!("x-account: {}\r\n", account)));
+        }
+        assert_eq!(form_fields(&sent[2])[0].1, "new");
+        let edit = form_fields(&sent[3]);
+        assert_eq!(edit[0], ("access_token".to_owned(), "old".to_owned()));
+        assert_eq!(edit[1], ("path".to_owned(), "Sample".to_owned()));
+        assert_eq!(edit[2], ("title".to_owned(), "Sample Page".to_owned()));
+        assert_eq!(
+            edit[5].1,
+            r#"[{"tag":"p","children":["This page has moved to ",{"tag":"a","attrs":{"href":"https://telegra.ph/Sample-2"},"children":["https://telegra.ph/Sample-2"]}]}]"#
+        );
+
+        let result = Telegraph::transfer_page(&to, &from, "Sample", true).await;
+        assert!(matches!(result, Err(Error::NotEditable(path)) if path == "Sample"));
+        assert_eq!(requests.lock().unwrap().split_off(0).len(), 1);
+    }
+}
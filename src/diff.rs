@@ -0,0 +1,343 @@
+//! Compare node trees
+use crate::{node_to_text, tree::text_content, Node};
+use std::fmt;
+
+/// Change of a top level node, see [`diff_nodes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockChange<'a> {
+    /// The node at `old` is equal to the one at `new`
+    Unchanged { old: usize, new: usize },
+    /// The node was inserted at `new`
+    Inserted { new: usize, node: &'a Node },
+    /// The node at `old` was removed
+    Removed { old: usize, node: &'a Node },
+    /// The node at `old` was changed into the one at `new`, with the changes of its text
+    Modified {
+        old: usize,
+        new: usize,
+        text: Vec<TextChange>,
+    },
+}
+
+/// Change of the text of a modified node, by words
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextChange {
+    Equal(String),
+    Inserted(String),
+    Removed(String),
+}
+
+/// Differences between two node trees, see [`diff_nodes`]
+///
+/// Its `Display` lists the changed nodes, like `+ 2: New paragraph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeDiff<'a> {
+    pub changes: Vec<BlockChange<'a>>,
+}
+
+impl NodeDiff<'_> {
+    /// Whether the trees are equal
+    pub fn is_unchanged(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|change| matches!(change, BlockChange::Unchanged { .. }))
+    }
+}
+
+impl fmt::Display for NodeDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            match change {
+                BlockChange::Unchanged { .. } => {}
+                BlockChange::Inserted { new, node } => {
+                    writeln!(f, "+ {}: {}", new, summary(node))?;
+                }
+                BlockChange::Removed { old, node } => {
+                    writeln!(f, "- {}: {}", old, summary(node))?;
+                }
+                BlockChange::Modified { old, new, text } => {
+                    write!(f, "~ {} -> {}: ", old, new)?;
+                    for change in text {
+                        match change {
+                            TextChange::Equal(text) => write!(f, "{}", text)?,
+                            TextChange::Inserted(text) => write!(f, "{{+{}+}}", text)?,
+                            TextChange::Removed(text) => write!(f, "[-{}-]", text)?,
+                        }
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether two node trees are equal
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, nodes_equal};
+///
+/// let a = html_to_nodes("<p>Hello</p>").unwrap();
+/// let b = html_to_nodes("<p>Hello</p>").unwrap();
+/// assert!(nodes_equal(&a, &b));
+/// ```
+pub fn nodes_equal(a: &[Node], b: &[Node]) -> bool {
+    a == b
+}
+
+/// Compare the top level nodes of `a` and `b`
+///
+/// Removed and inserted nodes at the same position are reported as modified if both are text
+/// or elements with the same tag, along with a word diff of their text.
+///
+/// ```
+/// use telegraph_rs::{diff_nodes, html_to_nodes};
+///
+/// let a = html_to_nodes("<p>Hello world</p><p>Bye</p>").unwrap();
+/// let b = html_to_nodes("<p>Hello there</p><p>Bye</p><p>New</p>").unwrap();
+/// let diff = diff_nodes(&a, &b);
+/// assert_eq!(diff.to_string(), "~ 0 -> 0: Hello [-world-]{+there+}\n+ 2: New\n");
+/// ```
+pub fn diff_nodes<'a>(a: &'a [Node], b: &'a [Node]) -> NodeDiff<'a> {
+    let mut changes = vec![];
+    let mut removed = vec![];
+    let mut inserted = vec![];
+    for op in diff_slices(a, b) {
+        match op {
+            Op::Equal(old, new) => {
+                pair_changes(a, b, &mut removed, &mut inserted, &mut changes);
+                changes.push(BlockChange::Unchanged { old, new });
+            }
+            Op::Removed(old) => removed.push(old),
+            Op::Inserted(new) => inserted.push(new),
+        }
+    }
+    pair_changes(a, b, &mut removed, &mut inserted, &mut changes);
+    NodeDiff { changes }
+}
+
+/// Turn a run of removed and inserted nodes into changes
+fn pair_changes<'a>(
+    a: &'a [Node],
+    b: &'a [Node],
+    removed: &mut Vec<usize>,
+    inserted: &mut Vec<usize>,
+    changes: &mut Vec<BlockChange<'a>>,
+) {
+    let mut unpaired_removed = vec![];
+    let mut unpaired_inserted = vec![];
+    for i in 0..removed.len().max(inserted.len()) {
+        match (removed.get(i), inserted.get(i)) {
+            (Some(&old), Some(&new)) if same_kind(&a[old], &b[new]) => {
+                let text = diff_text(&plain_text(&a[old]), &plain_text(&b[new]));
+                changes.push(BlockChange::Modified { old, new, text });
+            }
+            (old, new) => {
+                unpaired_removed.extend(old);
+                unpaired_inserted.extend(new);
+            }
+        }
+    }
+    changes.extend(
+        unpaired_removed
+            .into_iter()
+            .map(|old| BlockChange::Removed { old, node: &a[old] }),
+    );
+    changes.extend(
+        unpaired_inserted
+            .into_iter()
+            .map(|new| BlockChange::Inserted { new, node: &b[new] }),
+    );
+    removed.clear();
+    inserted.clear();
+}
+
+fn same_kind(a: &Node, b: &Node) -> bool {
+    match (a, b) {
+        (Node::Text(_), Node::Text(_)) => true,
+        (Node::NodeElement(a), Node::NodeElement(b)) => a.tag == b.tag,
+        _ => false,
+    }
+}
+
+fn plain_text(node: &Node) -> String {
+    let mut text = String::new();
+    text_content(std::slice::from_ref(node), &mut text);
+    text
+}
+
+/// First line of the text of `node`, shortened to 60 characters
+fn summary(node: &Node) -> String {
+    let text = node_to_text(std::slice::from_ref(node));
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(60) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_owned(),
+    }
+}
+
+/// Diff the words of two texts, whitespace is kept as separate words
+fn diff_text(a: &str, b: &str) -> Vec<TextChange> {
+    fn words(text: &str) -> Vec<&str> {
+        let mut words = vec![];
+        let mut start = 0;
+        for (i, c) in text.char_indices().skip(1) {
+            let previous = text[..i].chars().next_back().unwrap();
+            if c.is_whitespace() != previous.is_whitespace() {
+                words.push(&text[start..i]);
+                start = i;
+            }
+        }
+        if start < text.len() {
+            words.push(&text[start..]);
+        }
+        words
+    }
+
+    let (a, b) = (words(a), words(b));
+    let mut changes: Vec<TextChange> = vec![];
+    for op in diff_slices(&a, &b) {
+        let (word, change): (&str, fn(String) -> TextChange) = match op {
+            Op::Equal(i, _) => (a[i], TextChange::Equal),
+            Op::Removed(i) => (a[i], TextChange::Removed),
+            Op::Inserted(j) => (b[j], TextChange::Inserted),
+        };
+        match (changes.last_mut(), change(String::new())) {
+            (Some(TextChange::Equal(text)), TextChange::Equal(_))
+            | (Some(TextChange::Removed(text)), TextChange::Removed(_))
+            | (Some(TextChange::Inserted(text)), TextChange::Inserted(_)) => text.push_str(word),
+            _ => changes.push(change(word.to_owned())),
+        }
+    }
+    changes
+}
+
+enum Op {
+    Equal(usize, usize),
+    Removed(usize),
+    Inserted(usize),
+}
+
+/// Longest common subsequence diff of two slices, removals come before insertions
+fn diff_slices<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    // lengths[i][j] is the length of the common subsequence of a_mid[i..] and b_mid[j..]
+    let mut lengths = vec![vec![0usize; b_mid.len() + 1]; a_mid.len() + 1];
+    for i in (0..a_mid.len()).rev() {
+        for j in (0..b_mid.len()).rev() {
+            lengths[i][j] = match a_mid[i] == b_mid[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut ops = (0..prefix).map(|i| Op::Equal(i, i)).collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < a_mid.len() || j < b_mid.len() {
+        if i < a_mid.len() && j < b_mid.len() && a_mid[i] == b_mid[j] {
+            ops.push(Op::Equal(prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if i < a_mid.len() && (j == b_mid.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            ops.push(Op::Removed(prefix + i));
+            i += 1;
+        } else {
+            ops.push(Op::Inserted(prefix + j));
+            j += 1;
+        }
+    }
+    let (a_end, b_end) = (a.len() - suffix, b.len() - suffix);
+    ops.extend((0..suffix).map(|k| Op::Equal(a_end + k, b_end + k)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes(json: serde_json::Value) -> Vec<Node> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn p(text: &str) -> serde_json::Value {
+        serde_json::json!({"tag": "p", "children": [text]})
+    }
+
+    #[test]
+    fn insert_in_middle() {
+        let a = nodes(serde_json::json!([p("one"), p("two"), p("three")]));
+        let b = nodes(serde_json::json!([
+            p("one"),
+            p("new"),
+            p("two"),
+            p("three")
+        ]));
+        let diff = diff_nodes(&a, &b);
+        assert_eq!(
+            diff.changes,
+            [
+                BlockChange::Unchanged { old: 0, new: 0 },
+                BlockChange::Inserted {
+                    new: 1,
+                    node: &b[1]
+                },
+                BlockChange::Unchanged { old: 1, new: 2 },
+                BlockChange::Unchanged { old: 2, new: 3 },
+            ]
+        );
+        assert_eq!(diff.to_string(), "+ 1: new\n");
+        assert!(!nodes_equal(&a, &b));
+        assert!(diff_nodes(&a, &a).is_unchanged());
+    }
+
+    #[test]
+    fn attribute_change() {
+        let a = nodes(serde_json::json!([
+            {"tag": "p", "children": [{"tag": "a", "attrs": {"href": "https://a.com/"}, "children": ["a link"]}]},
+        ]));
+        let b = nodes(serde_json::json!([
+            {"tag": "p", "children": [{"tag": "a", "attrs": {"href": "https://b.com/"}, "children": ["a link"]}]},
+        ]));
+        let diff = diff_nodes(&a, &b);
+        assert_eq!(
+            diff.changes,
+            [BlockChange::Modified {
+                old: 0,
+                new: 0,
+                text: vec![TextChange::Equal("a link".to_owned())]
+            }]
+        );
+        assert!(!diff.is_unchanged());
+    }
+
+    #[test]
+    fn reorder() {
+        let a = nodes(serde_json::json!([p("one"), p("two"), {"tag": "hr"}]));
+        let b = nodes(serde_json::json!([{"tag": "hr"}, p("one"), p("two")]));
+        let diff = diff_nodes(&a, &b);
+        assert_eq!(
+            diff.changes,
+            [
+                BlockChange::Inserted {
+                    new: 0,
+                    node: &b[0]
+                },
+                BlockChange::Unchanged { old: 0, new: 1 },
+                BlockChange::Unchanged { old: 1, new: 2 },
+                BlockChange::Removed {
+                    old: 2,
+                    node: &a[2]
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Short constructors of common elements
+//!
+//! The functions return [`Node`]s, so they nest into each other and collect into the `Vec<Node>` of a page.
+//! They are not re-exported at the crate root, import them from this module.
+//!
+//! ```
+//! # async fn run() -> Result<(), telegraph_rs::Error> {
+//! use telegraph_rs::{dsl::*, validate_nodes, Telegraph};
+//!
+//! let article = vec![
+//!     h3("Getting started"),
+//!     p(vec![
+//!         "Install it with ".into(),
+//!         code("cargo add telegraph-rs"),
+//!         ", then read ".into(),
+//!         a("the api docs", "https://telegra.ph/api"),
+//!         ".".into(),
+//!     ]),
+//!     img("/file/diagram.png"),
+//!     h4("Steps"),
+//!     ol(vec!["Create an account", "Create a page"]),
+//!     blockquote(vec![bold("Note:"), " tokens can't be recovered".into()]),
+//!     pre("let telegraph = Telegraph::new(\"author\").create().await?;"),
+//!     hr(),
+//! ];
+//! assert!(validate_nodes(&article).is_ok());
+//!
+//! let telegraph = Telegraph::new("author").access_token("token").create().await?;
+//! let content = serde_json::to_string(&article).unwrap();
+//! let page = telegraph.create_page("Getting started", &content, false).await?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::{Node, NodeElement};
+
+/// Build a `Vec<Node>` with a lightweight syntax
+///
+/// Items are separated by commas and can be
+///
+/// - `"text"`, a text node
+/// - `{ expr }`, any value which converts into a [`Node`], like a `String` or the result of a [`dsl`](crate::dsl) function
+/// - `tag[items]`, an element with children, `bold` and `italic` can be used for `strong` and `em` like in the
+///   [`dsl`](crate::dsl) functions
+/// - `tag(expr)`, an element with a single child, usually text
+/// - `tag()`, an element without children, like `hr()`
+/// - `tag(name = value, ...)` and `tag(name = value, ...)[items]`, an element with attributes,
+///   the values are anything which can be borrowed as `str`
+///
+/// ```
+/// use telegraph_rs::nodes;
+///
+/// let name = String::from("world");
+/// let content = nodes![
+///     h3("Title"),
+///     p["Hello ", bold(name.clone()), "!"],
+///     ul[li("a"), li["b ", a(href = "https://telegra.ph/")["link"]]],
+///     figure[img(src = "/file/a.jpg"), figcaption("A picture")],
+///     hr(),
+///     { telegraph_rs::dsl::pre("let x = 1;") },
+/// ];
+/// assert_eq!(
+///     serde_json::to_string(&content[1]).unwrap(),
+///     r#"{"tag":"p","children":["Hello ",{"tag":"strong","children":["world"]},"!"]}"#
+/// );
+/// assert_eq!(content.len(), 6);
+/// ```
+///
+/// Items must be separated by commas:
+///
+/// ```compile_fail
+/// use telegraph_rs::nodes;
+///
+/// let content = nodes![p["a"] p["b"]];
+/// ```
+///
+/// And children are given in brackets, not as further arguments:
+///
+/// ```compile_fail
+/// use telegraph_rs::nodes;
+///
+/// let content = nodes![p("a", "b")];
+/// ```
+#[macro_export]
+macro_rules! nodes {
+    (@munch [$($out:expr),*]) => {
+        ::std::vec![$($out),*] as ::std::vec::Vec<$crate::Node>
+    };
+    (@munch [$($out:expr),*] $tag:ident ( $($attr:ident = $value:expr),+ $(,)? ) [ $($children:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::nodes!(@munch [$($out,)* $crate::NodeElement::new($crate::dsl::macro_tag(stringify!($tag)))
+            $(.attr(stringify!($attr), ::std::convert::AsRef::<str>::as_ref(&$value)))+
+            .children($crate::nodes![$($children)*])
+            .into_node()] $($($rest)*)?)
+    };
+    (@munch [$($out:expr),*] $tag:ident ( $($attr:ident = $value:expr),+ $(,)? ) $(, $($rest:tt)*)?) => {
+        $crate::nodes!(@munch [$($out,)* $crate::NodeElement::new($crate::dsl::macro_tag(stringify!($tag)))
+            $(.attr(stringify!($attr), ::std::convert::AsRef::<str>::as_ref(&$value)))+
+            .into_node()] $($($rest)*)?)
+    };
+    (@munch [$($out:expr),*] $tag:ident ( ) $(, $($rest:tt)*)?) => {
+        $crate::nodes!(@munch [$($out,)* $crate::NodeElement::new($crate::dsl::macro_tag(stringify!($tag))).into_node()] $($($rest)*)?)
+    };
+    (@munch [$($out:expr),*] $tag:ident ( $child:expr ) $(, $($rest:tt)*)?) => {
+        $crate::nodes!(@munch [$($out,)* $crate::NodeElement::new($crate::dsl::macro_tag(stringify!($tag))).child($child).into_node()] $($($rest)*)?)
+    };
+    (@munch [$($out:expr),*] $tag:ident [ $($children:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::nodes!(@munch [$($out,)* $crate::NodeElement::new($crate::dsl::macro_tag(stringify!($tag)))
+            .children($crate::nodes![$($children)*])
+            .into_node()] $($($rest)*)?)
+    };
+    (@munch [$($out:expr),*] $text:literal $(, $($rest:tt)*)?) => {
+        $crate::nodes!(@munch [$($out,)* $crate::Node::from($text)] $($($rest)*)?)
+    };
+    (@munch [$($out:expr),*] { $node:expr } $(, $($rest:tt)*)?) => {
+        $crate::nodes!(@munch [$($out,)* $crate::Node::from($node)] $($($rest)*)?)
+    };
+    (@munch [$($out:expr),*] $($rest:tt)*) => {
+        ::std::compile_error!(::std::concat!("invalid nodes! item at `", ::std::stringify!($($rest)*), "`"))
+    };
+    ($($items:tt)*) => {
+        $crate::nodes!(@munch [] $($items)*)
+    };
+}
+
+/// Tag of an element in [`nodes!`](crate::nodes)
+#[doc(hidden)]
+pub fn macro_tag(name: &str) -> &str {
+    match name {
+        "bold" => "strong",
+        "italic" => "em",
+        _ => name,
+    }
+}
+
+/// An element with `children`
+fn element<I>(tag: &str, children: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    NodeElement::new(tag).children(children).into_node()
+}
+
+/// A `p` paragraph
+pub fn p<I>(children: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    element("p", children)
+}
+
+/// A `h3` heading, the larger one
+pub fn h3(text: &str) -> Node {
+    element("h3", [text])
+}
+
+/// A `h4` heading
+pub fn h4(text: &str) -> Node {
+    element("h4", [text])
+}
+
+/// A link to `href`
+pub fn a(text: &str, href: &str) -> Node {
+    NodeElement::new("a")
+        .with_href(href)
+        .child(text)
+        .into_node()
+}
+
+/// An image showing `src`
+pub fn img(src: &str) -> Node {
+    NodeElement::img(src).into_node()
+}
+
+/// An image showing `src` in a `figure`, with a `figcaption` holding `caption` unless it is empty
+///
+/// ```
+/// use telegraph_rs::dsl::{a, figure};
+///
+/// let plain = figure("/file/cat.jpg", Some("A cat"));
+/// let linked = figure("/file/cat.jpg", vec!["By ".into(), a("someone", "https://example.com/")]);
+/// let bare = figure("/file/cat.jpg", None::<&str>);
+/// ```
+pub fn figure<I>(src: &str, caption: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    Node::figure_with(src, caption)
+}
+
+/// A video playing `src`, usually an uploaded mp4
+pub fn video(src: &str) -> Node {
+    Node::video(src)
+}
+
+/// An embed of `url` by the Telegraph `service`, like `youtube`, see [`Node::embed`]
+pub fn embed(service: &str, url: &str) -> Node {
+    Node::embed(service, url)
+}
+
+/// Bold text, a `strong` element
+pub fn bold(text: &str) -> Node {
+    element("strong", [text])
+}
+
+/// Italic text, an `em` element
+pub fn italic(text: &str) -> Node {
+    element("em", [text])
+}
+
+/// An unordered list, items which are not `li` elements are wrapped into one
+///
+/// ```
+/// use telegraph_rs::dsl::{italic, li, ul};
+///
+/// let list = ul(vec![li(vec![italic("one")]), "two".into()]);
+/// let json = serde_json::to_string(&list).unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"tag":"ul","children":[{"tag":"li","children":[{"tag":"em","children":["one"]}]},{"tag":"li","children":["two"]}]}"#
+/// );
+/// ```
+pub fn ul<I>(items: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    list("ul", items)
+}
+
+/// An ordered list, items which are not `li` elements are wrapped into one
+pub fn ol<I>(items: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    list("ol", items)
+}
+
+fn list<I>(tag: &str, items: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    let items = items.into_iter().map(|item| match item.into() {
+        Node::NodeElement(element) if element.tag == "li" => Node::NodeElement(element),
+        item => li([item]),
+    });
+    element(tag, items)
+}
+
+/// A list item
+pub fn li<I>(children: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    element("li", children)
+}
+
+/// A `blockquote`
+pub fn blockquote<I>(children: I) -> Node
+where
+    I: IntoIterator,
+    I::Item: Into<Node>,
+{
+    Node::blockquote(children)
+}
+
+/// Inline code
+pub fn code(text: &str) -> Node {
+    Node::code(text)
+}
+
+/// A preformatted block, whose whitespace is kept
+pub fn pre(text: &str) -> Node {
+    element("pre", [text])
+}
+
+/// A horizontal rule
+pub fn hr() -> Node {
+    NodeElement::hr().into_node()
+}
+
+/// A line break
+pub fn br() -> Node {
+    NodeElement::br().into_node()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(node: Node) -> String {
+        serde_json::to_string(&node).unwrap()
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!(json(h3("Title")), r#"{"tag":"h3","children":["Title"]}"#);
+        assert_eq!(
+            json(a("link", "https://telegra.ph/")),
+            r#"{"tag":"a","attrs":{"href":"https://telegra.ph/"},"children":["link"]}"#
+        );
+        assert_eq!(
+            json(img("/file/a.jpg")),
+            r#"{"tag":"img","attrs":{"src":"/file/a.jpg"}}"#
+        );
+        assert_eq!(json(p(Vec::<Node>::new())), r#"{"tag":"p"}"#);
+        assert_eq!(
+            json(p(vec!["a".into(), br(), bold("b"), italic("c")])),
+            r#"{"tag":"p","children":["a",{"tag":"br"},{"tag":"strong","children":["b"]},{"tag":"em","children":["c"]}]}"#
+        );
+        assert_eq!(
+            json(ol(["one", "two"])),
+            r#"{"tag":"ol","children":[{"tag":"li","children":["one"]},{"tag":"li","children":["two"]}]}"#
+        );
+        assert_eq!(json(hr()), r#"{"tag":"hr"}"#);
+    }
+
+    #[test]
+    fn valid_article() {
+        let article = vec![
+            h4("Section"),
+            blockquote(vec![code("x"), " and ".into(), pre("y")]),
+            ul(vec![li(vec![a("a", "/a")]), img("/file/b.png")]),
+        ];
+        assert_eq!(crate::validate_nodes(&article), Ok(()));
+    }
+
+    #[test]
+    fn nodes_macro() {
+        assert_eq!(crate::nodes![], Vec::<Node>::new());
+        let href = String::from("/a");
+        let nodes = crate::nodes![
+            h4("Section"),
+            p["a", { bold("b") }, i(String::from("c")), br(), italic["d"]],
+            ol[li["one"], li(href = href, )],
+            a(href = href)["link"],
+            "trailing",
+        ];
+        assert_eq!(
+            nodes,
+            vec![
+                NodeElement::new("h4").child("Section").into_node(),
+                p(vec![
+                    "a".into(),
+                    bold("b"),
+                    element("i", ["c"]),
+                    br(),
+                    italic("d")
+                ]),
+                ol(vec![
+                    li(["one"]),
+                    NodeElement::new("li").attr("href", "/a").into_node(),
+                ]),
+                a("link", "/a"),
+                "trailing".into(),
+            ]
+        );
+    }
+}
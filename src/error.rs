@@ -1,18 +1,26 @@
 use serde::Deserialize;
 use thiserror::Error;
 
+/// A response of the api, `result` on success and `error` otherwise
+///
+/// This is a struct rather than an untagged enum, so the result is deserialized straight from the response
+/// and raw values in it are kept.
 #[derive(Debug, Deserialize)]
-#[serde(untagged)]
-pub(crate) enum ApiResult<T> {
-    Ok { result: T },
-    Err { ok: bool, error: String },
+pub(crate) struct ApiResult<T> {
+    result: Option<T>,
+    error: Option<String>,
 }
 
-impl<T> Into<Result<T, Error>> for ApiResult<T> {
-    fn into(self) -> Result<T, Error> {
-        match self {
-            ApiResult::Ok { result: v } => Ok(v),
-            ApiResult::Err { error: e, .. } => Err(Error::ApiError(e)),
+impl<T> From<ApiResult<T>> for Result<T, Error> {
+    fn from(response: ApiResult<T>) -> Self {
+        match response {
+            ApiResult {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            ApiResult { error, .. } => Err(Error::ApiError(
+                error.unwrap_or_else(|| "missing result".to_owned()),
+            )),
         }
     }
 }
@@ -25,4 +33,61 @@ pub enum Error {
     ApiError(String),
     #[error("io error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("io error on {}: {source}", .path.display())]
+    FileError {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("asset {} of the node at {node:?}: {source}", .path.display())]
+    AssetError {
+        path: std::path::PathBuf,
+        node: Vec<usize>,
+        source: std::io::Error,
+    },
+    #[error("download limit of {0} bytes exceeded")]
+    DownloadLimit(u64),
+    #[error("content size of {size} bytes exceeds the limit of {limit} bytes")]
+    ContentTooLarge { size: usize, limit: usize },
+    #[error("page {0} has no content")]
+    MissingContent(String),
+    #[error("page {0} can't be edited by this account")]
+    NotEditable(String),
+    #[error("not a telegraph page: {0}")]
+    InvalidPageUrl(String),
+    #[error("no node at path {0:?}")]
+    InvalidPath(Vec<usize>),
+    #[error("invalid content: {}", join_errors(.0))]
+    InvalidContent(Vec<crate::ValidationError>),
+    #[cfg(feature = "html")]
+    #[error("html error: {0}")]
+    HtmlError(#[from] html_parser::Error),
+    #[cfg(feature = "html")]
+    #[error("html is longer than {0} bytes")]
+    HtmlTooLarge(usize),
+    #[cfg(feature = "html")]
+    #[error("html is nested deeper than {0} levels")]
+    HtmlTooDeep(usize),
+    #[error("unsupported charset: {0}")]
+    UnsupportedCharset(String),
+    #[error("the {0} feature is needed for this")]
+    FeatureDisabled(&'static str),
+}
+
+impl Error {
+    /// How long to wait before retrying, if this is a `FLOOD_WAIT_X` error of the api
+    pub fn flood_wait(&self) -> Option<std::time::Duration> {
+        match self {
+            Error::ApiError(error) => error
+                .strip_prefix("FLOOD_WAIT_")?
+                .parse()
+                .ok()
+                .map(std::time::Duration::from_secs),
+            _ => None,
+        }
+    }
+}
+
+fn join_errors(errors: &[crate::ValidationError]) -> String {
+    let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    errors.join("; ")
 }
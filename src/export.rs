@@ -0,0 +1,656 @@
+//! Export pages into local files
+use crate::{
+    extract_images, node_to_html, node_to_markdown, rewrite_links, utils::hosted_file, Error, Node,
+    Page, Result, Telegraph,
+};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Name of the manifest written by [`Telegraph::export_all`]
+pub const MANIFEST_FILE: &str = "index.json";
+
+/// Directory of the images downloaded by [`Telegraph::export_all`]
+pub const ASSETS_DIR: &str = "assets";
+
+/// Format of exported pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// Rendered with [`node_to_markdown`], into a `.md` file
+    #[default]
+    Markdown,
+    /// Rendered with [`node_to_html`], into a `.html` file
+    Html,
+}
+
+impl ExportFormat {
+    /// Extension of exported files, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// Options for [`Telegraph::export_page_with_options`]
+///
+/// ```
+/// use telegraph_rs::{ExportFormat, ExportOptions};
+///
+/// let options = ExportOptions::new().format(ExportFormat::Html).overwrite(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    format: ExportFormat,
+    overwrite: bool,
+}
+
+impl ExportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Format of the exported file, [`ExportFormat::Markdown`] by default.
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Whether to truncate an existing file instead of failing, false by default.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+}
+
+/// Options for [`Telegraph::export_all`]
+///
+/// ```
+/// use telegraph_rs::{ExportAllOptions, ExportFormat};
+///
+/// let options = ExportAllOptions::new()
+///     .format(ExportFormat::Html)
+///     .concurrency(8)
+///     .assets(true)
+///     .resume(true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExportAllOptions {
+    format: ExportFormat,
+    concurrency: usize,
+    assets: bool,
+    resume: bool,
+}
+
+impl Default for ExportAllOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            concurrency: 4,
+            assets: false,
+            resume: false,
+        }
+    }
+}
+
+impl ExportAllOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Format of the exported files, [`ExportFormat::Markdown`] by default.
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Number of pages fetched at the same time, 4 by default.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Whether to download images hosted by Telegraph into [`ASSETS_DIR`]
+    /// and point the exported pages to them, false by default.
+    pub fn assets(mut self, assets: bool) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    /// Whether to skip pages and assets whose file already exists, false by default.
+    ///
+    /// Otherwise existing files are overwritten.
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+}
+
+/// Result of [`Telegraph::export_all`]
+#[derive(Debug, Default)]
+pub struct ExportReport {
+    /// Paths of the exported pages
+    pub exported: Vec<String>,
+    /// Paths of the pages skipped because their file already exists
+    pub skipped: Vec<String>,
+    /// Downloaded assets, relative to the export directory
+    pub assets: Vec<String>,
+    /// Paths of pages or urls of assets which failed to export
+    pub errors: Vec<(String, Error)>,
+}
+
+impl ExportReport {
+    fn extend(&mut self, other: ExportReport) {
+        self.exported.extend(other.exported);
+        self.skipped.extend(other.skipped);
+        self.assets.extend(other.assets);
+        self.errors.extend(other.errors);
+    }
+}
+
+/// A page in the [`MANIFEST_FILE`] of an export, keyed by the page path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub title: String,
+    pub url: String,
+    /// The exported file, relative to the export directory
+    pub file: String,
+}
+
+/// Metadata of a page written at the top of exported files
+///
+/// It is rendered between `---` lines, with one `key: value` per line and values as JSON,
+/// which is also valid YAML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatter {
+    pub title: String,
+    pub author_name: Option<String>,
+    pub author_url: Option<String>,
+    pub url: String,
+    pub views: i32,
+    pub path: String,
+}
+
+impl FrontMatter {
+    pub fn from_page(page: &Page) -> Self {
+        Self {
+            title: page.title.clone(),
+            author_name: page.author_name.clone(),
+            author_url: page.author_url.clone(),
+            url: page.url.clone(),
+            views: page.views,
+            path: page.path.clone(),
+        }
+    }
+
+    /// Split the front matter off an exported file, returning it and the body
+    ///
+    /// Returns `None` if the text does not start with front matter or the title, url or path is missing.
+    /// Unknown keys are ignored.
+    ///
+    /// ```
+    /// use telegraph_rs::FrontMatter;
+    ///
+    /// let text = "---\ntitle: \"Hello\"\nurl: \"https://telegra.ph/Hello-01-01\"\npath: \"Hello-01-01\"\n---\n\nBody";
+    /// let (front_matter, body) = FrontMatter::parse(text).unwrap();
+    /// assert_eq!(front_matter.title, "Hello");
+    /// assert_eq!(front_matter.views, 0);
+    /// assert_eq!(body, "Body");
+    /// ```
+    pub fn parse(text: &str) -> Option<(Self, &str)> {
+        let rest = text.strip_prefix("---\n")?;
+        let end = rest.find("\n---\n")?;
+        let (mut title, mut url, mut path) = (None, None, None);
+        let mut front_matter = Self {
+            title: String::new(),
+            author_name: None,
+            author_url: None,
+            url: String::new(),
+            views: 0,
+            path: String::new(),
+        };
+        for line in rest[..end].lines() {
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => continue,
+            };
+            match key {
+                "title" => title = serde_json::from_str(value).ok(),
+                "author_name" => front_matter.author_name = serde_json::from_str(value).ok(),
+                "author_url" => front_matter.author_url = serde_json::from_str(value).ok(),
+                "url" => url = serde_json::from_str(value).ok(),
+                "views" => front_matter.views = value.parse().unwrap_or_default(),
+                "path" => path = serde_json::from_str(value).ok(),
+                _ => {}
+            }
+        }
+        front_matter.title = title?;
+        front_matter.url = url?;
+        front_matter.path = path?;
+        let body = &rest[end + 5..];
+        Some((front_matter, body.strip_prefix('\n').unwrap_or(body)))
+    }
+}
+
+impl fmt::Display for FrontMatter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = |s: &str| serde_json::to_string(s).unwrap();
+        writeln!(f, "---")?;
+        writeln!(f, "title: {}", string(&self.title))?;
+        if let Some(author_name) = &self.author_name {
+            writeln!(f, "author_name: {}", string(author_name))?;
+        }
+        if let Some(author_url) = &self.author_url {
+            writeln!(f, "author_url: {}", string(author_url))?;
+        }
+        writeln!(f, "url: {}", string(&self.url))?;
+        writeln!(f, "views: {}", self.views)?;
+        writeln!(f, "path: {}", string(&self.path))?;
+        writeln!(f, "---")
+    }
+}
+
+/// Render `page` with its [`FrontMatter`] followed by a blank line and the content in `format`
+///
+/// Fails with [`Error::MissingContent`] if the page was fetched without its content.
+pub fn export_page_to_string(page: &Page, format: ExportFormat) -> Result<String> {
+    let content = page
+        .content
+        .as_deref()
+        .ok_or_else(|| Error::MissingContent(page.path.clone()))?;
+    let body = match format {
+        ExportFormat::Markdown => node_to_markdown(content),
+        ExportFormat::Html => node_to_html(content),
+    };
+    Ok(format!("{}\n{}\n", FrontMatter::from_page(page), body))
+}
+
+/// Write `contents` to `file`, creating its parent directories
+pub(crate) fn write_file(file: &Path, contents: &[u8], overwrite: bool) -> Result<()> {
+    let file_error = |source| Error::FileError {
+        path: file.to_owned(),
+        source,
+    };
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent).map_err(file_error)?;
+    }
+    let mut options = OpenOptions::new();
+    match overwrite {
+        true => options.write(true).create(true).truncate(true),
+        false => options.write(true).create_new(true),
+    };
+    let mut output = options.open(file).map_err(file_error)?;
+    output.write_all(contents).map_err(file_error)
+}
+
+/// The file a page is exported to, `<path>.<extension>` inside `dest` if it is a directory, or `dest` itself
+///
+/// `dest` is a directory if it exists as one, ends with a separator or has no extension.
+fn export_file(path: &str, dest: &Path, format: ExportFormat) -> PathBuf {
+    let is_dir = dest.is_dir()
+        || dest.extension().is_none()
+        || dest
+            .as_os_str()
+            .to_string_lossy()
+            .ends_with(std::path::is_separator);
+    match is_dir {
+        true => dest.join(format!("{}.{}", path, format.extension())),
+        false => dest.to_owned(),
+    }
+}
+
+impl Telegraph {
+    /// Fetch the page at `path` and write it to `dest` in `format`, see [`Telegraph::export_page_with_options`]
+    ///
+    /// Fails if the file already exists.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use std::path::Path;
+    /// use telegraph_rs::{ExportFormat, Telegraph};
+    ///
+    /// // writes backup/Sample-Page-12-15.md
+    /// Telegraph::export_page("Sample-Page-12-15", Path::new("backup"), ExportFormat::Markdown).await?;
+    /// // writes sample.md
+    /// Telegraph::export_page("Sample-Page-12-15", Path::new("sample.md"), ExportFormat::Markdown).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_page(path: &str, dest: &Path, format: ExportFormat) -> Result<()> {
+        Self::export_page_with_options(path, dest, &ExportOptions::new().format(format)).await
+    }
+
+    /// Fetch the page at `path` and write it with [`export_page_to_string`]
+    ///
+    /// If `dest` exists as a directory, ends with a separator or has no extension,
+    /// it's taken as a directory and the file in it is named after the page path, like `Sample-Page-12-15.md`.
+    /// Missing parent directories are created.
+    pub async fn export_page_with_options(
+        path: &str,
+        dest: &Path,
+        options: &ExportOptions,
+    ) -> Result<()> {
+        let page = Self::get_page(path, true).await?;
+        let text = export_page_to_string(&page, options.format)?;
+        write_file(
+            &export_file(path, dest, options.format),
+            text.as_bytes(),
+            options.overwrite,
+        )
+    }
+
+    /// Export every page of the account into `dir`
+    ///
+    /// Pages are written like [`Telegraph::export_page`] into `<path>.<extension>` files, along with
+    /// a [`MANIFEST_FILE`] mapping the path of every exported page to its [`ManifestEntry`].
+    ///
+    /// Failures of single pages or assets are collected in the report instead of aborting the export.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use std::path::Path;
+    /// use telegraph_rs::{ExportAllOptions, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let options = ExportAllOptions::new().assets(true).resume(true);
+    /// let report = telegraph.export_all(Path::new("backup"), &options).await?;
+    /// for (path, error) in &report.errors {
+    ///     eprintln!("{}: {}", path, error);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_all(&self, dir: &Path, options: &ExportAllOptions) -> Result<ExportReport> {
+        self.export_all_from("https://telegra.ph", dir, options)
+            .await
+    }
+
+    pub(crate) async fn export_all_from(
+        &self,
+        site_url: &str,
+        dir: &Path,
+        options: &ExportAllOptions,
+    ) -> Result<ExportReport> {
+        let mut pages = vec![];
+        loop {
+            let list = self.get_page_list(pages.len() as i32, 200).await?;
+            let done = list.pages.is_empty();
+            pages.extend(list.pages);
+            if done || pages.len() >= list.total_count as usize {
+                break;
+            }
+        }
+
+        let mut report = ExportReport::default();
+        let reports = stream::iter(&pages)
+            .map(|page| self.export_to(site_url, dir, &page.path, options))
+            .buffered(options.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        for page_report in reports {
+            report.extend(page_report);
+        }
+
+        let manifest = pages
+            .iter()
+            .filter(|page| {
+                report.exported.contains(&page.path) || report.skipped.contains(&page.path)
+            })
+            .map(|page| {
+                let entry = ManifestEntry {
+                    title: page.title.clone(),
+                    url: page.url.clone(),
+                    file: format!("{}.{}", page.path, options.format.extension()),
+                };
+                (page.path.clone(), entry)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let manifest = serde_json::to_string_pretty(&manifest).unwrap();
+        write_file(&dir.join(MANIFEST_FILE), manifest.as_bytes(), true)?;
+        Ok(report)
+    }
+
+    /// Export a single page for [`Telegraph::export_all`]
+    async fn export_to(
+        &self,
+        site_url: &str,
+        dir: &Path,
+        path: &str,
+        options: &ExportAllOptions,
+    ) -> ExportReport {
+        let mut report = ExportReport::default();
+        let file = dir.join(format!("{}.{}", path, options.format.extension()));
+        if options.resume && file.exists() {
+            report.skipped.push(path.to_owned());
+            return report;
+        }
+        let result = async {
+            let mut page = self.get_own_page(path, true).await?;
+            if let (true, Some(content)) = (options.assets, page.content.as_mut()) {
+                self.download_assets(site_url, dir, content, options.resume, &mut report)
+                    .await;
+            }
+            let text = export_page_to_string(&page, options.format)?;
+            write_file(&file, text.as_bytes(), true)
+        }
+        .await;
+        match result {
+            Ok(()) => report.exported.push(path.to_owned()),
+            Err(e) => report.errors.push((path.to_owned(), e)),
+        }
+        report
+    }
+
+    /// Download the images of `content` hosted by Telegraph into [`ASSETS_DIR`] and point them to the local files
+    async fn download_assets(
+        &self,
+        site_url: &str,
+        dir: &Path,
+        content: &mut [Node],
+        resume: bool,
+        report: &mut ExportReport,
+    ) {
+        let mut assets = HashMap::new();
+        for image in extract_images(content) {
+            let file_path = match hosted_file(&image.src) {
+                Some(file_path) if !assets.contains_key(&image.src) => file_path,
+                _ => continue,
+            };
+            let name = file_path.rsplit('/').next().unwrap_or_default();
+            let asset = format!("{}/{}", ASSETS_DIR, name);
+            let file = dir.join(&asset);
+            let result = async {
+                if resume && file.exists() {
+                    return Ok(());
+                }
+                let url = format!("{}{}", site_url, file_path);
+                let response = self.client.get(url).send().await?.error_for_status()?;
+                write_file(&file, &response.bytes().await?, true)
+            }
+            .await;
+            match result {
+                Ok(()) => {
+                    report.assets.push(asset.clone());
+                    assets.insert(image.src, asset);
+                }
+                Err(e) => report.errors.push((image.src, e)),
+            }
+        }
+        rewrite_links(content, |src| assets.get(src).cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::client;
+    use crate::Node;
+
+    fn page() -> Page {
+        serde_json::from_value(serde_json::json!({
+            "path": "Sample-Page-12-15",
+            "url": "https://telegra.ph/Sample-Page-12-15",
+            "title": "Sample \"Page\"",
+            "description": "",
+            "author_name": "Anonymous",
+            "views": 42,
+            "content": [
+                {"tag": "h3", "children": ["Title"]},
+                {"tag": "p", "children": ["Some ", {"tag": "strong", "children": ["bold"]}, " text with a ", {"tag": "a", "attrs": {"href": "https://example.com/"}, "children": ["link"]}]},
+                {"tag": "ul", "children": [{"tag": "li", "children": ["one"]}, {"tag": "li", "children": ["two"]}]},
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn round_trip(format: ExportFormat, import: fn(&str) -> Result<Vec<Node>>) {
+        let page = page();
+        let text = export_page_to_string(&page, format).unwrap();
+        let (front_matter, body) = FrontMatter::parse(&text).unwrap();
+        assert_eq!(front_matter, FrontMatter::from_page(&page));
+        assert_eq!(
+            import(body.trim_end()).unwrap(),
+            page.content.unwrap(),
+            "{}",
+            body
+        );
+    }
+
+    #[test]
+    fn front_matter() {
+        let text = export_page_to_string(&page(), ExportFormat::Markdown).unwrap();
+        assert!(text.starts_with(concat!(
+            "---\n",
+            "title: \"Sample \\\"Page\\\"\"\n",
+            "author_name: \"Anonymous\"\n",
+            "url: \"https://telegra.ph/Sample-Page-12-15\"\n",
+            "views: 42\n",
+            "path: \"Sample-Page-12-15\"\n",
+            "---\n\n### Title\n",
+        )));
+        assert_eq!(FrontMatter::parse("no front matter"), None);
+    }
+
+    #[cfg(feature = "markdown")]
+    #[test]
+    fn markdown_round_trip() {
+        round_trip(ExportFormat::Markdown, crate::markdown_to_nodes);
+    }
+
+    #[cfg(feature = "html")]
+    #[test]
+    fn html_round_trip() {
+        round_trip(ExportFormat::Html, crate::html_to_nodes);
+    }
+
+    #[test]
+    fn write_files() {
+        let dir = std::env::temp_dir().join(format!("telegraph-rs-export-{}", std::process::id()));
+        let file = export_file("Sample-Page-12-15", &dir.join("a/b.md"), ExportFormat::Html);
+        assert_eq!(file, dir.join("a/b.md"));
+        write_file(&file, b"old", false).unwrap();
+        let error = write_file(&file, b"new", false).unwrap_err();
+        assert!(error.to_string().contains("b.md"), "{}", error);
+        write_file(&file, b"new", true).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+
+        let file = export_file("Sample-Page-12-15", &dir, ExportFormat::Html);
+        assert_eq!(file, dir.join("Sample-Page-12-15.html"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // missing directories
+        let file = export_file(
+            "Sample-Page-12-15",
+            &dir.join("backup"),
+            ExportFormat::Markdown,
+        );
+        assert_eq!(file, dir.join("backup/Sample-Page-12-15.md"));
+        let file = export_file(
+            "Sample-Page-12-15",
+            &dir.join("v1.0/"),
+            ExportFormat::Markdown,
+        );
+        assert_eq!(file, dir.join("v1.0/Sample-Page-12-15.md"));
+    }
+
+    #[tokio::test]
+    async fn export_all() {
+        let list = serde_json::json!({"ok": true, "result": {"total_count": 3, "pages": [
+            {"path": "One-01-01", "url": "https://telegra.ph/One-01-01", "title": "One", "description": "", "views": 1},
+            {"path": "Two-01-01", "url": "https://telegra.ph/Two-01-01", "title": "Two", "description": "", "views": 2},
+            {"path": "Three-01-01", "url": "https://telegra.ph/Three-01-01", "title": "Three", "description": "", "views": 3},
+        ]}});
+        let one = serde_json::json!({"ok": true, "result": {
+            "path": "One-01-01", "url": "https://telegra.ph/One-01-01", "title": "One", "description": "", "views": 1,
+            "content": [
+                {"tag": "p", "children": ["A cat"]},
+                {"tag": "img", "attrs": {"src": "/file/cat.jpg"}},
+                {"tag": "img", "attrs": {"src": "/file/missing.jpg"}},
+            ],
+        }});
+        let (addr, requests) = crate::tests::mock_server(vec![
+            (
+                "GET /getPageList",
+                "application/json",
+                list.to_string().into_bytes(),
+            ),
+            (
+                "GET /getPage/One-01-01",
+                "application/json",
+                one.to_string().into_bytes(),
+            ),
+            ("GET /file/cat.jpg", "image/jpeg", b"cat".to_vec()),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let dir =
+            std::env::temp_dir().join(format!("telegraph-rs-export-all-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Two-01-01.md"), "old").unwrap();
+        let options = ExportAllOptions::new().assets(true).resume(true);
+        let report = telegraph
+            .export_all_from(&addr, &dir, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(report.exported, ["One-01-01"]);
+        assert_eq!(report.skipped, ["Two-01-01"]);
+        assert_eq!(report.assets, ["assets/cat.jpg"]);
+        let errors = report
+            .errors
+            .iter()
+            .map(|(path, _)| &**path)
+            .collect::<Vec<_>>();
+        assert_eq!(errors, ["/file/missing.jpg", "Three-01-01"]);
+        assert!(!requests
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|request| request.starts_with("GET /getPage/Two")));
+
+        let text = std::fs::read_to_string(dir.join("One-01-01.md")).unwrap();
+        assert!(
+            text.ends_with("A cat\n\n![](assets/cat.jpg)![](/file/missing.jpg)\n"),
+            "{}",
+            text
+        );
+        assert_eq!(std::fs::read(dir.join("assets/cat.jpg")).unwrap(), b"cat");
+        let manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE)).unwrap();
+        let manifest: BTreeMap<String, ManifestEntry> = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(
+            manifest.keys().collect::<Vec<_>>(),
+            ["One-01-01", "Two-01-01"]
+        );
+        assert_eq!(manifest["Two-01-01"].file, "Two-01-01.md");
+        assert_eq!(manifest["One-01-01"].url, "https://telegra.ph/One-01-01");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
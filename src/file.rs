@@ -0,0 +1,193 @@
+//! Create pages from local files
+use crate::{decode_html, tree::text_content, Error, Node, NodeElement, Page, Result, Telegraph};
+use std::path::Path;
+
+/// Format of a file, detected from its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// `.md` and `.markdown`, converted with [`markdown_to_nodes`](crate::markdown_to_nodes)
+    Markdown,
+    /// `.html` and `.htm`, converted with [`html_to_nodes_bytes_with_options`](crate::html_to_nodes_bytes_with_options)
+    Html,
+    /// Anything else, put into a `pre` element
+    Text,
+}
+
+impl FileFormat {
+    /// Detect the format of `path` from its extension, ignoring case
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match &*extension.to_ascii_lowercase() {
+            "md" | "markdown" => FileFormat::Markdown,
+            "html" | "htm" => FileFormat::Html,
+            _ => FileFormat::Text,
+        }
+    }
+}
+
+/// Read the file at `path` and convert it to nodes according to [`FileFormat::from_path`]
+///
+/// The bytes are decoded with [`decode_html`], so a byte order mark or a declared charset is respected.
+/// Reading errors are reported as [`Error::FileError`] with the path.
+/// Markdown and html files fail with [`Error::FeatureDisabled`] unless the `markdown` and `html` features are enabled.
+pub fn file_to_nodes(path: &Path) -> Result<Vec<Node>> {
+    let bytes = std::fs::read(path).map_err(|source| Error::FileError {
+        path: path.to_owned(),
+        source,
+    })?;
+    text_to_nodes(&decode_html(&bytes, None)?, FileFormat::from_path(path))
+}
+
+/// Convert the decoded text of a file in `format`
+pub(crate) fn text_to_nodes(text: &str, format: FileFormat) -> Result<Vec<Node>> {
+    match format {
+        #[cfg(feature = "markdown")]
+        FileFormat::Markdown => crate::markdown_to_nodes(text),
+        #[cfg(not(feature = "markdown"))]
+        FileFormat::Markdown => Err(Error::FeatureDisabled("markdown")),
+        #[cfg(feature = "html")]
+        FileFormat::Html => {
+            let options = crate::ConversionOptions::sanitized().downgrade_headings(true);
+            crate::html_to_nodes_with_options(text, &options)
+        }
+        #[cfg(not(feature = "html"))]
+        FileFormat::Html => Err(Error::FeatureDisabled("html")),
+        FileFormat::Text => {
+            let text = text.trim_end_matches(['\r', '\n']);
+            let pre = NodeElement::new("pre");
+            let pre = match text.is_empty() {
+                true => pre,
+                false => pre.child(text),
+            };
+            Ok(vec![pre.into_node()])
+        }
+    }
+}
+
+/// Title of a page created from a file, the text of the first top level heading or the file stem
+fn file_title(path: &Path, nodes: &[Node]) -> String {
+    let heading = nodes.iter().find_map(|node| match node {
+        Node::NodeElement(element) if element.tag == "h3" || element.tag == "h4" => {
+            let mut text = String::new();
+            text_content(element.children.as_deref().unwrap_or_default(), &mut text);
+            Some(text.trim().to_owned()).filter(|text| !text.is_empty())
+        }
+        _ => None,
+    });
+    heading.unwrap_or_else(|| {
+        let stem = path.file_stem().unwrap_or_default();
+        stem.to_string_lossy().into_owned()
+    })
+}
+
+impl Telegraph {
+    /// Create a page from the file at `path`, see [`file_to_nodes`]
+    ///
+    /// Without `title`, the text of the first heading or the file stem is used.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use std::path::Path;
+    /// use telegraph_rs::Telegraph;
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let page = telegraph.create_page_from_file(Path::new("notes.md"), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_page_from_file(&self, path: &Path, title: Option<&str>) -> Result<Page> {
+        let nodes = file_to_nodes(path)?;
+        let title = match title {
+            Some(title) => title.to_owned(),
+            None => file_title(path, &nodes),
+        };
+        self.create_page_from(&title, nodes, false).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Write `bytes` to a file named `name` in a temporary directory
+    fn fixture(name: &str, bytes: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("telegraph-rs-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn convert(name: &str, bytes: &[u8]) -> (String, String) {
+        let path = fixture(name, bytes);
+        let nodes = file_to_nodes(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        (
+            file_title(&path, &nodes),
+            serde_json::to_string(&nodes).unwrap(),
+        )
+    }
+
+    #[cfg(feature = "markdown")]
+    #[test]
+    fn markdown_file() {
+        let (title, content) = convert("post.MD", b"Intro\n\n## The *title*\n\nText");
+        assert_eq!(title, "The title");
+        assert_eq!(
+            content,
+            r#"[{"tag":"p","children":["Intro"]},{"tag":"h4","children":["The ",{"tag":"em","children":["title"]}]},{"tag":"p","children":["Text"]}]"#
+        );
+    }
+
+    #[cfg(feature = "html")]
+    #[test]
+    fn html_file() {
+        let html = b"<meta charset=windows-1251><h1>\xcf\xf0\xe8\xe2\xe5\xf2</h1><p>text</p>";
+        let (title, content) = convert("page.htm", html);
+        assert_eq!(title, "Привет");
+        assert_eq!(
+            content,
+            r#"[{"tag":"h3","children":["Привет"]},{"tag":"p","children":["text"]}]"#
+        );
+    }
+
+    #[test]
+    fn text_file() {
+        let (title, content) = convert("notes.txt", b"\xef\xbb\xbf# not a heading\n  indented\n\n");
+        assert_eq!(title, "notes");
+        assert_eq!(
+            content,
+            r##"[{"tag":"pre","children":["# not a heading\n  indented"]}]"##
+        );
+    }
+
+    #[test]
+    fn disabled_formats() {
+        let cases = [
+            ("disabled.md", "markdown", cfg!(feature = "markdown")),
+            ("disabled.html", "html", cfg!(feature = "html")),
+        ];
+        for (name, feature, enabled) in cases {
+            let path = fixture(name, b"text");
+            let result = file_to_nodes(&path);
+            std::fs::remove_file(&path).unwrap();
+            match enabled {
+                true => assert!(result.is_ok()),
+                false => assert!(
+                    matches!(result, Err(Error::FeatureDisabled(disabled)) if disabled == feature)
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = Path::new("/nonexistent/telegraph-rs/page.md");
+        let error = file_to_nodes(path).unwrap_err();
+        assert!(matches!(error, Error::FileError { .. }));
+        assert!(error
+            .to_string()
+            .contains("/nonexistent/telegraph-rs/page.md"));
+    }
+}
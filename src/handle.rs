@@ -0,0 +1,160 @@
+//! A handle to edit a created or fetched page
+use crate::{Node, Page, Result, Telegraph};
+
+/// A [`Page`] bound to the [`Telegraph`] account editing it, created by [`Telegraph::page_handle`]
+///
+/// The handle keeps the page as last returned by Telegraph, and edits keep its title.
+/// If the page may have been retitled elsewhere since, call [`PageHandle::refresh`] before editing,
+/// otherwise the edit puts the held title back.
+///
+/// ```
+/// # async fn run() -> Result<(), telegraph_rs::Error> {
+/// use telegraph_rs::{dsl::*, Telegraph};
+///
+/// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+/// let page = telegraph.create_page_from("Title", r#"["Draft"]"#, false).await?;
+/// let mut handle = telegraph.page_handle(&page);
+/// handle.set_content(&[p(["Final"])]).await?;
+/// println!("{} views", handle.refresh().await?.views);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PageHandle<'a> {
+    telegraph: &'a Telegraph,
+    page: Page,
+}
+
+impl Telegraph {
+    /// Bind `page` to this account to edit it, see [`PageHandle`]
+    pub fn page_handle(&self, page: &Page) -> PageHandle<'_> {
+        PageHandle {
+            telegraph: self,
+            page: page.clone(),
+        }
+    }
+}
+
+impl PageHandle<'_> {
+    /// The page as last returned by Telegraph
+    pub fn page(&self) -> &Page {
+        &self.page
+    }
+
+    /// Take the page out of the handle
+    pub fn into_page(self) -> Page {
+        self.page
+    }
+
+    /// Fetch the page again with [`Telegraph::get_own_page`],
+    /// with its content if the held page has content
+    ///
+    /// The page is always fetched from Telegraph, dropping it from the cache if any.
+    pub async fn refresh(&mut self) -> Result<&Page> {
+        let return_content = self.page.content.is_some();
+        self.telegraph.invalidate(&self.page.path);
+        self.page = self
+            .telegraph
+            .get_own_page(&self.page.path, return_content)
+            .await?;
+        Ok(&self.page)
+    }
+
+    /// Replace the content of the page, keeping the held title
+    ///
+    /// The returned page has its content if the held page has content.
+    pub async fn set_content(&mut self, content: &[Node]) -> Result<&Page> {
+        let title = self.page.title.clone();
+        self.edit(&title, content).await
+    }
+
+    /// Replace the title and content of the page
+    pub async fn edit(&mut self, title: &str, content: &[Node]) -> Result<&Page> {
+        let return_content = self.page.content.is_some();
+        self.page = self
+            .telegraph
+            .edit_page_nodes(&self.page.path, title, content, return_content)
+            .await?;
+        Ok(&self.page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{client, form_field, mock_server},
+        NodeElement,
+    };
+
+    fn page(title: &str, content: Option<&str>) -> Vec<u8> {
+        let content = content.map_or(String::new(), |content| {
+            format!(r#","content":{}"#, content)
+        });
+        format!(
+            r#"{{"ok":true,"result":{{"path":"Hi-10-16","url":"https://telegra.ph/Hi-10-16","title":"{}","description":""{},"views":0}}}}"#,
+            title, content
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn edit_after_create() {
+        let (addr, requests) = mock_server(vec![
+            ("POST /createPage", "application/json", page("Hi", None)),
+            ("POST /editPage", "application/json", page("Hi", None)),
+            ("GET /getPage", "application/json", page("Renamed", None)),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        let content = [NodeElement::new("p").child("New").into_node()];
+
+        let page = telegraph
+            .create_page_from("Hi", r#"["a"]"#, false)
+            .await
+            .unwrap();
+        let mut handle = telegraph.page_handle(&page);
+        handle.set_content(&content).await.unwrap();
+        assert_eq!(handle.refresh().await.unwrap().title, "Renamed");
+        handle.set_content(&content).await.unwrap();
+        handle.edit("Other", &content).await.unwrap();
+        assert_eq!(handle.into_page().title, "Hi");
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[1].starts_with("POST /editPage"));
+        assert!(requests[1].contains("path=Hi-10-16"));
+        assert_eq!(form_field(&requests[1], "title").unwrap(), "Hi");
+        assert!(requests[2]
+            .starts_with("GET /getPage/Hi-10-16?access_token=token&return_content=false "));
+        assert_eq!(form_field(&requests[3], "title").unwrap(), "Renamed");
+        assert_eq!(form_field(&requests[4], "title").unwrap(), "Other");
+    }
+
+    #[tokio::test]
+    async fn edit_after_get() {
+        let (addr, requests) = mock_server(vec![
+            (
+                "GET /getPage",
+                "application/json",
+                page("Hi", Some(r#"["a"]"#)),
+            ),
+            (
+                "POST /editPage",
+                "application/json",
+                page("Hi", Some(r#"["New"]"#)),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let page = telegraph.get_own_page("Hi-10-16", true).await.unwrap();
+        let mut handle = telegraph.page_handle(&page);
+        let edited = handle.set_content(&["New".into()]).await.unwrap();
+        assert_eq!(edited.content, Some(vec!["New".into()]));
+        handle.refresh().await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(form_field(&requests[1], "title").unwrap(), "Hi");
+        assert!(requests[1].contains("return_content=true"));
+        assert!(requests[2].contains("return_content=true "));
+    }
+}
@@ -6,27 +6,98 @@
 //!
 //! ```
 //! # async fn run() -> Result<(), telegraph_rs::Error> {
-//! use telegraph_rs::{Telegraph, html_to_node};
+//! use telegraph_rs::Telegraph;
 //!
 //! let telegraph = Telegraph::new("test_account").create().await?;
 //!
-//! let page = telegraph.create_page("title", &html_to_node("<p>Hello, world</p>"), false).await?;
+//! let page = telegraph.create_page_html("title", "<p>Hello, world</p>", false).await?;
 //! # Ok(())
 //! # }
 //! ```
+#[cfg(feature = "upload")]
+pub mod assets;
+pub mod bulk;
+mod cache;
+pub mod charset;
+pub mod clone;
+pub mod diff;
+pub mod dsl;
 pub mod error;
+pub mod export;
+pub mod file;
+pub mod handle;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+#[cfg(feature = "upload")]
+pub mod mirror;
+pub mod paged;
+pub mod prelude;
+pub mod publish;
+#[cfg(feature = "raw-content")]
+pub mod raw;
+pub mod render;
+#[cfg(feature = "upload")]
+pub mod restore;
+pub mod stats;
+pub mod tag;
+pub mod tree;
 pub mod types;
 pub mod utils;
+pub mod validate;
 
+pub use bulk::*;
+pub use charset::*;
+pub use clone::*;
+pub use diff::*;
 pub use error::*;
+pub use export::*;
+pub use file::*;
+pub use handle::*;
+#[cfg(feature = "html")]
+pub use html::*;
+#[cfg(feature = "markdown")]
+pub use markdown::*;
+#[cfg(feature = "upload")]
+pub use mirror::*;
+pub use paged::*;
+pub use publish::*;
+#[cfg(feature = "raw-content")]
+pub use raw::*;
+pub use render::*;
+#[cfg(feature = "upload")]
+pub use restore::*;
+pub use stats::*;
+pub use tag::*;
+pub use tree::*;
 pub use types::*;
 pub use utils::*;
+pub use validate::*;
 
+use cache::PageCache;
 use reqwest::{multipart::Form, Client, Response};
-use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Validate serialized `content` with [`validate_nodes`]
+pub(crate) fn validate_content(content: &str) -> Result<()> {
+    let nodes = serde_json::from_str::<Vec<Node>>(content).map_err(|e| {
+        vec![ValidationError {
+            path: vec![],
+            message: format!("content is not an array of nodes: {}", e),
+        }]
+    });
+    nodes
+        .and_then(|nodes| validate_nodes(&nodes))
+        .map_err(Error::InvalidContent)
+}
+
+/// Default base url of the api
+const API_URL: &str = "https://api.telegra.ph";
+
 macro_rules! send {
     ($e:expr) => {
         $e.send().await.and_then(Response::error_for_status)
@@ -40,6 +111,10 @@ pub struct AccountBuilder {
     author_name: Option<String>,
     author_url: Option<String>,
     client: Client,
+    skip_size_check: bool,
+    validate: bool,
+    api_url: Option<String>,
+    cache: Option<Arc<PageCache>>,
 }
 
 impl AccountBuilder {
@@ -86,12 +161,59 @@ impl AccountBuilder {
         self
     }
 
+    /// Whether to check that page content is no larger than [`MAX_CONTENT_SIZE`] before sending it,
+    /// returning [`Error::ContentTooLarge`] instead of leaving it to the server (default: true)
+    pub fn check_content_size(mut self, check: bool) -> Self {
+        self.skip_size_check = !check;
+        self
+    }
+
+    /// Whether to check page content with [`validate_nodes`] before sending it,
+    /// returning [`Error::InvalidContent`] instead of an api error (default: false)
+    pub fn validate_content(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Base url of the api, `https://api.telegra.ph` by default.
+    ///
+    /// Useful for proxies and tests.
+    pub fn api_url(mut self, api_url: &str) -> Self {
+        self.api_url = Some(api_url.trim_end_matches('/').to_owned());
+        self
+    }
+
+    /// Cache the pages fetched by [`Telegraph::get_own_page`] for `ttl`, keeping at most `capacity` pages,
+    /// no cache by default.
+    ///
+    /// Every method reading a page through an account uses it: [`Telegraph::get_own_page_content`],
+    /// [`Telegraph::edit_page_content`], [`Telegraph::append_to_page`], [`Telegraph::prepend_to_page`],
+    /// [`Telegraph::replace_in_page`], [`Telegraph::unpublish_page`], [`Telegraph::clone_page`],
+    /// [`Telegraph::transfer_page`], [`Telegraph::export_all`] and [`PageHandle::refresh`].
+    /// The associated functions like [`Telegraph::get_page`], [`Telegraph::get_page_content`]
+    /// and [`Telegraph::get_page_by_url`] are deliberately never cached: they have no account to hold a cache,
+    /// and a process-wide cache would serve pages that no edit through a [`Telegraph`] can invalidate.
+    ///
+    /// The cache is shared by the clones of the [`Telegraph`] and by the one returned by
+    /// [`Telegraph::edit_account_info`], so an edit through any of them drops the page for all.
+    ///
+    /// The least recently used page is dropped when the cache is full.
+    /// A page is dropped when it's edited with the same [`Telegraph`] or its clones,
+    /// or with [`Telegraph::invalidate`]. Pages edited elsewhere may be stale until `ttl` passes,
+    /// which methods editing the fetched content like [`Telegraph::append_to_page`] would overwrite.
+    pub fn cache(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.cache = Some(Arc::new(PageCache::new(ttl, capacity)));
+        self
+    }
+
     /// If `access_token` is not set, an new account will be create.
     ///
     /// Otherwise import the existing account.
     pub async fn create(mut self) -> Result<Telegraph> {
+        let api_url = self.api_url.unwrap_or_else(|| API_URL.to_owned());
         if self.access_token.is_none() {
             let account = Telegraph::create_account(
+                &api_url,
                 &self.short_name,
                 self.author_name.as_deref(),
                 self.author_url.as_deref(),
@@ -106,13 +228,18 @@ impl AccountBuilder {
             short_name: self.short_name.to_owned(),
             author_name: self.author_name.unwrap_or(self.short_name),
             author_url: self.author_url,
+            skip_size_check: self.skip_size_check,
+            validate: self.validate,
+            api_url,
+            cache: self.cache,
         })
     }
 
     /// Edit info of an an existing account.
     pub async fn edit(self) -> Result<Telegraph> {
+        let api_url = self.api_url.unwrap_or_else(|| API_URL.to_owned());
         let response = send!(Client::new()
-            .get("https://api.telegra.ph/editAccountInfo")
+            .get(format!("{}/editAccountInfo", api_url))
             .query(&[
                 ("access_token", self.access_token.as_ref().unwrap()),
                 ("short_name", &self.short_name),
@@ -131,10 +258,46 @@ impl AccountBuilder {
             short_name: json.short_name.clone().unwrap(),
             author_name: json.author_name.or(json.short_name).unwrap(),
             author_url: json.author_url,
+            skip_size_check: self.skip_size_check,
+            validate: self.validate,
+            api_url,
+            cache: self.cache,
         })
     }
 }
 
+/// Options for [`Telegraph::create_page_with`] and [`Telegraph::edit_page_with`]
+#[derive(Debug, Clone, Default)]
+pub struct CreatePageOptions {
+    author_name: Option<String>,
+    author_url: Option<String>,
+    return_content: bool,
+}
+
+impl CreatePageOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the author of this page, the account's author name by default.
+    pub fn author_name(mut self, author_name: &str) -> Self {
+        self.author_name = Some(author_name.to_owned());
+        self
+    }
+
+    /// Profile link of the author of this page, the account's profile link by default.
+    pub fn author_url(mut self, author_url: &str) -> Self {
+        self.author_url = Some(author_url.to_owned());
+        self
+    }
+
+    /// Whether the returned page has its content, false by default.
+    pub fn return_content(mut self, return_content: bool) -> Self {
+        self.return_content = return_content;
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Telegraph {
     client: Client,
@@ -142,6 +305,10 @@ pub struct Telegraph {
     short_name: String,
     author_name: String,
     author_url: Option<String>,
+    skip_size_check: bool,
+    validate: bool,
+    api_url: String,
+    cache: Option<Arc<PageCache>>,
 }
 
 impl Telegraph {
@@ -162,6 +329,7 @@ impl Telegraph {
     /// # Ok(())
     /// # }
     /// ```
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(short_name: &str) -> AccountBuilder {
         AccountBuilder {
             short_name: short_name.to_owned(),
@@ -170,6 +338,7 @@ impl Telegraph {
     }
 
     pub(crate) async fn create_account<'a, S, T>(
+        api_url: &str,
         short_name: &str,
         author_name: S,
         author_url: T,
@@ -187,7 +356,7 @@ impl Telegraph {
             params.insert("author_url", author_url);
         }
         let response = send!(Client::new()
-            .get("https://api.telegra.ph/createAccount")
+            .get(format!("{}/createAccount", api_url))
             .query(&params))?;
         response.json::<ApiResult<Account>>().await?.into()
     }
@@ -196,6 +365,10 @@ impl Telegraph {
     ///
     /// if `return_content` is true, a content field will be returned in the Page object.
     ///
+    /// Content larger than [`MAX_CONTENT_SIZE`] is rejected with [`Error::ContentTooLarge`]
+    /// unless disabled by [`AccountBuilder::check_content_size`].
+    /// Invalid content is rejected with [`Error::InvalidContent`] if enabled by [`AccountBuilder::validate_content`].
+    ///
     /// ```
     /// # async fn test() -> Result<(), telegraph_rs::Error> {
     /// use telegraph_rs::{Telegraph, html_to_node};
@@ -216,18 +389,114 @@ impl Telegraph {
         return_content: bool,
     ) -> Result<Page> {
         // TODO: content HTML 形式
-        let response = send!(self
-            .client
-            .post("https://api.telegra.ph/createPage")
-            .form(&[
-                ("access_token", &*self.access_token),
-                ("title", title),
-                ("author_name", &*self.author_name),
-                ("author_url", self.author_url.as_deref().unwrap_or("")),
-                ("content", content),
-                ("return_content", &*return_content.to_string()),
-            ]))?;
-        response.json::<ApiResult<Page>>().await?.into()
+        let options = CreatePageOptions::new().return_content(return_content);
+        self.post_page(None, title, content, &options).await
+    }
+
+    /// [`Telegraph::create_page`] with `content` given as nodes or a string,
+    /// and the author and `return_content` given by `options`
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{CreatePageOptions, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("channel").access_token("token").create().await?;
+    /// let options = CreatePageOptions::new()
+    ///     .author_name("Alice")
+    ///     .author_url("https://t.me/alice");
+    /// let page = telegraph
+    ///     .create_page_with("title", r#"["Hello, world!"]"#, &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_page_with(
+        &self,
+        title: &str,
+        content: impl IntoContent,
+        options: &CreatePageOptions,
+    ) -> Result<Page> {
+        self.post_page(None, title, &content.into_content(), options)
+            .await
+    }
+
+    /// [`Telegraph::create_page`] with typed `content`, which is serialized with [`nodes_to_content_string`]
+    ///
+    /// The size check and the validation apply like to serialized content.
+    pub async fn create_page_nodes(
+        &self,
+        title: &str,
+        content: &[Node],
+        return_content: bool,
+    ) -> Result<Page> {
+        let options = CreatePageOptions::new().return_content(return_content);
+        self.create_page_with(title, content, &options).await
+    }
+
+    /// [`Telegraph::create_page`] with `content` given as nodes or a string
+    ///
+    /// ```
+    /// # async fn test() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{dsl::*, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    ///
+    /// let content = vec![h3("Hello"), p(["Hello, world!"])];
+    /// let page = telegraph.create_page_from("title", &content, false).await?;
+    /// let page = telegraph.edit_page_from(&page.path, "title", content, false).await?;
+    /// let page = telegraph.edit_page_from(&page.path, "title", r#"["Hello"]"#, false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_page_from(
+        &self,
+        title: &str,
+        content: impl IntoContent,
+        return_content: bool,
+    ) -> Result<Page> {
+        self.create_page(title, &content.into_content(), return_content)
+            .await
+    }
+
+    /// [`Telegraph::edit_page`] with typed `content`, see [`Telegraph::create_page_nodes`]
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{Node, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let page = Telegraph::get_page("Sample-Page-12-15", true).await?;
+    /// let mut content = page.content.unwrap_or_default();
+    /// if let Some(Node::NodeElement(paragraph)) = content.iter_mut().find(|node| {
+    ///     matches!(node, Node::NodeElement(element) if element.tag == "p")
+    /// }) {
+    ///     paragraph.children = Some(vec![Node::text("Updated")]);
+    /// }
+    /// telegraph.edit_page_nodes(&page.path, &page.title, &content, false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn edit_page_nodes(
+        &self,
+        path: &str,
+        title: &str,
+        content: &[Node],
+        return_content: bool,
+    ) -> Result<Page> {
+        let options = CreatePageOptions::new().return_content(return_content);
+        self.edit_page_with(path, title, content, &options).await
+    }
+
+    /// [`Telegraph::edit_page`] with `content` given as nodes or a string, see [`Telegraph::create_page_from`]
+    pub async fn edit_page_from(
+        &self,
+        path: &str,
+        title: &str,
+        content: impl IntoContent,
+        return_content: bool,
+    ) -> Result<Page> {
+        self.edit_page(path, title, &content.into_content(), return_content)
+            .await
     }
 
     /// Use this method to update information about a Telegraph account.
@@ -242,12 +511,16 @@ impl Telegraph {
             author_name: Some(self.author_name),
             author_url: self.author_url,
             client: self.client,
+            skip_size_check: self.skip_size_check,
+            validate: self.validate,
+            api_url: Some(self.api_url),
+            cache: self.cache,
         }
     }
 
     /// Use this method to edit an existing Telegraph page.
     ///
-    /// On success, returns a Page object.
+    /// On success, returns a Page object. Content is checked like in [`Telegraph::create_page`].
     pub async fn edit_page(
         &self,
         path: &str,
@@ -255,16 +528,239 @@ impl Telegraph {
         content: &str,
         return_content: bool,
     ) -> Result<Page> {
-        let response = send!(self.client.post("https://api.telegra.ph/editPage").form(&[
-            ("access_token", &*self.access_token),
-            ("path", path),
+        let options = CreatePageOptions::new().return_content(return_content);
+        self.post_page(Some(path), title, content, &options).await
+    }
+
+    /// [`Telegraph::edit_page`] with `content` given as nodes or a string,
+    /// and the author and `return_content` given by `options`, see [`Telegraph::create_page_with`]
+    pub async fn edit_page_with(
+        &self,
+        path: &str,
+        title: &str,
+        content: impl IntoContent,
+        options: &CreatePageOptions,
+    ) -> Result<Page> {
+        self.post_page(Some(path), title, &content.into_content(), options)
+            .await
+    }
+
+    /// Create a page, or edit the one at `path`, returning the page as `T`
+    ///
+    /// Fields are sent as a percent-encoded form, so `+`, `%` and non-ASCII text arrive unchanged.
+    pub(crate) async fn post_page<T: DeserializeOwned>(
+        &self,
+        path: Option<&str>,
+        title: &str,
+        content: &str,
+        options: &CreatePageOptions,
+    ) -> Result<T> {
+        self.check_content(content)?;
+        let author_name = options.author_name.as_deref().unwrap_or(&self.author_name);
+        let author_url = options.author_url.as_deref().or(self.author_url.as_deref());
+        let mut form = vec![("access_token", &*self.access_token)];
+        form.extend(path.map(|path| ("path", path)));
+        form.extend([
             ("title", title),
-            ("author_name", &*self.author_name),
-            ("author_url", self.author_url.as_deref().unwrap_or("")),
+            ("author_name", author_name),
+            ("author_url", author_url.unwrap_or("")),
             ("content", content),
-            ("return_content", &*return_content.to_string()),
-        ]))?;
-        response.json::<ApiResult<Page>>().await?.into()
+            (
+                "return_content",
+                if options.return_content {
+                    "true"
+                } else {
+                    "false"
+                },
+            ),
+        ]);
+        let method = if path.is_some() {
+            "editPage"
+        } else {
+            "createPage"
+        };
+        let response = send!(self
+            .client
+            .post(format!("{}/{}", self.api_url, method))
+            .form(&form));
+        if let Some(path) = path {
+            self.invalidate(path);
+        }
+        response?.json::<ApiResult<T>>().await?.into()
+    }
+
+    /// Check the size of serialized `content` unless disabled by [`AccountBuilder::check_content_size`],
+    /// and validate it if enabled by [`AccountBuilder::validate_content`]
+    fn check_content(&self, content: &str) -> Result<()> {
+        if !self.skip_size_check && content.len() > MAX_CONTENT_SIZE {
+            return Err(Error::ContentTooLarge {
+                size: content.len(),
+                limit: MAX_CONTENT_SIZE,
+            });
+        }
+        if self.validate {
+            validate_content(content)?;
+        }
+        Ok(())
+    }
+
+    /// [`Telegraph::edit_page`] keeping the current title of the page
+    ///
+    /// The title is fetched with an extra `getPage` request (without content) before editing,
+    /// so this costs two requests. Content is checked before either of them is sent.
+    pub async fn edit_page_content(
+        &self,
+        path: &str,
+        content: impl IntoContent,
+        return_content: bool,
+    ) -> Result<Page> {
+        let content = content.into_content();
+        self.check_content(&content)?;
+        let page = self.get_own_page(path, false).await?;
+        let options = CreatePageOptions::new().return_content(return_content);
+        self.post_page(Some(path), &page.title, &content, &options)
+            .await
+    }
+
+    /// Add `nodes` to the end of the page at `path`, keeping its title
+    ///
+    /// The page is fetched with its content, then edited with the new content.
+    /// This is a read-modify-write: if the page is edited by someone else in between,
+    /// one of the edits is lost, so don't run it concurrently on the same page.
+    ///
+    /// Fails with [`Error::ContentTooLarge`] before editing if the new content is too large,
+    /// see [`Telegraph::create_page`].
+    pub async fn append_to_page(&self, path: &str, nodes: &[Node]) -> Result<Page> {
+        self.splice_page(path, nodes, false).await
+    }
+
+    /// Like [`Telegraph::append_to_page`], adding `nodes` to the start of the page
+    pub async fn prepend_to_page(&self, path: &str, nodes: &[Node]) -> Result<Page> {
+        self.splice_page(path, nodes, true).await
+    }
+
+    async fn splice_page(&self, path: &str, nodes: &[Node], at_start: bool) -> Result<Page> {
+        let page = self.get_own_page(path, true).await?;
+        let mut content = page.content.unwrap_or_default();
+        if at_start {
+            content.splice(0..0, nodes.iter().cloned());
+        } else {
+            content.extend_from_slice(nodes);
+        }
+        self.edit_page_nodes(path, &page.title, &content, false)
+            .await
+    }
+
+    /// Replace every occurrence of `from` with `to` in the text of the page at `path`
+    ///
+    /// Only text nodes are changed, not attributes such as links, and an occurrence must be
+    /// inside a single text node, so `a <b>b</b>` doesn't match `a b`.
+    /// The page is edited like in [`Telegraph::append_to_page`].
+    ///
+    /// Returns the page and the number of replacements. If nothing matched,
+    /// the page isn't edited and the fetched page is returned.
+    pub async fn replace_in_page(&self, path: &str, from: &str, to: &str) -> Result<(Page, usize)> {
+        self.replace_text_in_page(path, |text| {
+            let count = if from.is_empty() {
+                0
+            } else {
+                text.matches(from).count()
+            };
+            (count > 0).then(|| (text.replace(from, to), count))
+        })
+        .await
+    }
+
+    /// Like [`Telegraph::replace_in_page`], replacing the matches of `regex`,
+    /// `replacement` can refer to capture groups, see [`regex::Regex::replace_all`]
+    #[cfg(feature = "regex")]
+    pub async fn replace_regex_in_page(
+        &self,
+        path: &str,
+        regex: &regex::Regex,
+        replacement: &str,
+    ) -> Result<(Page, usize)> {
+        self.replace_text_in_page(path, |text| {
+            let count = regex.find_iter(text).count();
+            (count > 0).then(|| (regex.replace_all(text, replacement).into_owned(), count))
+        })
+        .await
+    }
+
+    async fn replace_text_in_page(
+        &self,
+        path: &str,
+        mut replace: impl FnMut(&str) -> Option<(String, usize)>,
+    ) -> Result<(Page, usize)> {
+        let mut page = self.get_own_page(path, true).await?;
+        let mut count = 0;
+        visit_mut(
+            page.content.as_deref_mut().unwrap_or_default(),
+            |node, _| {
+                if let Node::Text(text) = node {
+                    if let Some((replaced, n)) = replace(text) {
+                        *text = replaced;
+                        count += n;
+                    }
+                }
+                Visit::Children
+            },
+        );
+        if count == 0 {
+            return Ok((page, 0));
+        }
+        let content = page.content.unwrap_or_default();
+        let page = self
+            .edit_page_nodes(path, &page.title, &content, false)
+            .await?;
+        Ok((page, count))
+    }
+
+    /// Blank the page at `path`, keeping its title
+    ///
+    /// **This is not a deletion.** Telegraph can't delete pages, so the page stays at its URL
+    /// with `placeholder` as its content, a paragraph "This page has been removed" by default.
+    /// The old content is still kept by Telegraph and may be cached elsewhere.
+    ///
+    /// The page is fetched first, and this fails with [`Error::NotEditable`]
+    /// unless Telegraph reports that the account can edit it.
+    pub async fn unpublish_page(&self, path: &str, placeholder: Option<&[Node]>) -> Result<Page> {
+        self.unpublish(path, None, placeholder).await
+    }
+
+    /// Like [`Telegraph::unpublish_page`], also changing the title of the page to `title`
+    pub async fn unpublish_page_with_title(
+        &self,
+        path: &str,
+        title: &str,
+        placeholder: Option<&[Node]>,
+    ) -> Result<Page> {
+        self.unpublish(path, Some(title), placeholder).await
+    }
+
+    async fn unpublish(
+        &self,
+        path: &str,
+        title: Option<&str>,
+        placeholder: Option<&[Node]>,
+    ) -> Result<Page> {
+        let page = self.get_editable_page(path).await?;
+        let removed = [NodeElement::new("p")
+            .child("This page has been removed")
+            .into_node()];
+        let content = placeholder.unwrap_or(&removed);
+        self.edit_page_nodes(path, title.unwrap_or(&page.title), content, false)
+            .await
+    }
+
+    /// Fetch the page at `path` without content, failing with [`Error::NotEditable`]
+    /// unless Telegraph reports that this account can edit it
+    pub(crate) async fn get_editable_page(&self, path: &str) -> Result<Page> {
+        let page = self.get_own_page(path, false).await?;
+        if page.can_edit != Some(true) {
+            return Err(Error::NotEditable(page.path));
+        }
+        Ok(page)
     }
 
     /// Use this method to get information about a Telegraph account. Returns an Account object on success.
@@ -273,7 +769,7 @@ impl Telegraph {
     pub async fn get_account_info(&self, fields: &[&str]) -> Result<Account> {
         let response = send!(self
             .client
-            .get("https://api.telegra.ph/getAccountInfo")
+            .get(format!("{}/getAccountInfo", self.api_url))
             .query(&[
                 ("access_token", &self.access_token),
                 ("fields", &serde_json::to_string(fields).unwrap()),
@@ -283,13 +779,106 @@ impl Telegraph {
 
     /// Use this method to get a Telegraph page. Returns a Page object on success.
     pub async fn get_page(path: &str, return_content: bool) -> Result<Page> {
+        Self::get_page_from(API_URL, path, return_content).await
+    }
+
+    /// Like [`Telegraph::get_page`], passing the access token of this account
+    ///
+    /// Telegraph only sets [`Page::can_edit`] when the token is given,
+    /// so this tells whether the page can be edited by this account before trying to.
+    /// The page is cached if enabled by [`AccountBuilder::cache`].
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::Telegraph;
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let page = telegraph.get_own_page("Sample-Page-12-15", false).await?;
+    /// if page.can_edit == Some(true) {
+    ///     telegraph.edit_page_content(&page.path, r#"["Hello"]"#, false).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_own_page(&self, path: &str, return_content: bool) -> Result<Page> {
+        if let Some(page) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(path, return_content))
+        {
+            return Ok(page);
+        }
+        let response = send!(self
+            .client
+            .get(format!("{}/getPage/{}", self.api_url, path))
+            .query(&[
+                ("access_token", &*self.access_token),
+                ("return_content", &return_content.to_string()),
+            ]))?;
+        let page: Result<Page> = response.json::<ApiResult<Page>>().await?.into();
+        if let (Ok(page), Some(cache)) = (&page, &self.cache) {
+            cache.insert(path, return_content, page.clone());
+        }
+        page
+    }
+
+    /// Get the content of the page at `path`
+    ///
+    /// Fails with [`Error::MissingContent`] if Telegraph returns the page without content.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{node_to_text, Telegraph};
+    ///
+    /// let content = Telegraph::get_page_content("Sample-Page-12-15").await?;
+    /// println!("{}", node_to_text(&content));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_page_content(path: &str) -> Result<Vec<Node>> {
+        let page = Self::get_page(path, true).await?;
+        page.content.ok_or(Error::MissingContent(page.path))
+    }
+
+    /// Like [`Telegraph::get_page_content`], passing the access token of this account,
+    /// see [`Telegraph::get_own_page`]
+    pub async fn get_own_page_content(&self, path: &str) -> Result<Vec<Node>> {
+        let page = self.get_own_page(path, true).await?;
+        page.content.ok_or(Error::MissingContent(page.path))
+    }
+
+    /// [`Telegraph::get_page`] with the page given by its url, like `https://telegra.ph/Sample-Page-12-15`
+    ///
+    /// Urls of telegra.ph, graph.org and te.legra.ph are accepted and their query and fragment are ignored,
+    /// other urls fail with [`Error::InvalidPageUrl`].
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::Telegraph;
+    ///
+    /// let page = Telegraph::get_page_by_url("https://telegra.ph/Sample-Page-12-15#Heading", false).await?;
+    /// assert_eq!(page.path, "Sample-Page-12-15");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_page_by_url(url: &str, return_content: bool) -> Result<Page> {
+        let path = utils::parse_page_url(url)?;
+        Self::get_page_from(API_URL, &path, return_content).await
+    }
+
+    /// [`Telegraph::get_page`] from the api at `api_url`, returning the page as `T`
+    pub(crate) async fn get_page_from<T: DeserializeOwned>(
+        api_url: &str,
+        path: &str,
+        return_content: bool,
+    ) -> Result<T> {
         let response = Client::new()
-            .get(&format!("https://api.telegra.ph/getPage/{}", path))
+            .get(format!("{}/getPage/{}", api_url, path))
             .query(&[("return_content", return_content.to_string())])
             .send()
             .await?
             .error_for_status()?;
-        response.json::<ApiResult<Page>>().await?.into()
+        response.json::<ApiResult<T>>().await?.into()
     }
 
     /// Use this method to get a list of pages belonging to a Telegraph account.
@@ -301,7 +890,7 @@ impl Telegraph {
     pub async fn get_page_list(&self, offset: i32, limit: i32) -> Result<PageList> {
         let response = send!(self
             .client
-            .get("https://api.telegra.ph/getPageList")
+            .get(format!("{}/getPageList", self.api_url))
             .query(&[
                 ("access_token", &self.access_token),
                 ("offset", &offset.to_string()),
@@ -329,10 +918,10 @@ impl Telegraph {
         let params = ["year", "month", "day", "hour"]
             .iter()
             .zip(time)
-            .collect::<HashMap<_, _>>();
+            .collect::<std::collections::HashMap<_, _>>();
 
         let response = send!(Client::new()
-            .get(&format!("https://api.telegra.ph/getViews/{}", path))
+            .get(format!("{}/getViews/{}", API_URL, path))
             .query(&params))?;
         response.json::<ApiResult<PageViews>>().await?.into()
     }
@@ -347,17 +936,15 @@ impl Telegraph {
     pub async fn revoke_access_token(&mut self) -> Result<Account> {
         let response = send!(self
             .client
-            .get("https://api.telegra.ph/revokeAccessToken")
+            .get(format!("{}/revokeAccessToken", self.api_url))
             .query(&[("access_token", &self.access_token)]))?;
         let json: Result<Account> = response.json::<ApiResult<Account>>().await?.into();
-        if json.is_ok() {
-            self.access_token = json
-                .as_ref()
-                .unwrap()
-                .access_token
-                .as_ref()
-                .unwrap()
-                .to_owned();
+        if let Ok(Account {
+            access_token: Some(access_token),
+            ..
+        }) = &json
+        {
+            self.access_token = access_token.to_owned();
         }
         json
     }
@@ -367,13 +954,22 @@ impl Telegraph {
     pub async fn upload_with<T: Uploadable>(
         files: &[T],
         client: &Client,
+    ) -> Result<Vec<ImageInfo>> {
+        Self::upload_to("https://telegra.ph/upload", files, client).await
+    }
+
+    #[cfg(feature = "upload")]
+    pub(crate) async fn upload_to<T: Uploadable>(
+        url: &str,
+        files: &[T],
+        client: &Client,
     ) -> Result<Vec<ImageInfo>> {
         let mut form = Form::new();
         for (i, file) in files.iter().enumerate() {
             let part = file.part()?;
             form = form.part(i.to_string(), part);
         }
-        let response = send!(client.post("https://telegra.ph/upload").multipart(form))?;
+        let response = send!(client.post(url).multipart(form))?;
 
         match response.json::<UploadResult>().await? {
             UploadResult::Error { error } => Err(Error::ApiError(error)),
@@ -386,34 +982,782 @@ impl Telegraph {
     pub async fn upload<T: Uploadable>(files: &[T]) -> Result<Vec<ImageInfo>> {
         Self::upload_with(files, &Client::new()).await
     }
-}
 
-#[cfg(feature = "html")]
-fn html_to_node_inner(node: &html_parser::Node) -> Option<Node> {
-    match node {
-        html_parser::Node::Text(text) => Some(Node::Text(text.to_owned())),
-        html_parser::Node::Element(element) => Some(Node::NodeElement(NodeElement {
-            tag: element.name.to_owned(),
-            attrs: {
-                (!element.attributes.is_empty()).then(|| element.attributes.clone())
-            },
-            children: {
-                if element.children.is_empty() {
-                    None
-                } else {
-                    element.children.iter().map(|node| html_to_node_inner(node))
-                        .collect::<Option<Vec<_>>>()
+    /// Upload the `data:` URI images in `nodes` to telegraph and replace their `src` with the uploaded ones
+    ///
+    /// Returns the uploaded images, malformed URIs are left untouched.
+    #[cfg(feature = "upload")]
+    pub async fn upload_data_images(nodes: &mut [Node], client: &Client) -> Result<Vec<ImageInfo>> {
+        Self::upload_data_images_to("https://telegra.ph/upload", nodes, client).await
+    }
+
+    #[cfg(feature = "upload")]
+    pub(crate) async fn upload_data_images_to(
+        url: &str,
+        nodes: &mut [Node],
+        client: &Client,
+    ) -> Result<Vec<ImageInfo>> {
+        fn collect<'a>(nodes: &'a mut [Node], found: &mut Vec<(&'a mut String, DataUri)>) {
+            for node in nodes {
+                if let Node::NodeElement(element) = node {
+                    if element.tag.eq_ignore_ascii_case("img") {
+                        if let Some(src) = element.attrs.as_mut().and_then(|a| a.src.as_mut()) {
+                            if let Some(data) = DataUri::parse(src) {
+                                found.push((src, data));
+                            }
+                        }
+                    }
+                    collect(element.children.as_deref_mut().unwrap_or_default(), found);
                 }
-            },
-        })),
-        _ => None,
+            }
+        }
+
+        let mut found = vec![];
+        collect(nodes, &mut found);
+        if found.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let (srcs, files): (Vec<_>, Vec<_>) = found.into_iter().unzip();
+        let images = Self::upload_to(url, &files, client).await?;
+        for (src, image) in srcs.into_iter().zip(&images) {
+            *src = image.src.clone();
+        }
+        Ok(images)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Telegraph;
+    use std::sync::{Arc, Mutex};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serve `routes` of `(request line prefix, content type, body)` on localhost,
+    /// returns the address and the received requests
+    pub(crate) async fn mock_server(
+        routes: Vec<(&'static str, &'static str, Vec<u8>)>,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        mock_handler(move |request| {
+            routes
+                .iter()
+                .find(|(prefix, _, _)| request.starts_with(prefix))
+                .map(|(_, content_type, body)| (*content_type, body.clone()))
+        })
+        .await
+    }
+
+    /// A client of the test account, sending its requests to `addr`
+    pub(crate) async fn client(addr: &str) -> Telegraph {
+        Telegraph::new("test")
+            .access_token("token")
+            .api_url(addr)
+            .create()
+            .await
+            .unwrap()
+    }
+
+    /// Serve the responses of `respond` on localhost, `None` is a 404,
+    /// returns the address and the received requests
+    pub(crate) async fn mock_handler(
+        mut respond: impl FnMut(&str) -> Option<(&'static str, Vec<u8>)> + Send + 'static,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 4096];
+                loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| {
+                                let (key, value) = line.split_once(':')?;
+                                key.eq_ignore_ascii_case("content-length")
+                                    .then(|| value.trim().parse::<usize>().ok())?
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+                let request = String::from_utf8_lossy(&request).into_owned();
+                let (status, content_type, body) = respond(&request).map_or(
+                    ("404 Not Found", "text/plain", vec![]),
+                    |(content_type, body)| ("200 OK", content_type, body),
+                );
+                let header = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    content_type,
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).await.unwrap();
+                stream.write_all(&body).await.unwrap();
+                received.lock().unwrap().push(request);
+            }
+        });
+        (addr, requests)
+    }
+
+    /// Fields of the form sent in the body of `request`, in order
+    pub(crate) fn form_fields(request: &str) -> Vec<(String, String)> {
+        let body = request.split("\r\n\r\n").nth(1).unwrap_or_default();
+        let form = reqwest::Url::parse(&format!("https://telegra.ph/?{}", body)).unwrap();
+        form.query_pairs()
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect()
+    }
+
+    /// Value of the field `key` of the form sent in the body of `request`
+    pub(crate) fn form_field(request: &str, key: &str) -> Option<String> {
+        form_fields(request)
+            .into_iter()
+            .find_map(|(name, value)| (name == key).then_some(value))
+    }
+
+    /// Api response with a page without content
+    pub(crate) fn page_response(path: &str, title: &str) -> Vec<u8> {
+        format!(
+            r#"{{"ok":true,"result":{{"path":"{}","url":"https://telegra.ph/{}","title":"{}","description":"","views":0}}}}"#,
+            path, path, title
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn content_size_limit() {
+        use crate::{content_size, Error, NodeElement, MAX_CONTENT_SIZE};
+
+        let paragraph = |len| vec![NodeElement::new("p").child("a".repeat(len)).into_node()];
+        let len = MAX_CONTENT_SIZE - content_size(&paragraph(0));
+        let under = serde_json::to_string(&paragraph(len)).unwrap();
+        let over = serde_json::to_string(&paragraph(len + 1)).unwrap();
+        assert_eq!(content_size(&paragraph(len)), MAX_CONTENT_SIZE);
+
+        let telegraph = Telegraph::new("test")
+            .access_token("token")
+            .create()
+            .await
+            .unwrap();
+        assert!(telegraph.check_content(&under).is_ok());
+        let result = telegraph.create_page("title", &over, false).await;
+        assert!(matches!(
+            result,
+            Err(Error::ContentTooLarge { size, limit })
+                if size == MAX_CONTENT_SIZE + 1 && limit == MAX_CONTENT_SIZE
+        ));
+        let result = telegraph.edit_page("path", "title", &over, false).await;
+        assert!(matches!(result, Err(Error::ContentTooLarge { .. })));
+
+        let telegraph = Telegraph::new("test")
+            .access_token("token")
+            .check_content_size(false)
+            .create()
+            .await
+            .unwrap();
+        assert!(telegraph.check_content(&over).is_ok());
+    }
+
+    #[tokio::test]
+    async fn validate_content() {
+        use crate::Error;
+
+        let content = r#"[{"tag":"div","children":["x"]}]"#;
+        let telegraph = Telegraph::new("test")
+            .access_token("token")
+            .create()
+            .await
+            .unwrap();
+        assert!(telegraph.check_content(content).is_ok());
+
+        let telegraph = Telegraph::new("test")
+            .access_token("token")
+            .validate_content(true)
+            .create()
+            .await
+            .unwrap();
+        let result = telegraph.create_page("title", content, false).await;
+        assert!(matches!(result, Err(Error::InvalidContent(errors)) if errors[0].path == [0]));
+        assert!(telegraph.check_content("not json").is_err());
+        assert!(telegraph
+            .check_content(r#"[{"tag":"p","children":["x"]}]"#)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn compare_content() {
+        use crate::{Node, NodeElement, Page, PageList};
+
+        let page = r#"{"path":"Hi-10-16","url":"https://telegra.ph/Hi-10-16","title":"Hi","description":"Hello","content":[{"tag":"p","children":["Hello, ",{"tag":"a","attrs":{"href":"/a","target":"_blank"},"children":["world"]}]}],"views":3,"can_edit":true}"#;
+        let list = format!(
+            r#"{{"ok":true,"result":{{"total_count":1,"pages":[{}]}}}}"#,
+            page
+        );
+        let (addr, _) = mock_server(vec![
+            (
+                "POST /createPage",
+                "application/json",
+                format!(r#"{{"ok":true,"result":{}}}"#, page).into_bytes(),
+            ),
+            ("GET /getPageList", "application/json", list.into_bytes()),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let content = vec![NodeElement::new("p")
+            .child("Hello, ")
+            .child(
+                NodeElement::new("a")
+                    .attr("target", "_blank")
+                    .attr("href", "/a")
+                    .child("world"),
+            )
+            .into_node()];
+        let json = serde_json::to_string(&content).unwrap();
+        let created = telegraph.create_page("Hi", &json, true).await.unwrap();
+        assert_eq!(created.content.as_ref(), Some(&content));
+        assert_ne!(created.content, Some(vec![Node::text("Hello, world")]));
+        assert_eq!(created, serde_json::from_str::<Page>(page).unwrap());
+
+        let list = telegraph.get_page_list(0, 50).await.unwrap();
+        assert_eq!(
+            list,
+            PageList {
+                total_count: 1,
+                pages: vec![created],
+            }
+        );
+    }
 
+    #[tokio::test]
+    async fn create_page_nodes() {
+        use crate::{Node, NodeElement};
+
+        let page = page_response("Hi-10-16", "Hi");
+        let (addr, requests) =
+            mock_server(vec![("POST /createPage", "application/json", page)]).await;
+        let telegraph = client(&addr).await;
+
+        let content = vec![
+            NodeElement::new("p")
+                .child("1 + 1 = 2 & \"quoted\"")
+                .into_node(),
+            Node::figure("/file/a.jpg", Some("Привет")),
+        ];
+        let json = r#"[{"tag":"p","children":["1 + 1 = 2 & \"quoted\""]},{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/a.jpg"}},{"tag":"figcaption","children":["Привет"]}]}]"#;
+        telegraph.create_page("Hi", json, true).await.unwrap();
+        telegraph
+            .create_page_nodes("Hi", &content, true)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(form_fields(&requests[0]), form_fields(&requests[1]));
+        assert!(requests[0].contains("content=%5B%7B%22tag%22"));
+    }
+
+    #[tokio::test]
+    async fn edit_page_nodes() {
+        use crate::{Node, NodeElement};
+
+        let page = page_response("Hi-10-16", "Hi");
+        let (addr, requests) =
+            mock_server(vec![("POST /editPage", "application/json", page)]).await;
+        let telegraph = client(&addr).await;
+
+        let content = vec![
+            NodeElement::new("p")
+                .child("a+b & 100% ")
+                .child(NodeElement::new("a").attr("href", "/x?y=1&z").child("🦀"))
+                .into_node(),
+            Node::code_block("let x = \"\\n\";"),
+        ];
+        telegraph
+            .edit_page_nodes("Hi-10-16", "Hi", &content, false)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(form_field(&requests[0], "path").unwrap(), "Hi-10-16");
+        assert_eq!(
+            form_field(&requests[0], "content").unwrap(),
+            serde_json::to_string(&content).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn page_author_options() {
+        use crate::CreatePageOptions;
+
+        let page = page_response("Hi-10-16", "Hi");
+        let (addr, requests) = mock_server(vec![
+            ("POST /createPage", "application/json", page.clone()),
+            ("POST /editPage", "application/json", page),
+        ])
+        .await;
+        let account = |author_url: Option<&str>| {
+            let builder = Telegraph::new("short")
+                .access_token("token")
+                .author_name("Account")
+                .api_url(&addr);
+            match author_url {
+                Some(author_url) => builder.author_url(author_url),
+                None => builder,
+            }
+            .create()
+        };
+        let with_url = account(Some("https://t.me/account")).await.unwrap();
+        let without_url = account(None).await.unwrap();
+
+        let cases = [
+            (
+                &with_url,
+                CreatePageOptions::new(),
+                ["Account", "https://t.me/account", "false"],
+            ),
+            (
+                &without_url,
+                CreatePageOptions::new(),
+                ["Account", "", "false"],
+            ),
+            (
+                &with_url,
+                CreatePageOptions::new()
+                    .author_name("Alice")
+                    .return_content(true),
+                ["Alice", "https://t.me/account", "true"],
+            ),
+            (
+                &without_url,
+                CreatePageOptions::new().author_url("https://t.me/alice"),
+                ["Account", "https://t.me/alice", "false"],
+            ),
+            (
+                &with_url,
+                CreatePageOptions::new().author_name("Alice").author_url(""),
+                ["Alice", "", "false"],
+            ),
+        ];
+        for (telegraph, options, [author_name, author_url, return_content]) in &cases {
+            telegraph
+                .create_page_with("Hi", r#"["a"]"#, options)
+                .await
+                .unwrap();
+            telegraph
+                .edit_page_with("Hi-10-16", "Hi", r#"["a"]"#, options)
+                .await
+                .unwrap();
+            let requests = requests.lock().unwrap().split_off(0);
+            let expected = [
+                ("access_token", "token"),
+                ("title", "Hi"),
+                ("author_name", author_name),
+                ("author_url", author_url),
+                ("content", r#"["a"]"#),
+                ("return_content", return_content),
+            ];
+            let expected = expected
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(form_fields(&requests[0]), expected);
+            let mut edit = expected;
+            edit.insert(1, ("path".to_owned(), "Hi-10-16".to_owned()));
+            assert_eq!(form_fields(&requests[1]), edit);
+        }
+
+        without_url
+            .create_page("Hi", r#"["a"]"#, true)
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            form_fields(&requests[0])[2],
+            ("author_name".to_owned(), "Account".to_owned())
+        );
+        assert_eq!(
+            form_fields(&requests[0])[5],
+            ("return_content".to_owned(), "true".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn edit_page_content() {
+        let page = page_response("Hi-10-16", "Current");
+        let (addr, requests) = mock_server(vec![
+            ("GET /getPage/Hi-10-16", "application/json", page.clone()),
+            ("POST /editPage", "application/json", page),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let nodes = vec![crate::NodeElement::new("p").child("New").into_node()];
+        let edited = telegraph
+            .edit_page_content("Hi-10-16", &nodes, false)
+            .await
+            .unwrap();
+        assert_eq!(edited.title, "Current");
+
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 2);
+        assert!(
+            sent[0].starts_with("GET /getPage/Hi-10-16?access_token=token&return_content=false ")
+        );
+        assert!(sent[1].starts_with("POST /editPage"));
+        let fields = form_fields(&sent[1]);
+        assert_eq!(fields[1], ("path".into(), "Hi-10-16".into()));
+        assert_eq!(fields[2], ("title".into(), "Current".into()));
+        assert_eq!(
+            fields[5],
+            (
+                "content".into(),
+                r#"[{"tag":"p","children":["New"]}]"#.into()
+            )
+        );
+
+        let too_large = format!(r#"["{}"]"#, "a".repeat(crate::MAX_CONTENT_SIZE));
+        let result = telegraph
+            .edit_page_content("Hi-10-16", too_large, false)
+            .await;
+        assert!(matches!(result, Err(crate::Error::ContentTooLarge { .. })));
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn append_and_prepend() {
+        let page = r#"{"ok":true,"result":{"path":"Log-10-16","url":"https://telegra.ph/Log-10-16","title":"Log","description":"","content":[{"tag":"p","children":["old"]}],"views":0}}"#;
+        let (addr, requests) = mock_server(vec![
+            (
+                "GET /getPage/Log-10-16",
+                "application/json",
+                page.as_bytes().to_vec(),
+            ),
+            (
+                "POST /editPage",
+                "application/json",
+                page.as_bytes().to_vec(),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        let new = [crate::NodeElement::new("p").child("new").into_node()];
+        telegraph.append_to_page("Log-10-16", &new).await.unwrap();
+        telegraph.prepend_to_page("Log-10-16", &new).await.unwrap();
+        let sent = requests.lock().unwrap().split_off(0);
+        assert!(
+            sent[0].starts_with("GET /getPage/Log-10-16?access_token=token&return_content=true ")
+        );
+        assert!(sent[1].starts_with("POST /editPage"));
+        let append = form_fields(&sent[1]);
+        assert_eq!(append[2], ("title".to_owned(), "Log".to_owned()));
+        assert_eq!(
+            append[5].1,
+            r#"[{"tag":"p","children":["old"]},{"tag":"p","children":["new"]}]"#
+        );
+        let prepend = form_fields(&sent[3]);
+        assert_eq!(
+            prepend[5].1,
+            r#"[{"tag":"p","children":["new"]},{"tag":"p","children":["old"]}]"#
+        );
+
+        let large = [crate::Node::text("a".repeat(crate::MAX_CONTENT_SIZE))];
+        let result = telegraph.append_to_page("Log-10-16", &large).await;
+        assert!(matches!(result, Err(crate::Error::ContentTooLarge { .. })));
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].starts_with("GET /getPage"));
+    }
+
+    #[tokio::test]
+    async fn replace_in_page() {
+        let page = r#"{"ok":true,"result":{"path":"Typo-10-16","url":"https://telegra.ph/Typo-10-16","title":"Typo","description":"","content":[{"tag":"p","children":["teh cat, teh dog ",{"tag":"a","attrs":{"href":"https://teh.example/"},"children":["teh ",{"tag":"b","children":["t"]},"eh"]}]}],"views":0}}"#;
+        let (addr, requests) = mock_server(vec![
+            (
+                "GET /getPage/Typo-10-16",
+                "application/json",
+                page.as_bytes().to_vec(),
+            ),
+            (
+                "POST /editPage",
+                "application/json",
+                page.as_bytes().to_vec(),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        let content = |request: &str| form_field(request, "content").unwrap();
+
+        let (_, count) = telegraph
+            .replace_in_page("Typo-10-16", "teh", "the")
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+        let sent = requests.lock().unwrap().split_off(0);
+        assert!(sent[1].starts_with("POST /editPage"));
+        assert_eq!(
+            content(&sent[1]),
+            r#"[{"tag":"p","children":["the cat, the dog ",{"tag":"a","attrs":{"href":"https://teh.example/"},"children":["the ",{"tag":"b","children":["t"]},"eh"]}]}]"#
+        );
+
+        for from in ["cat, teh dog teh", "missing", ""] {
+            let (page, count) = telegraph
+                .replace_in_page("Typo-10-16", from, "x")
+                .await
+                .unwrap();
+            assert_eq!(count, 0);
+            assert_eq!(page.title, "Typo");
+            let sent = requests.lock().unwrap().split_off(0);
+            assert_eq!(sent.len(), 1);
+            assert!(sent[0].starts_with("GET /getPage"));
+        }
+
+        #[cfg(feature = "regex")]
+        {
+            let regex = regex::Regex::new(r"(\w+) (cat|dog)").unwrap();
+            let (_, count) = telegraph
+                .replace_regex_in_page("Typo-10-16", &regex, "$2 $1")
+                .await
+                .unwrap();
+            assert_eq!(count, 2);
+            let sent = requests.lock().unwrap().split_off(0);
+            assert!(
+                content(&sent[1]).starts_with(r#"[{"tag":"p","children":["cat teh, dog teh ","#)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn unpublish_page() {
+        let page = |path: &str, can_edit: bool| {
+            format!(
+                r#"{{"ok":true,"result":{{"path":"{}","url":"https://telegra.ph/{}","title":"Secret","description":"","views":0,"can_edit":{}}}}}"#,
+                path, path, can_edit
+            )
+            .into_bytes()
+        };
+        let (addr, requests) = mock_server(vec![
+            ("GET /getPage/Mine", "application/json", page("Mine", true)),
+            (
+                "GET /getPage/Theirs",
+                "application/json",
+                page("Theirs", false),
+            ),
+            ("POST /editPage", "application/json", page("Mine", true)),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        telegraph.unpublish_page("Mine", None).await.unwrap();
+        let placeholder = [crate::NodeElement::new("p").child("Gone").into_node()];
+        telegraph
+            .unpublish_page_with_title("Mine", "Removed", Some(&placeholder))
+            .await
+            .unwrap();
+        let sent = requests.lock().unwrap().split_off(0);
+        assert!(sent[0].starts_with("GET /getPage/Mine?access_token=token&return_content=false "));
+        let edit = form_fields(&sent[1]);
+        assert_eq!(edit[1], ("path".to_owned(), "Mine".to_owned()));
+        assert_eq!(edit[2], ("title".to_owned(), "Secret".to_owned()));
+        assert_eq!(
+            edit[5].1,
+            r#"[{"tag":"p","children":["This page has been removed"]}]"#
+        );
+        let edit = form_fields(&sent[3]);
+        assert_eq!(edit[2], ("title".to_owned(), "Removed".to_owned()));
+        assert_eq!(edit[5].1, r#"[{"tag":"p","children":["Gone"]}]"#);
+
+        let result = telegraph.unpublish_page("Theirs", None).await;
+        assert!(matches!(result, Err(crate::Error::NotEditable(path)) if path == "Theirs"));
+        let sent = requests.lock().unwrap().split_off(0);
+        assert_eq!(sent.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_page_by_url() {
+        let urls = [
+            "https://telegra.ph/Sample-Page-12-15",
+            "https://graph.org/Sample-Page-12-15?x=1",
+            "http://te.legra.ph/Sample-Page-12-15/#Heading",
+            "https://telegra.ph/%D0%9F%D1%80%D0%B8%D0%B2%D0%B5%D1%82-12-15",
+        ];
+        let paths = urls
+            .iter()
+            .map(|url| crate::utils::parse_page_url(url).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(paths[..3], ["Sample-Page-12-15"; 3]);
+        assert_eq!(paths[3], "Привет-12-15");
+
+        for url in [
+            "https://example.com/Sample-Page-12-15",
+            "https://telegra.ph/",
+            "https://telegra.ph/file/cat.jpg",
+            "ftp://telegra.ph/Sample-Page-12-15",
+            "Sample-Page-12-15",
+        ] {
+            let result = Telegraph::get_page_by_url(url, false).await;
+            assert!(matches!(result, Err(crate::Error::InvalidPageUrl(invalid)) if invalid == url));
+        }
+
+        let page = page_response("Sample-Page-12-15", "Sample Page");
+        let (addr, requests) = mock_server(vec![(
+            "GET /getPage/Sample-Page-12-15?return_content=false ",
+            "application/json",
+            page,
+        )])
+        .await;
+        let page: crate::Page = Telegraph::get_page_from(&addr, &paths[2], false)
+            .await
+            .unwrap();
+        assert_eq!(page.title, "Sample Page");
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_own_page() {
+        let page = |can_edit: bool| {
+            format!(
+                r#"{{"ok":true,"result":{{"path":"Hi-10-16","url":"https://telegra.ph/Hi-10-16","title":"Hi","description":"","content":["a"],"views":0,"can_edit":{}}}}}"#,
+                can_edit
+            )
+            .into_bytes()
+        };
+        let (addr, requests) = mock_server(vec![
+            (
+                "GET /getPage/Hi-10-16?access_token=mine&",
+                "application/json",
+                page(true),
+            ),
+            (
+                "GET /getPage/Hi-10-16?access_token=",
+                "application/json",
+                page(false),
+            ),
+        ])
+        .await;
+        let account = |token: &str| {
+            Telegraph::new("test")
+                .access_token(token)
+                .api_url(&addr)
+                .create()
+        };
+
+        let mine = account("mine").await.unwrap();
+        let page = mine.get_own_page("Hi-10-16", true).await.unwrap();
+        assert_eq!(page.can_edit, Some(true));
+        assert_eq!(page.content, Some(vec![crate::Node::text("a")]));
+        let theirs = account("theirs").await.unwrap();
+        let page = theirs.get_own_page("Hi-10-16", false).await.unwrap();
+        assert_eq!(page.can_edit, Some(false));
+
+        let requests = requests.lock().unwrap();
+        assert!(
+            requests[0].starts_with("GET /getPage/Hi-10-16?access_token=mine&return_content=true ")
+        );
+        assert!(requests[1]
+            .starts_with("GET /getPage/Hi-10-16?access_token=theirs&return_content=false "));
+    }
+
+    #[tokio::test]
+    async fn get_own_page_content() {
+        let (addr, _) = mock_server(vec![
+            (
+                "GET /getPage/Full",
+                "application/json",
+                br#"{"ok":true,"result":{"path":"Full","url":"https://telegra.ph/Full","title":"Full","description":"","content":["a",{"tag":"br"}],"views":0}}"#.to_vec(),
+            ),
+            (
+                "GET /getPage/Empty",
+                "application/json",
+                page_response("Empty", "Empty"),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let content = telegraph.get_own_page_content("Full").await.unwrap();
+        assert_eq!(
+            content,
+            vec![
+                crate::Node::text("a"),
+                crate::NodeElement::new("br").into_node()
+            ]
+        );
+        let missing = telegraph.get_own_page_content("Empty").await;
+        assert!(matches!(&missing, Err(crate::Error::MissingContent(path)) if path == "Empty"));
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            "page Empty has no content"
+        );
+    }
+
+    #[tokio::test]
+    async fn content_encoding() {
+        let texts = [
+            "a+b",
+            "100% done",
+            "a & b = c",
+            "🎉 emoji",
+            "Привет, мир",
+            "你好",
+        ];
+        let content: Vec<crate::Node> = texts
+            .iter()
+            .map(|text| crate::NodeElement::new("p").child(*text).into())
+            .collect();
+        let content = crate::nodes_to_content_string(&content);
+        let page = format!(
+            r#"{{"ok":true,"result":{{"path":"Hi-10-16","url":"https://telegra.ph/Hi-10-16","title":"Привет + 🎉","description":"","content":{},"views":0}}}}"#,
+            content
+        );
+        let (addr, requests) = mock_server(vec![
+            (
+                "POST /createPage",
+                "application/json",
+                page.clone().into_bytes(),
+            ),
+            (
+                "GET /getPage/Hi-10-16",
+                "application/json",
+                page.into_bytes(),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        let created = telegraph
+            .create_page("Привет + 🎉", &content, false)
+            .await
+            .unwrap();
+
+        let request = requests.lock().unwrap().remove(0);
+        let body = request.split("\r\n\r\n").nth(1).unwrap();
+        assert!(body.is_ascii());
+        assert!(body.contains("a%2Bb") && body.contains("100%25+done"));
+        assert_eq!(form_field(&request, "title").unwrap(), "Привет + 🎉");
+        assert_eq!(form_field(&request, "content").unwrap(), content);
+
+        let fetched: crate::Page = Telegraph::get_page_from(&addr, &created.path, true)
+            .await
+            .unwrap();
+        assert_eq!(fetched.title, "Привет + 🎉");
+        let fetched = crate::node_to_text(&fetched.content.unwrap());
+        assert_eq!(fetched.lines().collect::<Vec<_>>(), texts);
+    }
+
+    #[cfg(feature = "html")]
     #[test]
     fn html_to_node() {
         let html = r#"<a>Text</a><p>img:<img src="https://me"></p>"#;
@@ -422,7 +1766,7 @@ mod tests {
 
     #[tokio::test]
     async fn create_and_revoke_account() {
-        let result = Telegraph::create_account("sample", "a", None).await;
+        let result = Telegraph::create_account(crate::API_URL, "sample", "a", None).await;
         println!("{:?}", result);
         assert!(result.is_ok());
 
@@ -474,7 +1818,7 @@ mod tests {
         let page = telegraph
             .create_page(
                 "OVO",
-                r#"[{"tag":"p","children":["Hello,+world!"]}]"#,
+                r#"[{"tag":"p","children":["Hello, world!"]}]"#,
                 false,
             )
             .await;
@@ -489,7 +1833,7 @@ mod tests {
             .edit_page(
                 &page.unwrap().path,
                 "QAQ",
-                r#"[{"tag":"p","children":["Goodbye,+world!"]}]"#,
+                r#"[{"tag":"p","children":["Goodbye, world!"]}]"#,
                 false,
             )
             .await;
@@ -511,7 +1855,7 @@ mod tests {
 
     #[tokio::test]
     async fn get_views() {
-        let views = Telegraph::get_views("Sample-Page-12-15", &vec![2016, 12]).await;
+        let views = Telegraph::get_views("Sample-Page-12-15", &[2016, 12]).await;
         println!("{:?}", views);
         assert!(views.is_ok());
     }
@@ -520,8 +1864,84 @@ mod tests {
     #[tokio::test]
     #[cfg(feature = "upload")]
     async fn upload() {
-        let images = Telegraph::upload(&vec!["1.jpeg", "2.jpeg"]).await;
+        let images = Telegraph::upload(&["1.jpeg", "2.jpeg"]).await;
         println!("{:?}", images);
         assert!(images.is_ok());
     }
+
+    #[tokio::test]
+    #[cfg(all(feature = "upload", feature = "html"))]
+    async fn upload_data_images() {
+        let html =
+            r#"<p><img src="data:image/png;base64,iVBORw0KGgo="><img src="/file/old.jpg"></p>"#;
+        let mut nodes = crate::html_to_nodes(html).unwrap();
+        let (addr, requests) = mock_server(vec![(
+            "POST /upload",
+            "application/json",
+            br#"[{"src":"/file/new.png"}]"#.to_vec(),
+        )])
+        .await;
+        let url = format!("{}/upload", addr);
+        let images = Telegraph::upload_data_images_to(&url, &mut nodes, &reqwest::Client::new())
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 1);
+
+        let request = requests.lock().unwrap().remove(0);
+        assert!(request.contains("Content-Type: image/png"));
+        assert!(request.contains(r#"filename="file.png""#));
+        assert_eq!(
+            serde_json::to_string(&nodes).unwrap(),
+            r#"[{"tag":"p","children":[{"tag":"img","attrs":{"src":"/file/new.png"}},{"tag":"img","attrs":{"src":"/file/old.jpg"}}]}]"#
+        );
+    }
+
+    #[cfg(all(feature = "upload", feature = "html"))]
+    #[tokio::test]
+    async fn upload_extracted_images() {
+        use crate::{extract_images, node_at_mut, Node};
+
+        let dir = std::env::temp_dir().join(format!("telegraph-rs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cat = dir.join("cat.png");
+        let dog = dir.join("dog.mp4");
+        std::fs::write(&cat, b"cat").unwrap();
+        std::fs::write(&dog, b"dog").unwrap();
+
+        let html = format!(
+            r#"<figure><img src="{}"><figcaption>A <b>cat</b></figcaption></figure><ul><li>a <video src="{}"></video></li></ul>"#,
+            cat.display(),
+            dog.display()
+        );
+        let mut nodes = crate::html_to_nodes(&html).unwrap();
+        let images = extract_images(&nodes);
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].caption.as_deref(), Some("A cat"));
+        assert_eq!(images[1].caption, None);
+        assert_eq!(images[1].path, [1, 0, 1]);
+
+        let (addr, _) = mock_server(vec![(
+            "POST /upload",
+            "application/json",
+            br#"[{"src":"/file/cat.png"},{"src":"/file/dog.mp4"}]"#.to_vec(),
+        )])
+        .await;
+        let files = images.iter().map(|image| &image.src).collect::<Vec<_>>();
+        let uploaded =
+            Telegraph::upload_to(&format!("{}/upload", addr), &files, &reqwest::Client::new())
+                .await
+                .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for (image, info) in images.iter().zip(uploaded) {
+            if let Some(Node::NodeElement(element)) = node_at_mut(&mut nodes, &image.path) {
+                element.attrs.get_or_insert_with(Default::default).src = Some(info.src);
+            }
+        }
+        let srcs = extract_images(&nodes)
+            .into_iter()
+            .map(|image| image.src)
+            .collect::<Vec<_>>();
+        assert_eq!(srcs, ["/file/cat.png", "/file/dog.mp4"]);
+    }
 }
@@ -0,0 +1,982 @@
+//! Convert markdown into telegraph nodes
+use crate::{
+    anchor_slug, validate_nodes, Error, Node, NodeAttrs, NodeElement, Page, Result, Telegraph,
+};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+
+/// How to show the language of fenced code blocks, which Telegraph can't store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CodeLanguage {
+    /// Drop the language
+    #[default]
+    Ignore,
+    /// Add a comment line like `// rust` at the start of the block
+    Comment,
+    /// Wrap the block in a `figure` with the language as `figcaption`
+    Caption,
+}
+
+/// How to convert tables, which are not supported by Telegraph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableStyle {
+    /// Render the table as column-aligned plain text in a `pre` block, keeping only the text of cells
+    #[default]
+    Preformatted,
+    /// Render each row as a list item with a `Header: value` line per cell
+    List,
+}
+
+/// Options for [`markdown_to_nodes_with_options`]
+///
+/// ```
+/// use telegraph_rs::{CodeLanguage, MarkdownOptions};
+///
+/// let options = MarkdownOptions::new().code_language(CodeLanguage::Comment);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarkdownOptions {
+    code_language: CodeLanguage,
+    inline_images: bool,
+    notes_heading: String,
+    tables: TableStyle,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            code_language: CodeLanguage::default(),
+            inline_images: false,
+            notes_heading: "Notes".to_owned(),
+            tables: TableStyle::default(),
+        }
+    }
+}
+
+impl MarkdownOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How to show the language of fenced code blocks, [`CodeLanguage::Ignore`] by default.
+    pub fn code_language(mut self, code_language: CodeLanguage) -> Self {
+        self.code_language = code_language;
+        self
+    }
+
+    /// Keep images in paragraphs with other text as bare `img`, instead of splitting the paragraph
+    /// around their figures, false by default.
+    ///
+    /// Useful for small images like emojis.
+    pub fn inline_images(mut self, inline_images: bool) -> Self {
+        self.inline_images = inline_images;
+        self
+    }
+
+    /// Heading of the footnotes section, `Notes` by default.
+    pub fn notes_heading(mut self, notes_heading: &str) -> Self {
+        self.notes_heading = notes_heading.to_owned();
+        self
+    }
+
+    /// How to convert tables, [`TableStyle::Preformatted`] by default.
+    pub fn tables(mut self, tables: TableStyle) -> Self {
+        self.tables = tables;
+        self
+    }
+}
+
+/// Parse markdown to node string
+///
+/// ```rust
+/// use telegraph_rs::markdown_to_node;
+///
+/// let node = markdown_to_node("Hello, *world*").unwrap();
+/// assert_eq!(node, r#"[{"tag":"p","children":["Hello, ",{"tag":"em","children":["world"]}]}]"#);
+/// ```
+pub fn markdown_to_node(md: &str) -> Result<String> {
+    let nodes = markdown_to_nodes(md)?;
+    Ok(serde_json::to_string(&nodes).unwrap())
+}
+
+/// Parse markdown to typed nodes, see [`markdown_to_nodes_with_options`]
+///
+/// ```rust
+/// use telegraph_rs::{markdown_to_nodes, Node};
+///
+/// let nodes = markdown_to_nodes("# Title\n\n---").unwrap();
+/// match &nodes[0] {
+///     Node::NodeElement(element) => assert_eq!(element.tag, "h3"),
+///     Node::Text(_) => unreachable!(),
+/// }
+/// ```
+pub fn markdown_to_nodes(md: &str) -> Result<Vec<Node>> {
+    markdown_to_nodes_with_options(md, &MarkdownOptions::default())
+}
+
+/// Parse markdown to typed nodes with custom options
+///
+/// Only tags accepted by Telegraph are emitted:
+///
+/// - `#` and `##` headings become `h3` and `h4`, `#####` and `######` become bold paragraphs
+/// - images become a `figure` with their alt text as `figcaption`, and a link around an image
+///   becomes a link in the caption. Figures are moved out of paragraphs, and turned back into
+///   `img` in other elements
+/// - fenced and indented code blocks become `pre` with a `code` inside, keeping their text as is
+///   except for the final line break
+/// - `~~strikethrough~~` becomes `s`, and task list items start with ☑ or ☐
+/// - tables are converted as set by [`MarkdownOptions::tables`]
+/// - footnote references become links like `[1]` to a section with the footnotes at the end,
+///   since Telegraph only has anchors for headings
+/// - inline html is dropped, html blocks are converted with
+///   [`ConversionOptions::sanitized`](crate::ConversionOptions::sanitized) if the `html` feature is enabled
+pub fn markdown_to_nodes_with_options(md: &str, options: &MarkdownOptions) -> Result<Vec<Node>> {
+    let mut stack = vec![Frame::new(Kind::Root)];
+    let mut footnotes = Footnotes::default();
+    let extensions = Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(md, extensions) {
+        match event {
+            Event::Start(tag) => stack.push(Frame::new(Kind::from(tag))),
+            Event::End(_) => {
+                let frame = stack.pop().expect("unbalanced markdown events");
+                if let Kind::Footnote(label) = frame.kind {
+                    footnotes.define(&label, frame.children);
+                    continue;
+                }
+                let parent = &mut stack
+                    .last_mut()
+                    .expect("unbalanced markdown events")
+                    .children;
+                frame.finish(parent, options);
+            }
+            event => {
+                let children = &mut stack.last_mut().unwrap().children;
+                match event {
+                    Event::Text(text) => push_text(children, &text),
+                    Event::Code(code) => children.push(
+                        NodeElement::new("code")
+                            .child(code.into_string())
+                            .into_node(),
+                    ),
+                    Event::SoftBreak => push_text(children, " "),
+                    Event::HardBreak => children.push(Node::NodeElement(NodeElement::br())),
+                    Event::Rule => children.push(Node::NodeElement(NodeElement::hr())),
+                    Event::Html(html) => push_text(children, &html),
+                    Event::TaskListMarker(checked) => {
+                        push_text(children, if checked { "☑ " } else { "☐ " })
+                    }
+                    Event::FootnoteReference(label) => {
+                        let number = footnotes.number(&label);
+                        let href = format!("#{}", anchor_slug(&options.notes_heading));
+                        let link = NodeElement::new("a")
+                            .with_href(&href)
+                            .child(format!("[{}]", number));
+                        children.push(link.into_node());
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    let mut nodes = stack.pop().map(|frame| frame.children).unwrap_or_default();
+    footnotes.finish(&mut nodes, options);
+    Ok(nodes)
+}
+
+impl Telegraph {
+    /// Convert `markdown` with [`markdown_to_nodes`] and create a page with it
+    ///
+    /// The converted content is always checked with [`validate_nodes`], so a failed conversion is
+    /// reported as [`Error::InvalidContent`] before anything is sent, unlike errors of the api.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::Telegraph;
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let page = telegraph
+    ///     .create_page_md("Hello", "# Hello\n\nA page written in **markdown**.", false)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_page_md(
+        &self,
+        title: &str,
+        markdown: &str,
+        return_content: bool,
+    ) -> Result<Page> {
+        let content = markdown_content(markdown)?;
+        self.create_page(title, &content, return_content).await
+    }
+
+    /// Convert `markdown` like [`Telegraph::create_page_md`] and replace the content of the page at `path`
+    pub async fn edit_page_md(
+        &self,
+        path: &str,
+        title: &str,
+        markdown: &str,
+        return_content: bool,
+    ) -> Result<Page> {
+        let content = markdown_content(markdown)?;
+        self.edit_page(path, title, &content, return_content).await
+    }
+
+    /// Like [`Telegraph::edit_page_md`], but skip the request if [`markdown_hash`] of `title` and
+    /// `markdown` equals `last_hash`
+    ///
+    /// Returns `None` if the page was not edited.
+    ///
+    /// ```
+    /// # async fn run(telegraph: telegraph_rs::Telegraph, last_hash: u64) -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::markdown_hash;
+    ///
+    /// let (title, markdown) = ("Hello", "# Hello\n\nUpdated from the source.");
+    /// if let Some(page) = telegraph
+    ///     .edit_page_md_if_changed("Hello-10-16", title, markdown, last_hash)
+    ///     .await?
+    /// {
+    ///     println!("updated {}, new hash {}", page.url, markdown_hash(title, markdown)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn edit_page_md_if_changed(
+        &self,
+        path: &str,
+        title: &str,
+        markdown: &str,
+        last_hash: u64,
+    ) -> Result<Option<Page>> {
+        let content = markdown_content(markdown)?;
+        if content_hash(title, &content) == last_hash {
+            return Ok(None);
+        }
+        let page = self.edit_page(path, title, &content, false).await?;
+        Ok(Some(page))
+    }
+}
+
+/// Hash of `title` and the content converted from `markdown`, for [`Telegraph::edit_page_md_if_changed`]
+///
+/// The hash is stable across runs and versions of Rust, so it can be stored next to the source.
+/// Markdown converted to the same content, like `*text*` and `_text_`, has the same hash.
+///
+/// ```
+/// use telegraph_rs::markdown_hash;
+///
+/// let a = markdown_hash("Title", "Some *text*").unwrap();
+/// assert_eq!(a, markdown_hash("Title", "Some _text_").unwrap());
+/// assert_ne!(a, markdown_hash("Other title", "Some *text*").unwrap());
+/// ```
+pub fn markdown_hash(title: &str, markdown: &str) -> Result<u64> {
+    Ok(content_hash(title, &markdown_content(markdown)?))
+}
+
+/// 64-bit FNV-1a hash of the title and serialized content
+fn content_hash(title: &str, content: &str) -> u64 {
+    let bytes = title.bytes().chain([0]).chain(content.bytes());
+    bytes.fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Convert and validate `markdown`, returning the serialized nodes
+fn markdown_content(markdown: &str) -> Result<String> {
+    let nodes = markdown_to_nodes(markdown)?;
+    validate_nodes(&nodes).map_err(Error::InvalidContent)?;
+    Ok(serde_json::to_string(&nodes).unwrap())
+}
+
+/// An element being built from the events between its start and end
+struct Frame {
+    kind: Kind,
+    children: Vec<Node>,
+}
+
+enum Kind {
+    Root,
+    /// Nested elements, outermost first
+    Element(&'static [&'static str], Option<NodeAttrs>),
+    /// A code block with its language
+    CodeBlock(Option<String>),
+    /// An image, whose children are its alt text
+    Image(String),
+    /// A table, whose children are `thead` and `tr` elements of `td`
+    Table,
+    /// A footnote definition with its label
+    Footnote(String),
+    /// Raw html, whose children are its text
+    Html,
+    /// A markdown element without counterpart, whose children are kept
+    Unwrap,
+}
+
+impl From<Tag<'_>> for Kind {
+    fn from(tag: Tag<'_>) -> Self {
+        let tags: &[&str] = match tag {
+            Tag::Paragraph => &["p"],
+            Tag::Heading { level, .. } => match level {
+                HeadingLevel::H1 | HeadingLevel::H3 => &["h3"],
+                HeadingLevel::H2 | HeadingLevel::H4 => &["h4"],
+                HeadingLevel::H5 | HeadingLevel::H6 => &["p", "strong"],
+            },
+            Tag::BlockQuote(_) => &["blockquote"],
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().map(str::to_owned)
+                    }
+                    CodeBlockKind::Indented => None,
+                };
+                return Kind::CodeBlock(language);
+            }
+            Tag::List(None) => &["ul"],
+            Tag::List(Some(_)) => &["ol"],
+            Tag::Item => &["li"],
+            Tag::Emphasis => &["em"],
+            Tag::Strong => &["strong"],
+            Tag::Strikethrough => &["s"],
+            Tag::Link { dest_url, .. } => {
+                let attrs = NodeAttrs::from_href(dest_url.into_string());
+                return Kind::Element(&["a"], Some(attrs));
+            }
+            Tag::Image { dest_url, .. } => return Kind::Image(dest_url.into_string()),
+            Tag::Table(_) => return Kind::Table,
+            Tag::TableHead => &["thead"],
+            Tag::TableRow => &["tr"],
+            Tag::TableCell => &["td"],
+            Tag::HtmlBlock => return Kind::Html,
+            Tag::FootnoteDefinition(label) => return Kind::Footnote(label.into_string()),
+            _ => return Kind::Unwrap,
+        };
+        Kind::Element(tags, None)
+    }
+}
+
+impl Frame {
+    fn new(kind: Kind) -> Self {
+        Frame {
+            kind,
+            children: vec![],
+        }
+    }
+
+    /// Append the converted nodes to `parent`
+    fn finish(self, parent: &mut Vec<Node>, options: &MarkdownOptions) {
+        match self.kind {
+            Kind::Root | Kind::Unwrap => parent.extend(self.children),
+            Kind::Element(["p"], _) => finish_paragraph(self.children, parent, options),
+            Kind::Element(["a"], Some(attrs)) if matches!(&*self.children, [node] if is_figure(node)) =>
+            {
+                let figure = self.children.into_iter().next().unwrap();
+                parent.push(link_figure(figure, attrs));
+            }
+            Kind::Element(tags, attrs) => {
+                let (outer, inner) = tags.split_first().unwrap();
+                let mut children = self.children.into_iter().map(unwrap_figure).collect();
+                for tag in inner.iter().rev() {
+                    children = vec![NodeElement::new(tag).children(children).into_node()];
+                }
+                let mut element = NodeElement::new(outer).children(children);
+                element.attrs = attrs;
+                parent.push(element.into_node());
+            }
+            Kind::CodeBlock(language) => {
+                let mut code = String::new();
+                crate::text_content(&self.children, &mut code);
+                if code.ends_with('\n') {
+                    code.pop();
+                }
+                if let (Some(language), CodeLanguage::Comment) = (&language, options.code_language)
+                {
+                    code.insert_str(0, &format!("{} {}\n", comment_prefix(language), language));
+                }
+                let pre = NodeElement::new("pre").child(NodeElement::new("code").child(code));
+                match (language, options.code_language) {
+                    (Some(language), CodeLanguage::Caption) => parent.push(
+                        NodeElement::new("figure")
+                            .child(pre)
+                            .child(NodeElement::new("figcaption").child(language))
+                            .into_node(),
+                    ),
+                    _ => parent.push(pre.into_node()),
+                }
+            }
+            Kind::Image(src) => {
+                let mut caption = String::new();
+                crate::text_content(&self.children, &mut caption);
+                let caption = caption.trim();
+                let caption = match caption.is_empty() {
+                    true => vec![],
+                    false => vec![Node::Text(caption.to_owned())],
+                };
+                parent.push(figure(NodeElement::img(&src), caption));
+            }
+            Kind::Table => parent.push(convert_table(self.children, options.tables)),
+            Kind::Html => parent.extend(convert_html(self.children)),
+            Kind::Footnote(_) => unreachable!("footnotes are collected separately"),
+        }
+    }
+}
+
+/// Convert the rows of a table, the first one is the header
+fn convert_table(rows: Vec<Node>, style: TableStyle) -> Node {
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let cells = match row {
+                Node::NodeElement(row) => row.children.unwrap_or_default(),
+                Node::Text(_) => vec![],
+            };
+            cells
+                .into_iter()
+                .map(|cell| match cell {
+                    Node::NodeElement(cell) => cell.children.unwrap_or_default(),
+                    Node::Text(_) => vec![],
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    match style {
+        TableStyle::Preformatted => {
+            let rows = rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| {
+                            let mut text = String::new();
+                            crate::text_content(cell, &mut text);
+                            text.trim().to_owned()
+                        })
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+            let table = crate::utils::format_table(&rows, true);
+            NodeElement::new("pre").child(table).into_node()
+        }
+        TableStyle::List => {
+            let mut rows = rows.into_iter();
+            let header = rows.next().unwrap_or_default();
+            let items = rows.map(|row| {
+                let mut children = vec![];
+                for (i, cell) in row.into_iter().enumerate() {
+                    if i > 0 {
+                        children.push(Node::NodeElement(NodeElement::br()));
+                    }
+                    let name = header.get(i).filter(|name| !name.is_empty());
+                    if let Some(name) = name {
+                        children.push(
+                            NodeElement::new("strong")
+                                .children(name.clone())
+                                .into_node(),
+                        );
+                        children.push(Node::Text(": ".to_owned()));
+                    }
+                    for node in cell {
+                        match node {
+                            Node::Text(text) => push_text(&mut children, &text),
+                            node => children.push(node),
+                        }
+                    }
+                }
+                NodeElement::new("li").children(children).into_node()
+            });
+            NodeElement::new("ul").children(items).into_node()
+        }
+    }
+}
+
+/// Footnotes in the order of their first reference
+#[derive(Default)]
+struct Footnotes {
+    /// Lowercased labels with the content of their definition
+    notes: Vec<(String, Option<Vec<Node>>)>,
+}
+
+impl Footnotes {
+    /// Number of the footnote `label`, starting from 1
+    fn number(&mut self, label: &str) -> usize {
+        let label = label.to_lowercase();
+        match self.notes.iter().position(|(l, _)| *l == label) {
+            Some(i) => i + 1,
+            None => {
+                self.notes.push((label, None));
+                self.notes.len()
+            }
+        }
+    }
+
+    fn define(&mut self, label: &str, content: Vec<Node>) {
+        let i = self.number(label) - 1;
+        self.notes[i].1 = Some(content);
+    }
+
+    /// Append a heading and a list with the defined footnotes
+    fn finish(self, nodes: &mut Vec<Node>, options: &MarkdownOptions) {
+        let items = self
+            .notes
+            .into_iter()
+            .filter_map(|(_, content)| {
+                let mut content = content?;
+                if let [Node::NodeElement(p)] = &mut *content {
+                    if p.tag == "p" {
+                        content = p.children.take().unwrap_or_default();
+                    }
+                }
+                Some(NodeElement::new("li").children(content).into_node())
+            })
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            return;
+        }
+        let heading = NodeElement::new("h4").child(&*options.notes_heading);
+        nodes.push(heading.into_node());
+        nodes.push(NodeElement::new("ol").children(items).into_node());
+    }
+}
+
+/// Move the figures of a paragraph after it, splitting the text around them
+fn finish_paragraph(children: Vec<Node>, parent: &mut Vec<Node>, options: &MarkdownOptions) {
+    let is_blank = |node: &Node| matches!(node, Node::Text(text) if text.trim().is_empty());
+    let has_text = children
+        .iter()
+        .any(|node| !is_figure(node) && !is_blank(node));
+    if options.inline_images && has_text {
+        let children = children.into_iter().map(unwrap_figure);
+        parent.push(NodeElement::new("p").children(children).into_node());
+        return;
+    }
+
+    let mut run = vec![];
+    for node in children {
+        if !is_figure(&node) {
+            run.push(node);
+            continue;
+        }
+        push_run(&mut run, parent);
+        parent.push(node);
+    }
+    push_run(&mut run, parent);
+
+    fn push_run(run: &mut Vec<Node>, parent: &mut Vec<Node>) {
+        if let Some(Node::Text(text)) = run.first_mut() {
+            *text = text.trim_start().to_owned();
+        }
+        if let Some(Node::Text(text)) = run.last_mut() {
+            text.truncate(text.trim_end().len());
+        }
+        run.retain(|node| !matches!(node, Node::Text(text) if text.is_empty()));
+        if !run.is_empty() {
+            parent.push(NodeElement::new("p").children(run.drain(..)).into_node());
+        }
+    }
+}
+
+fn figure(img: NodeElement, caption: Vec<Node>) -> Node {
+    let mut figure = NodeElement::new("figure").child(img);
+    if !caption.is_empty() {
+        figure.push_child(NodeElement::new("figcaption").children(caption));
+    }
+    figure.into_node()
+}
+
+/// Whether `node` is the figure of an image
+fn is_figure(node: &Node) -> bool {
+    match node {
+        Node::NodeElement(element) if element.tag == "figure" => matches!(
+            element.children.as_deref(),
+            Some([Node::NodeElement(img), ..]) if img.tag == "img"
+        ),
+        _ => false,
+    }
+}
+
+/// Turn the figure of a linked image into one with the link as caption,
+/// showing the alt text or the link target
+fn link_figure(figure: Node, attrs: NodeAttrs) -> Node {
+    let Node::NodeElement(figure) = figure else {
+        return figure;
+    };
+    let mut children = figure.children.unwrap_or_default().into_iter();
+    let img = match children.next() {
+        Some(Node::NodeElement(img)) => img,
+        _ => unreachable!("figures start with an image"),
+    };
+    let text = match children.next() {
+        Some(Node::NodeElement(caption)) => caption.children.unwrap_or_default(),
+        _ => vec![Node::Text(attrs.href.clone().unwrap_or_default())],
+    };
+    let mut link = NodeElement::new("a").children(text);
+    link.attrs = Some(attrs);
+    self::figure(img, vec![link.into_node()])
+}
+
+/// Turn a figure back into its image, keeping the link of its caption
+fn unwrap_figure(node: Node) -> Node {
+    let figure = match node {
+        Node::NodeElement(figure) if is_figure(&node) => figure,
+        node => return node,
+    };
+    let mut children = figure.children.unwrap_or_default().into_iter();
+    let img = children.next().unwrap();
+    let link = children
+        .next()
+        .and_then(|caption| match caption {
+            Node::NodeElement(caption) => caption.children?.into_iter().next(),
+            Node::Text(_) => None,
+        })
+        .and_then(|link| match link {
+            Node::NodeElement(link) if link.tag == "a" => link.attrs,
+            _ => None,
+        });
+    match link {
+        Some(attrs) => {
+            let mut link = NodeElement::new("a").child(img);
+            link.attrs = Some(attrs);
+            link.into_node()
+        }
+        None => img,
+    }
+}
+
+/// Start of a line comment in `language`
+fn comment_prefix(language: &str) -> &'static str {
+    match &*language.to_ascii_lowercase() {
+        "bash" | "sh" | "shell" | "zsh" | "fish" | "python" | "py" | "ruby" | "rb" | "perl"
+        | "r" | "yaml" | "yml" | "toml" | "ini" | "dockerfile" | "makefile" | "nix" => "#",
+        "sql" | "lua" | "haskell" | "hs" | "elm" => "--",
+        "lisp" | "clojure" | "scheme" | "asm" => ";",
+        "tex" | "latex" | "erlang" | "matlab" => "%",
+        _ => "//",
+    }
+}
+
+#[cfg(feature = "html")]
+fn convert_html(text: Vec<Node>) -> Vec<Node> {
+    let mut html = String::new();
+    crate::text_content(&text, &mut html);
+    let options = crate::ConversionOptions::sanitized().normalize_whitespace(true);
+    crate::html_to_nodes_with_options(&html, &options).unwrap_or_default()
+}
+
+#[cfg(not(feature = "html"))]
+fn convert_html(_text: Vec<Node>) -> Vec<Node> {
+    vec![]
+}
+
+/// Push `text`, merging it with a preceding text node
+fn push_text(children: &mut Vec<Node>, text: &str) {
+    match children.last_mut() {
+        Some(Node::Text(last)) => last.push_str(text),
+        _ => children.push(Node::Text(text.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{client, form_field, mock_server, page_response};
+
+    const MARKDOWN: &str = r#"# Title
+
+Some *emphasis*, **strong** text and `inline code`
+with a [link](https://example.com/ "Example") and a hard\
+break.
+
+## Section
+
+> Quoted **text**
+
+- one
+- two
+  1. nested
+  2. list
+
+![Alt text](/file/a.jpg)
+
+```
+fn main() {}
+```
+
+##### Small heading
+
+***
+"#;
+
+    #[cfg(feature = "html")]
+    const HTML: &str = r#"<h1>Title</h1>
+<p>Some <em>emphasis</em>, <strong>strong</strong> text and <code>inline code</code>
+with a <a href="https://example.com/" title="Example">link</a> and a hard<br>break.</p>
+<h2>Section</h2>
+<blockquote><p>Quoted <strong>text</strong></p></blockquote>
+<ul><li>one</li><li>two<ol><li>nested</li><li>list</li></ol></li></ul>
+<figure><img src="/file/a.jpg" alt="Alt text"><figcaption>Alt text</figcaption></figure>
+<pre><code>fn main() {}</code></pre>
+<h5>Small heading</h5>
+<hr>
+"#;
+
+    #[test]
+    fn allowed_tags() {
+        let nodes = markdown_to_nodes(MARKDOWN).unwrap();
+        assert_eq!(crate::validate_nodes(&nodes), Ok(()));
+    }
+
+    #[cfg(feature = "html")]
+    #[test]
+    fn same_as_html() {
+        let options = crate::ConversionOptions::sanitized()
+            .downgrade_headings(true)
+            .normalize_whitespace(true);
+        let expected = crate::html_to_nodes_with_options(HTML, &options).unwrap();
+        assert_eq!(markdown_to_nodes(MARKDOWN).unwrap(), expected);
+    }
+
+    #[test]
+    fn html_blocks() {
+        let nodes = markdown_to_nodes("<div>\n<b>bold</b>\n</div>\n\ntext <kbd>key</kbd>").unwrap();
+        let json = serde_json::to_string(&nodes).unwrap();
+        #[cfg(feature = "html")]
+        assert_eq!(
+            json,
+            r#"[{"tag":"b","children":["bold"]},{"tag":"p","children":["text key"]}]"#
+        );
+        #[cfg(not(feature = "html"))]
+        assert_eq!(json, r#"[{"tag":"p","children":["text key"]}]"#);
+    }
+
+    fn code(md: &str, options: &MarkdownOptions) -> String {
+        let nodes = markdown_to_nodes_with_options(md, options).unwrap();
+        serde_json::to_string(&nodes).unwrap()
+    }
+
+    #[test]
+    fn code_blocks() {
+        let options = MarkdownOptions::new();
+        assert_eq!(
+            code("    let x = 1;\n\n        indented\n\ttab\n", &options),
+            r#"[{"tag":"pre","children":[{"tag":"code","children":["let x = 1;\n\n    indented\ntab"]}]}]"#
+        );
+        assert_eq!(
+            code("```html\n<b>not bold</b> &amp;\n\n\n```", &options),
+            r#"[{"tag":"pre","children":[{"tag":"code","children":["<b>not bold</b> &amp;\n\n"]}]}]"#
+        );
+        assert_eq!(
+            code("````\n```rust\n  inner\n```\n````", &options),
+            r#"[{"tag":"pre","children":[{"tag":"code","children":["```rust\n  inner\n```"]}]}]"#
+        );
+    }
+
+    #[test]
+    fn code_language() {
+        let md = "```rust title\nfn main() {}\n```\n\n```python\npass\n```";
+        let options = MarkdownOptions::new().code_language(CodeLanguage::Comment);
+        assert_eq!(
+            code(md, &options),
+            concat!(
+                r#"[{"tag":"pre","children":[{"tag":"code","children":["// rust\nfn main() {}"]}]},"#,
+                r##"{"tag":"pre","children":[{"tag":"code","children":["# python\npass"]}]}]"##,
+            )
+        );
+        let options = MarkdownOptions::new().code_language(CodeLanguage::Caption);
+        assert_eq!(
+            code("```rust\nfn main() {}\n```", &options),
+            concat!(
+                r#"[{"tag":"figure","children":[{"tag":"pre","children":[{"tag":"code","children":["fn main() {}"]}]},"#,
+                r#"{"tag":"figcaption","children":["rust"]}]}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn image_captions() {
+        let md = "![A cat](/file/cat.jpg \"Title\")\n\n![][dog]\n\n[dog]: /file/dog.jpg";
+        assert_eq!(
+            code(md, &MarkdownOptions::new()),
+            concat!(
+                r#"[{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/cat.jpg"}},"#,
+                r#"{"tag":"figcaption","children":["A cat"]}]},"#,
+                r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/dog.jpg"}}]}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn linked_images() {
+        let md = "[![A cat](/file/cat.jpg)](https://cats.example/)\n\n[![](/file/dog.jpg)](/dog)";
+        assert_eq!(
+            code(md, &MarkdownOptions::new()),
+            concat!(
+                r#"[{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/cat.jpg"}},"#,
+                r#"{"tag":"figcaption","children":[{"tag":"a","attrs":{"href":"https://cats.example/"},"children":["A cat"]}]}]},"#,
+                r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/dog.jpg"}},"#,
+                r#"{"tag":"figcaption","children":[{"tag":"a","attrs":{"href":"/dog"},"children":["/dog"]}]}]}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn inline_images() {
+        let md = "Before ![smile](/file/smile.png) [![x](/file/x.png)](/x) after";
+        assert_eq!(
+            code(md, &MarkdownOptions::new()),
+            concat!(
+                r#"[{"tag":"p","children":["Before"]},"#,
+                r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/smile.png"}},{"tag":"figcaption","children":["smile"]}]},"#,
+                r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/x.png"}},"#,
+                r#"{"tag":"figcaption","children":[{"tag":"a","attrs":{"href":"/x"},"children":["x"]}]}]},"#,
+                r#"{"tag":"p","children":["after"]}]"#,
+            )
+        );
+        let options = MarkdownOptions::new().inline_images(true);
+        assert_eq!(
+            code(md, &options),
+            concat!(
+                r#"[{"tag":"p","children":["Before ",{"tag":"img","attrs":{"src":"/file/smile.png"}}," ","#,
+                r#"{"tag":"a","attrs":{"href":"/x"},"children":[{"tag":"img","attrs":{"src":"/file/x.png"}}]}," after"]}]"#,
+            )
+        );
+        assert_eq!(
+            code("- *![a](/a.png)*", &options),
+            r#"[{"tag":"ul","children":[{"tag":"li","children":[{"tag":"em","children":[{"tag":"img","attrs":{"src":"/a.png"}}]}]}]}]"#
+        );
+    }
+
+    #[test]
+    fn nested_code_caption() {
+        let options = MarkdownOptions::new().code_language(CodeLanguage::Caption);
+        assert_eq!(
+            code("> ```sh\n> ls\n> ```", &options),
+            concat!(
+                r#"[{"tag":"blockquote","children":[{"tag":"figure","children":["#,
+                r#"{"tag":"pre","children":[{"tag":"code","children":["ls"]}]},{"tag":"figcaption","children":["sh"]}]}]}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn footnotes() {
+        let md = "Text[^a] and more[^B].\n\nAgain[^a].\n\n[^a]: First note.\n\n[^b]: Second note,\n\n    in two *paragraphs*.\n";
+        assert_eq!(
+            code(md, &MarkdownOptions::new()),
+            concat!(
+                r##"[{"tag":"p","children":["Text",{"tag":"a","attrs":{"href":"#Notes"},"children":["[1]"]},"##,
+                r##"" and more",{"tag":"a","attrs":{"href":"#Notes"},"children":["[2]"]},"."]},"##,
+                r##"{"tag":"p","children":["Again",{"tag":"a","attrs":{"href":"#Notes"},"children":["[1]"]},"."]},"##,
+                r##"{"tag":"h4","children":["Notes"]},{"tag":"ol","children":["##,
+                r##"{"tag":"li","children":["First note."]},"##,
+                r##"{"tag":"li","children":[{"tag":"p","children":["Second note,"]},"##,
+                r##"{"tag":"p","children":["in two ",{"tag":"em","children":["paragraphs"]},"."]}]}]}]"##,
+            )
+        );
+
+        let options = MarkdownOptions::new().notes_heading("Footnotes and sources");
+        let nodes = markdown_to_nodes_with_options("x[^1]\n\n[^1]: y", &options).unwrap();
+        assert!(serde_json::to_string(&nodes)
+            .unwrap()
+            .contains(r##"{"href":"#Footnotes-and-sources"}"##));
+    }
+
+    #[test]
+    fn tables() {
+        let md = "| Name | Link |\n| :--- | ---: |\n| Telegraph | [the site](https://telegra.ph/) |\n| Rust | *none* |\n";
+        assert_eq!(
+            code(md, &MarkdownOptions::new()),
+            r#"[{"tag":"pre","children":["Name       Link\n-------------------\nTelegraph  the site\nRust       none"]}]"#
+        );
+        let options = MarkdownOptions::new().tables(TableStyle::List);
+        assert_eq!(
+            code(md, &options),
+            concat!(
+                r#"[{"tag":"ul","children":[{"tag":"li","children":["#,
+                r#"{"tag":"strong","children":["Name"]},": Telegraph",{"tag":"br"},"#,
+                r#"{"tag":"strong","children":["Link"]},": ",{"tag":"a","attrs":{"href":"https://telegra.ph/"},"children":["the site"]}]},"#,
+                r#"{"tag":"li","children":[{"tag":"strong","children":["Name"]},": Rust",{"tag":"br"},"#,
+                r#"{"tag":"strong","children":["Link"]},": ",{"tag":"em","children":["none"]}]}]}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn strikethrough_and_tasks() {
+        let md = "- [x] done ~~and *gone*~~\n  - [ ] todo with **bold ~~strike~~**\n    - ~~nested~~ [x] not a task\n- [ ] ~~last~~";
+        assert_eq!(
+            code(md, &MarkdownOptions::new()),
+            concat!(
+                r#"[{"tag":"ul","children":["#,
+                r#"{"tag":"li","children":["☑ done ",{"tag":"s","children":["and ",{"tag":"em","children":["gone"]}]},"#,
+                r#"{"tag":"ul","children":[{"tag":"li","children":["☐ todo with ",{"tag":"strong","children":["bold ",{"tag":"s","children":["strike"]}]},"#,
+                r#"{"tag":"ul","children":[{"tag":"li","children":[{"tag":"s","children":["nested"]}," [x] not a task"]}]}]}]}]},"#,
+                r#"{"tag":"li","children":["☐ ",{"tag":"s","children":["last"]}]}]}]"#,
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn create_page_md() {
+        let (addr, requests) = mock_server(vec![(
+            "POST /createPage",
+            "application/json",
+            page_response("Hello-10-16", "Hello"),
+        )])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let md = "# Hello\n\nA page written in **markdown**.";
+        let page = telegraph.create_page_md("Hello", md, false).await.unwrap();
+        assert_eq!(page.path, "Hello-10-16");
+
+        let content = form_field(&requests.lock().unwrap()[0], "content").unwrap();
+        assert_eq!(content, markdown_to_node(md).unwrap());
+    }
+
+    #[tokio::test]
+    async fn edit_page_md() {
+        let (addr, requests) = mock_server(vec![
+            (
+                "POST /createPage",
+                "application/json",
+                page_response("Hello-10-16", "Hello"),
+            ),
+            (
+                "POST /editPage",
+                "application/json",
+                page_response("Hello-10-16", "Hello"),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        telegraph
+            .create_page_md("Hello", MARKDOWN, false)
+            .await
+            .unwrap();
+        telegraph
+            .edit_page_md("Hello-10-16", "Hello", MARKDOWN, false)
+            .await
+            .unwrap();
+        let hash = markdown_hash("Hello", MARKDOWN).unwrap();
+        let page = telegraph
+            .edit_page_md_if_changed("Hello-10-16", "Hello", MARKDOWN, hash)
+            .await
+            .unwrap();
+        assert!(page.is_none());
+        let page = telegraph
+            .edit_page_md_if_changed("Hello-10-16", "Hi", MARKDOWN, hash)
+            .await
+            .unwrap();
+        assert!(page.is_some());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].starts_with("POST /editPage"));
+        let created = form_field(&requests[0], "content").unwrap();
+        assert_eq!(form_field(&requests[1], "content").unwrap(), created);
+        assert_eq!(form_field(&requests[2], "content").unwrap(), created);
+        assert_eq!(form_field(&requests[1], "path").unwrap(), "Hello-10-16");
+    }
+}
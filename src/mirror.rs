@@ -0,0 +1,290 @@
+//! Mirror remote images to telegraph
+use crate::{bytes_part, guess_mime, Error, ImageInfo, Node, Result, Telegraph, Uploadable};
+use futures_util::{stream, StreamExt};
+use reqwest::{header::CONTENT_TYPE, multipart::Part, Url};
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Hosts whose files are already served by telegraph
+const TELEGRAPH_HOSTS: &[&str] = &["telegra.ph", "graph.org"];
+
+/// Options for [`Telegraph::mirror_images`]
+///
+/// ```
+/// use telegraph_rs::MirrorOptions;
+///
+/// let options = MirrorOptions::new().concurrency(2).max_bytes(10 << 20);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MirrorOptions {
+    concurrency: usize,
+    max_bytes: u64,
+}
+
+impl Default for MirrorOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            max_bytes: 50 << 20,
+        }
+    }
+}
+
+impl MirrorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of images downloaded and uploaded at the same time, 4 by default.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Total number of bytes downloaded in one pass, 50 MiB by default.
+    ///
+    /// Images which would exceed the limit fail with [`Error::DownloadLimit`].
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+/// Result of [`Telegraph::mirror_images`]
+#[derive(Debug, Default)]
+pub struct MirrorReport {
+    /// Mirrored urls and the uploaded images
+    pub images: Vec<(String, ImageInfo)>,
+    /// Urls which failed to mirror, the nodes referencing them are left untouched
+    pub errors: Vec<(String, Error)>,
+}
+
+struct Download {
+    mime: String,
+    bytes: Vec<u8>,
+}
+
+impl Uploadable for Download {
+    fn part(&self) -> Result<Part> {
+        bytes_part(self.bytes.clone(), &self.mime)
+    }
+}
+
+impl Telegraph {
+    /// Download every external `img` and `video` in `nodes`, upload them to telegraph
+    /// and replace their `src` with the uploaded ones.
+    ///
+    /// Failures of single images are collected in the report instead of aborting the whole pass.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{html_to_nodes, MirrorOptions, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let mut nodes = html_to_nodes(r#"<img src="https://example.com/cat.jpg">"#)?;
+    /// let report = telegraph.mirror_images(&mut nodes, &MirrorOptions::new()).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mirror_images(&self, nodes: &mut [Node], options: &MirrorOptions) -> MirrorReport {
+        self.mirror_images_to("https://telegra.ph/upload", nodes, options)
+            .await
+    }
+
+    pub(crate) async fn mirror_images_to(
+        &self,
+        upload_url: &str,
+        nodes: &mut [Node],
+        options: &MirrorOptions,
+    ) -> MirrorReport {
+        fn collect<'a>(nodes: &'a mut [Node], found: &mut Vec<&'a mut String>) {
+            for node in nodes {
+                if let Node::NodeElement(element) = node {
+                    if ["img", "video"].contains(&&*element.tag.to_ascii_lowercase()) {
+                        if let Some(src) = element.attrs.as_mut().and_then(|a| a.src.as_mut()) {
+                            if is_external(src) {
+                                found.push(src);
+                            }
+                        }
+                    }
+                    collect(element.children.as_deref_mut().unwrap_or_default(), found);
+                }
+            }
+        }
+
+        let mut found = vec![];
+        collect(nodes, &mut found);
+
+        let mut urls = found.iter().map(|src| src.to_string()).collect::<Vec<_>>();
+        urls.sort();
+        urls.dedup();
+
+        let downloaded = AtomicU64::new(0);
+        let results = stream::iter(urls)
+            .map(|url| async {
+                let result = async {
+                    let file = self.download(&url, &downloaded, options.max_bytes).await?;
+                    let mut images = Self::upload_to(upload_url, &[file], &self.client).await?;
+                    images
+                        .pop()
+                        .ok_or_else(|| Error::ApiError("empty upload result".to_owned()))
+                }
+                .await;
+                (url, result)
+            })
+            .buffer_unordered(options.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut report = MirrorReport::default();
+        for (url, result) in results {
+            match result {
+                Ok(image) => report.images.push((url, image)),
+                Err(e) => report.errors.push((url, e)),
+            }
+        }
+
+        let mirrored = report
+            .images
+            .iter()
+            .map(|(url, image)| (url.as_str(), image.src.as_str()))
+            .collect::<HashMap<_, _>>();
+        for src in found {
+            if let Some(mirrored) = mirrored.get(&**src) {
+                *src = mirrored.to_string();
+            }
+        }
+        report
+    }
+
+    async fn download(
+        &self,
+        url: &str,
+        downloaded: &AtomicU64,
+        max_bytes: u64,
+    ) -> Result<Download> {
+        let mut response = self.client.get(url).send().await?.error_for_status()?;
+        let mime = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|mime| mime.to_str().ok())
+            .and_then(|mime| mime.split(';').next())
+            .map(|mime| mime.trim().to_ascii_lowercase())
+            .filter(|mime| !mime.is_empty() && mime != "application/octet-stream")
+            .unwrap_or_else(|| {
+                guess_mime(
+                    Url::parse(url)
+                        .map(|url| url.path().to_owned())
+                        .unwrap_or_default(),
+                )
+            });
+
+        let mut bytes = vec![];
+        while let Some(chunk) = response.chunk().await? {
+            let total =
+                downloaded.fetch_add(chunk.len() as u64, Ordering::SeqCst) + chunk.len() as u64;
+            if total > max_bytes {
+                return Err(Error::DownloadLimit(max_bytes));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(Download { mime, bytes })
+    }
+}
+
+/// Whether `src` is an absolute http url not hosted by telegraph
+fn is_external(src: &str) -> bool {
+    match Url::parse(src.trim()) {
+        Ok(url) => {
+            matches!(url.scheme(), "http" | "https")
+                && url.host_str().is_some_and(|host| {
+                    !TELEGRAPH_HOSTS.iter().any(|telegraph| {
+                        host == *telegraph || host.ends_with(&format!(".{}", telegraph))
+                    })
+                })
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::mock_server, NodeElement};
+
+    fn media(tag: &str, src: &str) -> Node {
+        NodeElement::new(tag).with_src(src).into_node()
+    }
+
+    #[tokio::test]
+    async fn mirror_images() {
+        let (addr, requests) = mock_server(vec![
+            ("GET /cat.jpg", "image/jpeg", vec![0xff; 16]),
+            ("GET /clip", "video/mp4", vec![0; 16]),
+            ("GET /huge.png", "image/png", vec![0; 1024]),
+            (
+                "POST /upload",
+                "application/json",
+                br#"[{"src":"/file/mirrored"}]"#.to_vec(),
+            ),
+        ])
+        .await;
+        let telegraph = Telegraph::new("test")
+            .access_token("token")
+            .create()
+            .await
+            .unwrap();
+
+        let cat = format!("{}/cat.jpg", addr);
+        let mut nodes = vec![
+            media("img", &cat),
+            NodeElement::new("figure")
+                .child(media("video", &format!("{}/clip", addr)))
+                .child(media("img", &cat))
+                .into_node(),
+            media("img", &format!("{}/missing.gif", addr)),
+            media("img", &format!("{}/huge.png", addr)),
+            media("img", "/file/hosted.jpg"),
+            media("img", "https://telegra.ph/file/hosted.jpg"),
+        ];
+        let options = MirrorOptions::new().concurrency(1).max_bytes(512);
+        let mut report = telegraph
+            .mirror_images_to(&format!("{}/upload", addr), &mut nodes, &options)
+            .await;
+
+        assert_eq!(report.images.len(), 2);
+        report.errors.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(report.errors.len(), 2);
+        assert!(matches!(report.errors[0].1, Error::DownloadLimit(512)));
+        assert!(matches!(report.errors[1].1, Error::ReqwestError(_)));
+
+        let json = serde_json::to_value(&nodes).unwrap();
+        assert_eq!(json[0]["attrs"]["src"], "/file/mirrored");
+        assert_eq!(json[1]["children"][0]["attrs"]["src"], "/file/mirrored");
+        assert_eq!(json[1]["children"][1]["attrs"]["src"], "/file/mirrored");
+        assert!(json[2]["attrs"]["src"]
+            .as_str()
+            .unwrap()
+            .ends_with("/missing.gif"));
+        assert!(json[3]["attrs"]["src"]
+            .as_str()
+            .unwrap()
+            .ends_with("/huge.png"));
+        assert_eq!(json[4]["attrs"]["src"], "/file/hosted.jpg");
+
+        // the cat is downloaded once and uploaded with its mime type
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|r| r.starts_with("GET /cat.jpg"))
+                .count(),
+            1
+        );
+        assert!(requests
+            .iter()
+            .any(|r| r.starts_with("POST /upload") && r.contains("Content-Type: image/jpeg")));
+    }
+}
@@ -0,0 +1,443 @@
+//! Publish content which is too large for a single page, and merge it back
+use crate::{content_size, Error, Node, NodeElement, Page, Result, Telegraph, MAX_CONTENT_SIZE};
+use futures_util::future;
+use reqwest::Url;
+
+/// Length of the page urls reserved for the navigation links while splitting content
+const NAV_URL_RESERVE: usize = 512;
+
+/// Hosts serving telegraph pages
+const TELEGRAPH_HOSTS: &[&str] = &["telegra.ph", "graph.org"];
+
+/// What to insert between the pages merged by [`merge_pages_with_separator`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartSeparator {
+    /// Concatenate the content of the pages
+    #[default]
+    None,
+    /// Insert a `hr` between the pages
+    Rule,
+    /// Insert a `h3` with the title before every page
+    Title,
+}
+
+/// Options for [`Telegraph::create_paged`]
+///
+/// ```
+/// use telegraph_rs::PagedOptions;
+///
+/// let options = PagedOptions::new().next_text("Next →").return_content(true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PagedOptions {
+    max_size: usize,
+    previous_text: String,
+    next_text: String,
+    return_content: bool,
+}
+
+impl Default for PagedOptions {
+    fn default() -> Self {
+        Self {
+            max_size: MAX_CONTENT_SIZE,
+            previous_text: "← Previous part".to_owned(),
+            next_text: "Next part →".to_owned(),
+            return_content: false,
+        }
+    }
+}
+
+impl PagedOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum content size of every page including the navigation links, [`MAX_CONTENT_SIZE`] by default.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Text of the link to the previous part, "← Previous part" by default.
+    pub fn previous_text(mut self, text: &str) -> Self {
+        self.previous_text = text.to_owned();
+        self
+    }
+
+    /// Text of the link to the next part, "Next part →" by default.
+    pub fn next_text(mut self, text: &str) -> Self {
+        self.next_text = text.to_owned();
+        self
+    }
+
+    /// Whether the returned pages include their final content, false by default.
+    pub fn return_content(mut self, return_content: bool) -> Self {
+        self.return_content = return_content;
+        self
+    }
+}
+
+/// Split top level blocks of `nodes` into chunks whose [`content_size`] is at most `max_size`
+///
+/// Chunks preferably start at a `h3` or `h4` heading, as long as this leaves the previous chunk at least half full.
+/// Fails with [`Error::ContentTooLarge`] if a single block is larger than `max_size`.
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, split_content};
+///
+/// let nodes = html_to_nodes("<p>one</p><p>two</p><p>three</p>").unwrap();
+/// let chunks = split_content(&nodes, 70).unwrap();
+/// assert_eq!(chunks.len(), 2);
+/// ```
+pub fn split_content(nodes: &[Node], max_size: usize) -> Result<Vec<Vec<Node>>> {
+    let mut chunks = vec![];
+    let mut chunk = vec![];
+    let mut size = content_size(&chunk);
+    for node in nodes {
+        let node_size = content_size(std::slice::from_ref(node));
+        if node_size > max_size {
+            return Err(Error::ContentTooLarge {
+                size: node_size,
+                limit: max_size,
+            });
+        }
+        // the node replaces the brackets or adds a comma
+        while size + node_size - 2 + usize::from(!chunk.is_empty()) > max_size {
+            let cut = chunk
+                .iter()
+                .rposition(is_heading)
+                .filter(|&i| i > 0 && content_size(&chunk[..i]) >= max_size / 2)
+                .unwrap_or(chunk.len());
+            let rest = chunk.split_off(cut);
+            chunks.push(std::mem::replace(&mut chunk, rest));
+            size = content_size(&chunk);
+        }
+        size += node_size - 2 + usize::from(!chunk.is_empty());
+        chunk.push(node.clone());
+    }
+    if !chunk.is_empty() || chunks.is_empty() {
+        chunks.push(chunk);
+    }
+    Ok(chunks)
+}
+
+/// Concatenate the content of `pages`, see [`merge_pages_with_separator`]
+pub fn merge_pages(pages: &[Page]) -> Result<Vec<Node>> {
+    merge_pages_with_separator(pages, PartSeparator::None)
+}
+
+/// Concatenate the content of `pages`, separated by `separator`
+///
+/// Trailing paragraphs consisting only of links to the merged pages, like the navigation added by
+/// [`Telegraph::create_paged`], are removed.
+/// Fails with [`Error::MissingContent`] if a page was fetched without its content.
+pub fn merge_pages_with_separator(pages: &[Page], separator: PartSeparator) -> Result<Vec<Node>> {
+    let paths = pages.iter().map(|page| &*page.path).collect::<Vec<_>>();
+    let mut nodes = vec![];
+    for (i, page) in pages.iter().enumerate() {
+        let mut content = page
+            .content
+            .clone()
+            .ok_or_else(|| Error::MissingContent(page.path.clone()))?;
+        while content
+            .last()
+            .is_some_and(|node| is_navigation(node, &paths))
+        {
+            content.pop();
+        }
+        match separator {
+            PartSeparator::Rule if i > 0 => nodes.push(Node::NodeElement(NodeElement {
+                tag: "hr".to_owned(),
+                attrs: None,
+                children: None,
+            })),
+            PartSeparator::Title => nodes.push(Node::NodeElement(NodeElement {
+                tag: "h3".to_owned(),
+                attrs: None,
+                children: Some(vec![Node::Text(page.title.clone())]),
+            })),
+            _ => {}
+        }
+        nodes.append(&mut content);
+    }
+    Ok(nodes)
+}
+
+/// Whether `node` is a paragraph of links to `paths` or blank text
+fn is_navigation(node: &Node, paths: &[&str]) -> bool {
+    let element = match node {
+        Node::Text(text) => return text.trim().is_empty(),
+        Node::NodeElement(element) if element.tag == "p" => element,
+        Node::NodeElement(_) => return false,
+    };
+    let children = element.children.as_deref().unwrap_or_default();
+    let mut links = 0;
+    for child in children {
+        match child {
+            Node::NodeElement(link) if link.tag == "a" => {
+                let href = link.attrs.as_ref().and_then(|attrs| attrs.href.as_deref());
+                match href.and_then(page_path) {
+                    Some(path) if paths.contains(&&*path) => links += 1,
+                    _ => return false,
+                }
+            }
+            Node::Text(text)
+                if text
+                    .trim_matches(|c: char| c.is_whitespace() || c == '|')
+                    .is_empty() => {}
+            _ => return false,
+        }
+    }
+    links > 0
+}
+
+/// Path of a telegraph page url like `https://telegra.ph/Sample-Page-12-15` or `/Sample-Page-12-15`
+fn page_path(href: &str) -> Option<String> {
+    let url = Url::parse("https://telegra.ph/").ok()?.join(href).ok()?;
+    if !TELEGRAPH_HOSTS.contains(&url.host_str()?) {
+        return None;
+    }
+    Some(url.path().trim_start_matches('/').to_owned())
+}
+
+fn is_heading(node: &Node) -> bool {
+    matches!(node, Node::NodeElement(element) if element.tag == "h3" || element.tag == "h4")
+}
+
+/// A paragraph linking to the previous and next part
+fn navigation(previous: Option<&str>, next: Option<&str>, options: &PagedOptions) -> Node {
+    let link = |href: &str, text: &str| {
+        Node::NodeElement(
+            NodeElement {
+                tag: "a".to_owned(),
+                attrs: None,
+                children: Some(vec![Node::Text(text.to_owned())]),
+            }
+            .with_href(href),
+        )
+    };
+    let mut children = vec![];
+    if let Some(previous) = previous {
+        children.push(link(previous, &options.previous_text));
+    }
+    if let Some(next) = next {
+        if !children.is_empty() {
+            children.push(Node::Text(" | ".to_owned()));
+        }
+        children.push(link(next, &options.next_text));
+    }
+    Node::NodeElement(NodeElement {
+        tag: "p".to_owned(),
+        attrs: None,
+        children: Some(children),
+    })
+}
+
+impl Telegraph {
+    /// Split `content` with [`split_content`] and publish the chunks as pages titled `title`,
+    /// linked to each other by "← Previous part" and "Next part →" links.
+    ///
+    /// The links to the next part are added by editing the pages once all of them are created.
+    /// Returns the pages in order.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{html_to_nodes, PagedOptions, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let nodes = html_to_nodes("<h3>Chapter 1</h3><p>...</p><h3>Chapter 2</h3><p>...</p>")?;
+    /// let pages = telegraph.create_paged("A long story", &nodes, &PagedOptions::new()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_paged(
+        &self,
+        title: &str,
+        content: &[Node],
+        options: &PagedOptions,
+    ) -> Result<Vec<Page>> {
+        let url = "x".repeat(NAV_URL_RESERVE);
+        let reserve = content_size(&[navigation(Some(&url), Some(&url), options)]) - 1;
+        let chunks = split_content(content, options.max_size.saturating_sub(reserve))?;
+        let last = chunks.len() - 1;
+
+        let mut pages: Vec<Page> = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mut nodes = chunk.clone();
+            if let Some(previous) = i.checked_sub(1).map(|i| &pages[i].url) {
+                nodes.push(navigation(Some(previous), None, options));
+            }
+            let nodes = serde_json::to_string(&nodes).unwrap();
+            let return_content = options.return_content && i == last;
+            pages.push(self.create_page(title, &nodes, return_content).await?);
+        }
+
+        for (i, chunk) in chunks.iter().enumerate().take(last) {
+            let mut nodes = chunk.clone();
+            let previous = i.checked_sub(1).map(|i| &*pages[i].url);
+            nodes.push(navigation(previous, Some(&pages[i + 1].url), options));
+            let nodes = serde_json::to_string(&nodes).unwrap();
+            let page = self
+                .edit_page(&pages[i].path, title, &nodes, options.return_content)
+                .await?;
+            pages[i] = page;
+        }
+        Ok(pages)
+    }
+
+    /// Get `paths` concurrently and merge their content with [`merge_pages_with_separator`]
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::{PartSeparator, Telegraph};
+    ///
+    /// let paths = ["A-long-story-01-01", "A-long-story-01-01-2"];
+    /// let nodes = Telegraph::fetch_and_merge(&paths, PartSeparator::Rule).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_and_merge(paths: &[&str], separator: PartSeparator) -> Result<Vec<Node>> {
+        let pages =
+            future::try_join_all(paths.iter().map(|path| Telegraph::get_page(path, true))).await?;
+        merge_pages_with_separator(&pages, separator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(chunks: &[Vec<Node>]) -> Vec<Vec<String>> {
+        chunks
+            .iter()
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|node| match node {
+                        Node::NodeElement(element) => element.tag.clone(),
+                        Node::Text(text) => text.clone(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_at_headings() {
+        // every paragraph is 100 bytes
+        let len = 100 - content_size(&[NodeElement::new("p").child("").into_node()]);
+        let p = || NodeElement::new("p").child("a".repeat(len)).into_node();
+        let nodes = vec![
+            p(),
+            NodeElement::new("h3").child("one").into_node(),
+            p(),
+            p(),
+            NodeElement::new("h4").child("two").into_node(),
+            p(),
+            p(),
+        ];
+        let chunks = split_content(&nodes, 400).unwrap();
+        assert!(chunks.iter().all(|chunk| content_size(chunk) <= 400));
+        assert_eq!(
+            tags(&chunks),
+            [vec!["p", "h3", "p", "p"], vec!["h4", "p", "p"]]
+        );
+
+        // a heading which would leave the previous chunk less than half full is ignored
+        let nodes = vec![
+            p(),
+            NodeElement::new("h3").child("one").into_node(),
+            p(),
+            p(),
+            p(),
+            p(),
+        ];
+        let chunks = split_content(&nodes, 400).unwrap();
+        assert_eq!(tags(&chunks), [vec!["p", "h3", "p", "p"], vec!["p", "p"]]);
+
+        let chunks = split_content(&nodes, usize::MAX).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(split_content(&[], 400).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn split_oversized_block() {
+        let nodes = vec![
+            NodeElement::new("p").child("short").into_node(),
+            NodeElement::new("pre").child("a".repeat(500)).into_node(),
+        ];
+        let size = content_size(&nodes[1..]);
+        assert!(matches!(
+            split_content(&nodes, 400),
+            Err(Error::ContentTooLarge { size: s, limit: 400 }) if s == size
+        ));
+    }
+
+    #[test]
+    fn merge() {
+        let page = |path: &str, content: serde_json::Value| -> Page {
+            serde_json::from_value(serde_json::json!({
+                "path": path,
+                "url": format!("https://telegra.ph/{}", path),
+                "title": format!("Title of {}", path),
+                "description": "",
+                "views": 0,
+                "content": content,
+            }))
+            .unwrap()
+        };
+        let navigation = |href: &str| serde_json::json!({"tag": "p", "children": [{"tag": "a", "attrs": {"href": href}, "children": ["Next part →"]}]});
+        let pages = [
+            page(
+                "Story-01-01",
+                serde_json::json!([
+                    {"tag": "p", "children": ["one"]},
+                    {"tag": "p", "children": [{"tag": "a", "attrs": {"href": "https://example.com/"}, "children": ["kept"]}]},
+                    navigation("https://telegra.ph/Story-01-01-2"),
+                ]),
+            ),
+            page(
+                "Story-01-01-2",
+                serde_json::json!([
+                    {"tag": "p", "children": ["two"]},
+                    {"tag": "p", "children": [
+                        {"tag": "a", "attrs": {"href": "/Story-01-01"}, "children": ["← Previous part"]},
+                        " | ",
+                        {"tag": "a", "attrs": {"href": "https://graph.org/Story-01-01-3"}, "children": ["Next part →"]},
+                    ]},
+                ]),
+            ),
+            page(
+                "Story-01-01-3",
+                serde_json::json!(["three", navigation("https://telegra.ph/Other-01-01")]),
+            ),
+        ];
+
+        let merged = merge_pages_with_separator(&pages, PartSeparator::Rule).unwrap();
+        assert_eq!(
+            serde_json::to_value(&merged).unwrap(),
+            serde_json::json!([
+                {"tag": "p", "children": ["one"]},
+                {"tag": "p", "children": [{"tag": "a", "attrs": {"href": "https://example.com/"}, "children": ["kept"]}]},
+                {"tag": "hr"},
+                {"tag": "p", "children": ["two"]},
+                {"tag": "hr"},
+                "three",
+                navigation("https://telegra.ph/Other-01-01"),
+            ])
+        );
+
+        let merged = merge_pages_with_separator(&pages[..1], PartSeparator::Title).unwrap();
+        assert_eq!(
+            serde_json::to_value(&merged[0]).unwrap(),
+            serde_json::json!({"tag": "h3", "children": ["Title of Story-01-01"]})
+        );
+
+        let mut pages = pages;
+        pages[1].content = None;
+        assert!(matches!(
+            merge_pages(&pages),
+            Err(Error::MissingContent(path)) if path == "Story-01-01-2"
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+//! The commonly used items, to be glob imported
+//!
+//! ```
+//! # async fn run() -> telegraph_rs::Result<()> {
+//! use telegraph_rs::prelude::*;
+//!
+//! let telegraph = Telegraph::new("author").access_token("token").create().await?;
+//! let content = nodes![h3("Hello"), p["Hello, ", bold("world"), "!"]];
+//! let page = telegraph.create_page_from("Hello", &content, false).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only the client, the page types, the error type and the main conversions are included,
+//! so a glob import doesn't shadow names of the importing crate.
+//! The element constructors of [`dsl`](crate::dsl) are the exception, since they are what content is written with.
+pub use crate::{
+    dsl::{a, blockquote, bold, br, code, h3, h4, hr, img, italic, li, ol, p, pre, ul},
+    node_to_html, node_to_text, nodes, validate_nodes, Account, AccountBuilder, Error, IntoContent,
+    Node, NodeElement, Page, PageList, PageViews, Result, Telegraph,
+};
+#[cfg(feature = "html")]
+pub use crate::{html_to_node, html_to_nodes};
+#[cfg(feature = "markdown")]
+pub use crate::{markdown_to_node, markdown_to_nodes};
+#[cfg(feature = "upload")]
+pub use crate::{ImageInfo, Uploadable};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{client, mock_server, page_response};
+
+    #[tokio::test]
+    async fn main_flows() {
+        let page = page_response("Hello-10-16", "Hello");
+        let (addr, requests) =
+            mock_server(vec![("POST /createPage", "application/json", page)]).await;
+        let telegraph: Telegraph = client(&addr).await;
+
+        let content: Vec<Node> = nodes![h3("Hello"), p["Hello, ", bold("world"), "!"]];
+        assert_eq!(validate_nodes(&content), Ok(()));
+        assert_eq!(node_to_text(&content), "Hello\nHello, world!");
+        let page: Result<Page> = telegraph.create_page_from("Hello", &content, false).await;
+        assert_eq!(page.unwrap().path, "Hello-10-16");
+        assert!(requests.lock().unwrap()[0].contains("world"));
+
+        let element: NodeElement = NodeElement::new("p").child(code("x"));
+        assert_eq!(node_to_html(&[element.into()]), "<p><code>x</code></p>");
+        let error: Error = Error::ApiError("PAGE_NOT_FOUND".to_owned());
+        assert_eq!(error.to_string(), "api error: PAGE_NOT_FOUND");
+    }
+
+    #[cfg(feature = "html")]
+    #[test]
+    fn html() {
+        let nodes = html_to_nodes("<p>Hello</p>").unwrap();
+        assert_eq!(nodes, vec![p(["Hello"])]);
+        assert_eq!(
+            html_to_node("<p>Hello</p>"),
+            "[{\"tag\":\"p\",\"children\":[\"Hello\"]}]"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! A builder for creating and editing pages
+use crate::{
+    nodes_to_content_string, validate_content, validate_nodes, CreatePageOptions, Error,
+    IntoContent, Node, Page, Result, Telegraph,
+};
+
+/// Content of a [`PageBuilder`]
+#[derive(Debug, Clone)]
+enum PageContent {
+    Nodes(Vec<Node>),
+    Json(String),
+}
+
+/// A page to create or edit, built by [`Telegraph::page`]
+///
+/// ```
+/// # async fn run() -> Result<(), telegraph_rs::Error> {
+/// use telegraph_rs::{dsl::*, Telegraph};
+///
+/// let telegraph = Telegraph::new("channel").access_token("token").create().await?;
+/// let page = telegraph
+///     .page("Title")
+///     .author("Alice")
+///     .author_url("https://t.me/alice")
+///     .nodes(vec![p(["Hello, world!"])])
+///     .return_content(true)
+///     .publish()
+///     .await?;
+///
+/// let page = telegraph
+///     .page("New title")
+///     .edit(&page.path)
+///     .content(r#"["Hello again"]"#)
+///     .publish()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[must_use = "the page is only sent by `publish`"]
+pub struct PageBuilder<'a> {
+    telegraph: &'a Telegraph,
+    title: String,
+    path: Option<String>,
+    content: Option<PageContent>,
+    options: CreatePageOptions,
+    validate: bool,
+    #[cfg(feature = "upload")]
+    upload_images: bool,
+    #[cfg(feature = "upload")]
+    upload_url: String,
+}
+
+impl Telegraph {
+    /// Start building a page titled `title`, see [`PageBuilder`]
+    pub fn page(&self, title: &str) -> PageBuilder<'_> {
+        PageBuilder {
+            telegraph: self,
+            title: title.to_owned(),
+            path: None,
+            content: None,
+            options: CreatePageOptions::new(),
+            validate: false,
+            #[cfg(feature = "upload")]
+            upload_images: false,
+            #[cfg(feature = "upload")]
+            upload_url: "https://telegra.ph/upload".to_owned(),
+        }
+    }
+}
+
+impl PageBuilder<'_> {
+    /// Edit the page at `path` instead of creating a new one
+    pub fn edit(mut self, path: &str) -> Self {
+        self.path = Some(path.to_owned());
+        self
+    }
+
+    /// Name of the author, the account's author name by default.
+    pub fn author(mut self, author_name: &str) -> Self {
+        self.options = self.options.author_name(author_name);
+        self
+    }
+
+    /// Profile link of the author, the account's profile link by default.
+    pub fn author_url(mut self, author_url: &str) -> Self {
+        self.options = self.options.author_url(author_url);
+        self
+    }
+
+    /// Content of the page as nodes
+    pub fn nodes(mut self, nodes: Vec<Node>) -> Self {
+        self.content = Some(PageContent::Nodes(nodes));
+        self
+    }
+
+    /// Content of the page as nodes or serialized JSON
+    pub fn content(mut self, content: impl IntoContent) -> Self {
+        self.content = Some(PageContent::Json(content.into_content()));
+        self
+    }
+
+    /// Whether the returned page has its content, false by default.
+    pub fn return_content(mut self, return_content: bool) -> Self {
+        self.options = self.options.return_content(return_content);
+        self
+    }
+
+    /// Whether to validate the content with [`validate_nodes`] before publishing,
+    /// even if it isn't enabled by [`AccountBuilder::validate_content`](crate::AccountBuilder::validate_content),
+    /// false by default.
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Whether to upload images embedded as data URIs in [`PageBuilder::nodes`] before publishing,
+    /// see [`Telegraph::upload_data_images`], false by default.
+    #[cfg(feature = "upload")]
+    pub fn upload_images(mut self, upload_images: bool) -> Self {
+        self.upload_images = upload_images;
+        self
+    }
+
+    /// Create the page, or edit it if [`PageBuilder::edit`] was called
+    ///
+    /// Fails with [`Error::MissingContent`] if no content or an empty one was given.
+    pub async fn publish(self) -> Result<Page> {
+        let content = match self.content {
+            Some(PageContent::Nodes(nodes)) if nodes.is_empty() => None,
+            #[cfg(feature = "upload")]
+            Some(PageContent::Nodes(mut nodes)) => {
+                if self.upload_images {
+                    let client = &self.telegraph.client;
+                    Telegraph::upload_data_images_to(&self.upload_url, &mut nodes, client).await?;
+                }
+                Some(PageContent::Nodes(nodes))
+            }
+            content => content,
+        };
+        let content = match content {
+            Some(PageContent::Nodes(nodes)) => {
+                if self.validate {
+                    validate_nodes(&nodes).map_err(Error::InvalidContent)?;
+                }
+                nodes_to_content_string(&nodes)
+            }
+            Some(PageContent::Json(json)) if !json.trim().is_empty() && json.trim() != "[]" => {
+                if self.validate {
+                    validate_content(&json)?;
+                }
+                json
+            }
+            _ => return Err(Error::MissingContent(self.title)),
+        };
+        self.telegraph
+            .post_page(self.path.as_deref(), &self.title, &content, &self.options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::client;
+    use crate::{
+        tests::{form_fields, mock_server, page_response},
+        NodeElement,
+    };
+
+    async fn telegraph() -> (
+        Telegraph,
+        std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+        String,
+    ) {
+        let (addr, requests) = mock_server(vec![
+            (
+                "POST /createPage",
+                "application/json",
+                page_response("Hi-10-16", "Hi"),
+            ),
+            (
+                "POST /editPage",
+                "application/json",
+                page_response("Hi-10-16", "Hi"),
+            ),
+            (
+                "POST /upload",
+                "application/json",
+                br#"[{"src":"/file/new.png"}]"#.to_vec(),
+            ),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        (telegraph, requests, addr)
+    }
+
+    #[tokio::test]
+    async fn create_and_edit() {
+        let (telegraph, requests, _) = telegraph().await;
+        let nodes = vec![NodeElement::new("p").child("Hello").into_node()];
+        let page = telegraph
+            .page("Hi")
+            .author("Alice")
+            .nodes(nodes.clone())
+            .return_content(true)
+            .publish()
+            .await
+            .unwrap();
+        telegraph
+            .page("Hi again")
+            .edit(&page.path)
+            .content(&nodes)
+            .publish()
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST /createPage"));
+        let create = form_fields(&requests[0]);
+        assert_eq!(create[2], ("author_name".to_owned(), "Alice".to_owned()));
+        assert_eq!(create[4].1, r#"[{"tag":"p","children":["Hello"]}]"#);
+        assert_eq!(create[5].1, "true");
+        assert!(requests[1].starts_with("POST /editPage"));
+        let edit = form_fields(&requests[1]);
+        assert_eq!(edit[1], ("path".to_owned(), "Hi-10-16".to_owned()));
+        assert_eq!(edit[2].1, "Hi again");
+        assert_eq!(edit[3], ("author_name".to_owned(), "test".to_owned()));
+        assert_eq!(edit[5].1, create[4].1);
+    }
+
+    #[tokio::test]
+    async fn misuse() {
+        let (telegraph, requests, _) = telegraph().await;
+        let missing = [
+            telegraph.page("Hi"),
+            telegraph.page("Hi").nodes(vec![]),
+            telegraph.page("Hi").content(" [] "),
+        ];
+        for builder in missing {
+            let result = builder.publish().await;
+            assert!(matches!(result, Err(Error::MissingContent(title)) if title == "Hi"));
+        }
+        let invalid = telegraph
+            .page("Hi")
+            .content(r#"[{"tag":"div"}]"#)
+            .validate(true)
+            .publish()
+            .await;
+        assert!(matches!(invalid, Err(Error::InvalidContent(_))));
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "upload")]
+    #[tokio::test]
+    async fn upload_images() {
+        let (telegraph, requests, addr) = telegraph().await;
+        let mut builder = telegraph
+            .page("Hi")
+            .nodes(vec![
+                NodeElement::img("data:image/png;base64,iVBORw0KGgo=").into()
+            ])
+            .upload_images(true)
+            .validate(true);
+        builder.upload_url = format!("{}/upload", addr);
+        builder.publish().await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST /upload"));
+        assert_eq!(
+            form_fields(&requests[1])[4].1,
+            r#"[{"tag":"img","attrs":{"src":"/file/new.png"}}]"#
+        );
+    }
+}
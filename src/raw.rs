@@ -0,0 +1,148 @@
+//! Pages whose content is kept as raw JSON
+//!
+//! Deserializing the content of a big page into [`Node`]s is the most expensive part of fetching it.
+//! [`PageRaw`] keeps the content as it was sent by Telegraph, so it can be published again
+//! with [`Telegraph::edit_page_raw`] without being parsed or encoded again.
+use crate::{Node, Page, Result, Telegraph, API_URL};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+
+/// A [`Page`] whose content is kept as raw JSON
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PageRaw {
+    /// Path to the page.
+    pub path: String,
+    /// URL of the page.
+    pub url: String,
+    /// Title of the page.
+    pub title: String,
+    /// Description of the page.
+    pub description: String,
+    /// Optional. Name of the author, displayed below the title.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    /// Optional. Profile link, opened when users click on the author's name below the title.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_url: Option<String>,
+    /// Optional. Image URL of the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// Optional. Content of the page, a JSON array of nodes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Box<RawValue>>,
+    /// Number of page views for the page.
+    pub views: i32,
+    /// Optional. Only returned if access_token passed.
+    ///
+    /// True, if the target Telegraph account can edit the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_edit: Option<bool>,
+}
+
+impl PageRaw {
+    /// Parse the content into nodes
+    pub fn nodes(&self) -> serde_json::Result<Option<Vec<Node>>> {
+        self.content
+            .as_deref()
+            .map(|content| serde_json::from_str(content.get()))
+            .transpose()
+    }
+
+    /// Parse the content, turning this into a [`Page`]
+    pub fn into_page(self) -> serde_json::Result<Page> {
+        let content = self.nodes()?;
+        Ok(Page {
+            path: self.path,
+            url: self.url,
+            title: self.title,
+            description: self.description,
+            author_name: self.author_name,
+            author_url: self.author_url,
+            image_url: self.image_url,
+            content,
+            views: self.views,
+            can_edit: self.can_edit,
+        })
+    }
+}
+
+impl Telegraph {
+    /// Like [`Telegraph::get_page`], keeping the content as raw JSON
+    pub async fn get_page_raw(path: &str, return_content: bool) -> Result<PageRaw> {
+        Self::get_page_from(API_URL, path, return_content).await
+    }
+
+    /// Like [`Telegraph::edit_page`], sending `content` as is and keeping the returned content as raw JSON
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::Telegraph;
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let page = Telegraph::get_page_raw("Sample-Page-12-15", true).await?;
+    /// if let Some(content) = &page.content {
+    ///     telegraph.edit_page_raw(&page.path, "New title", content, false).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn edit_page_raw(
+        &self,
+        path: &str,
+        title: &str,
+        content: &RawValue,
+        return_content: bool,
+    ) -> Result<PageRaw> {
+        let options = crate::CreatePageOptions::new().return_content(return_content);
+        self.post_page(Some(path), title, content.get(), &options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{client, mock_server};
+
+    const CONTENT: &str = r#"[{"tag":"p", "attrs":{},"children":["a é ",{"tag":"br"}]}]"#;
+
+    #[tokio::test]
+    async fn republish() {
+        let page = format!(
+            r#"{{"ok":true,"result":{{"path":"A-10-16","url":"https://telegra.ph/A-10-16","title":"A","description":"","content":{},"views":2}}}}"#,
+            CONTENT
+        );
+        let (addr, requests) = mock_server(vec![
+            (
+                "GET /getPage/A-10-16",
+                "application/json",
+                page.clone().into_bytes(),
+            ),
+            ("POST /editPage", "application/json", page.into_bytes()),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+
+        let page: PageRaw = Telegraph::get_page_from(&addr, "A-10-16", true)
+            .await
+            .unwrap();
+        let content = page.content.as_deref().unwrap();
+        assert_eq!(content.get(), CONTENT);
+        let edited = telegraph
+            .edit_page_raw(&page.path, "B", content, true)
+            .await
+            .unwrap();
+        assert_eq!(edited.content.unwrap().get(), CONTENT);
+
+        let requests = requests.lock().unwrap();
+        let sent = crate::tests::form_field(&requests[1], "content").unwrap();
+        assert_eq!(sent, CONTENT);
+
+        let typed = page.clone().into_page().unwrap();
+        assert_eq!(typed.content, page.nodes().unwrap());
+        assert_eq!(
+            serde_json::to_string(&typed.content.unwrap()).unwrap(),
+            r#"[{"tag":"p","children":["a é ",{"tag":"br"}]}]"#
+        );
+    }
+}
@@ -0,0 +1,653 @@
+//! Render node trees into other formats
+use crate::{text_content, utils::embedded_url, Node, NodeAttrs, NodeElement};
+use std::fmt;
+
+/// Elements which are followed by a line break in plain text
+const TEXT_BLOCK_TAGS: &[&str] = &[
+    "p",
+    "li",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "aside",
+    "pre",
+    "figure",
+    "figcaption",
+    "ul",
+    "ol",
+    "hr",
+    "iframe",
+    "video",
+];
+
+/// Elements rendered as Markdown blocks
+const MARKDOWN_BLOCK_TAGS: &[&str] = &[
+    "p",
+    "li",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "aside",
+    "pre",
+    "figure",
+    "ul",
+    "ol",
+    "hr",
+];
+
+/// Elements which are rendered without a closing tag
+const HTML_VOID_TAGS: &[&str] = &["br", "hr", "img"];
+
+/// Characters which are escaped in Markdown text
+const MARKDOWN_SPECIAL: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '>', '~'];
+
+/// Extract the plain text of a node tree
+///
+/// Blocks like paragraphs, list items and headings are put on lines of their own,
+/// links contribute their text and images their caption.
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, node_to_text};
+///
+/// let nodes = html_to_nodes("<p>Hello, <b>world</b></p><ul><li>one</li><li>two</li></ul>").unwrap();
+/// assert_eq!(node_to_text(&nodes), "Hello, world\none\ntwo");
+/// ```
+pub fn node_to_text(nodes: &[Node]) -> String {
+    fn push_text(nodes: &[Node], text: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(t) => text.push_str(t),
+                Node::NodeElement(element) if element.tag == "br" => text.push('\n'),
+                Node::NodeElement(element) => {
+                    let block = TEXT_BLOCK_TAGS.contains(&&*element.tag);
+                    if block {
+                        end_line(text);
+                    }
+                    push_text(element.children.as_deref().unwrap_or_default(), text);
+                    if block {
+                        end_line(text);
+                    }
+                }
+            }
+        }
+    }
+
+    fn end_line(text: &mut String) {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+
+    let mut text = String::new();
+    push_text(nodes, &mut text);
+    text.truncate(text.trim_end_matches('\n').len());
+    text
+}
+
+/// Render a node tree as Markdown
+///
+/// Embeds are rendered as their bare URL, and characters which are significant in Markdown are escaped.
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, node_to_markdown};
+///
+/// let nodes = html_to_nodes("<h3>Title</h3><p>Some <b>bold</b> text</p><ul><li>one</li></ul>").unwrap();
+/// assert_eq!(node_to_markdown(&nodes), "### Title\n\nSome **bold** text\n\n- one");
+/// ```
+pub fn node_to_markdown(nodes: &[Node]) -> String {
+    markdown_blocks(nodes).join("\n\n")
+}
+
+/// Render a node tree as HTML
+///
+/// Attributes are sorted by name so the output is stable.
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, node_to_html};
+///
+/// let nodes = html_to_nodes(r#"<p>1 &lt; 2<br><a href="/a?b&amp;c">link</a></p>"#).unwrap();
+/// assert_eq!(node_to_html(&nodes), r#"<p>1 &lt; 2<br><a href="/a?b&amp;c">link</a></p>"#);
+/// ```
+pub fn node_to_html(nodes: &[Node]) -> String {
+    let mut html = String::new();
+    push_html(nodes, None, &mut html);
+    html
+}
+
+/// Render `nodes` as HTML, text leaves longer than `text_limit` characters are cut with an ellipsis
+fn push_html(nodes: &[Node], text_limit: Option<usize>, html: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => push_text(text, text_limit, html),
+            Node::NodeElement(element) => push_element(element, text_limit, html),
+        }
+    }
+}
+
+fn push_text(text: &str, text_limit: Option<usize>, html: &mut String) {
+    match text_limit.and_then(|limit| text.char_indices().nth(limit)) {
+        Some((end, _)) => {
+            escape_html(&text[..end], false, html);
+            html.push('…');
+        }
+        None => escape_html(text, false, html),
+    }
+}
+
+fn push_element(element: &NodeElement, text_limit: Option<usize>, html: &mut String) {
+    html.push('<');
+    html.push_str(&element.tag);
+    let mut attrs = element
+        .attrs
+        .iter()
+        .flat_map(NodeAttrs::iter)
+        .collect::<Vec<_>>();
+    attrs.sort_unstable_by_key(|(name, _)| *name);
+    for (name, value) in attrs {
+        html.push(' ');
+        html.push_str(name);
+        if let Some(value) = value {
+            html.push_str("=\"");
+            escape_html(value, true, html);
+            html.push('"');
+        }
+    }
+    html.push('>');
+    if HTML_VOID_TAGS.contains(&&*element.tag) {
+        return;
+    }
+    push_html(
+        element.children.as_deref().unwrap_or_default(),
+        text_limit,
+        html,
+    );
+    html.push_str("</");
+    html.push_str(&element.tag);
+    html.push('>');
+}
+
+/// Text leaves longer than this are cut when a node is displayed
+const DISPLAY_TEXT_LIMIT: usize = 80;
+
+/// Compact HTML for logs and test failures, like [`node_to_html`] but with long text cut
+///
+/// ```
+/// use telegraph_rs::{Node, NodeElement};
+///
+/// let node = NodeElement::new("p").child("Hello, ").child(NodeElement::new("b").child("world")).into_node();
+/// assert_eq!(node.to_string(), "<p>Hello, <b>world</b></p>");
+/// ```
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut html = String::new();
+        push_html(
+            std::slice::from_ref(self),
+            Some(DISPLAY_TEXT_LIMIT),
+            &mut html,
+        );
+        f.write_str(&html)
+    }
+}
+
+/// Compact HTML for logs and test failures, see the implementation for [`Node`]
+impl fmt::Display for NodeElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut html = String::new();
+        push_element(self, Some(DISPLAY_TEXT_LIMIT), &mut html);
+        f.write_str(&html)
+    }
+}
+
+fn escape_html(text: &str, attribute: bool, html: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' if attribute => html.push_str("&quot;"),
+            c => html.push(c),
+        }
+    }
+}
+
+/// Render nodes as Markdown blocks, runs of inline nodes become paragraphs
+fn markdown_blocks(nodes: &[Node]) -> Vec<String> {
+    let mut blocks = vec![];
+    let mut start = 0;
+    for (i, node) in nodes.iter().enumerate() {
+        if let Node::NodeElement(element) = node {
+            if MARKDOWN_BLOCK_TAGS.contains(&&*element.tag) {
+                push_paragraph(&nodes[start..i], &mut blocks);
+                let block = markdown_block(element);
+                if !block.is_empty() {
+                    blocks.push(block);
+                }
+                start = i + 1;
+            }
+        }
+    }
+    push_paragraph(&nodes[start..], &mut blocks);
+    blocks
+}
+
+fn push_paragraph(nodes: &[Node], blocks: &mut Vec<String>) {
+    let mut text = String::new();
+    markdown_inline(nodes, &mut text);
+    let mut text = text.trim();
+    // a trailing line break leaves a dangling backslash
+    let backslashes = text.len() - text.trim_end_matches('\\').len();
+    if backslashes % 2 == 1 {
+        text = text[..text.len() - 1].trim_end();
+    }
+    if !text.is_empty() {
+        blocks.push(text.to_owned());
+    }
+}
+
+fn markdown_block(element: &NodeElement) -> String {
+    let children = element.children.as_deref().unwrap_or_default();
+    match &*element.tag {
+        "hr" => "---".to_owned(),
+        "pre" => {
+            let mut text = String::new();
+            text_content(children, &mut text);
+            let fence = "`".repeat(longest_run(&text, '`').max(2) + 1);
+            format!("{}\n{}\n{}", fence, text.trim_end_matches('\n'), fence)
+        }
+        "blockquote" | "aside" => markdown_blocks(children)
+            .join("\n\n")
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    ">".to_owned()
+                } else {
+                    format!("> {}", line)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "ul" | "ol" => {
+            let mut items = vec![];
+            for child in children {
+                let marker = match element.tag == "ol" {
+                    true => format!("{}. ", items.len() + 1),
+                    false => "- ".to_owned(),
+                };
+                let content = match child {
+                    Node::NodeElement(item) if item.tag == "li" => {
+                        markdown_blocks(item.children.as_deref().unwrap_or_default()).join("\n")
+                    }
+                    Node::Text(text) if text.trim().is_empty() => continue,
+                    node => markdown_blocks(std::slice::from_ref(node)).join("\n"),
+                };
+                let mut item = marker.trim_end().to_owned();
+                for (i, line) in content.lines().enumerate() {
+                    if i > 0 {
+                        item.push('\n');
+                    }
+                    if !line.is_empty() {
+                        item.push_str(&" ".repeat(if i == 0 { 1 } else { marker.len() }));
+                        item.push_str(line);
+                    }
+                }
+                items.push(item);
+            }
+            items.join("\n")
+        }
+        "figure" => {
+            let caption = children.iter().find_map(|node| match node {
+                Node::NodeElement(element) if element.tag == "figcaption" => Some(element),
+                _ => None,
+            });
+            let mut caption_text = String::new();
+            if let Some(caption) = caption {
+                let children = caption.children.as_deref().unwrap_or_default();
+                markdown_inline(children, &mut caption_text);
+            }
+            let caption_text = caption_text.trim().replace("\\\n", " ");
+            let media = match find_media(children) {
+                Some(media) => media,
+                None => return markdown_blocks(children).join("\n\n"),
+            };
+            let src = attr(media, "src").unwrap_or_default();
+            let mut block = match &*media.tag {
+                "img" => format!("![{}]({})", caption_text, link_destination(src)),
+                _ => embedded_url(src).unwrap_or_else(|| src.to_owned()),
+            };
+            if !caption_text.is_empty() {
+                block.push('\n');
+                emphasis("*", &caption_text, &mut block);
+            }
+            block
+        }
+        tag => {
+            let content = markdown_blocks(children).join("\n\n");
+            match heading_level(tag) {
+                Some(level) if !content.is_empty() => {
+                    format!("{} {}", "#".repeat(level), content.replace("\\\n", " "))
+                }
+                _ => content,
+            }
+        }
+    }
+}
+
+fn markdown_inline(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        let element = match node {
+            Node::Text(text) => {
+                escape_markdown(text, out);
+                continue;
+            }
+            Node::NodeElement(element) => element,
+        };
+        let children = element.children.as_deref().unwrap_or_default();
+        match &*element.tag {
+            "br" => out.push_str("\\\n"),
+            "strong" | "b" | "em" | "i" | "s" => {
+                let mut inner = String::new();
+                markdown_inline(children, &mut inner);
+                let delimiter = match &*element.tag {
+                    "strong" | "b" => "**",
+                    "s" => "~~",
+                    _ => "*",
+                };
+                emphasis(delimiter, &inner, out);
+            }
+            "code" => {
+                let mut text = String::new();
+                text_content(children, &mut text);
+                let text = text.replace('\n', " ");
+                let ticks = "`".repeat(longest_run(&text, '`') + 1);
+                let pad = if text.starts_with('`') || text.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                out.extend([&*ticks, pad, &text, pad, &ticks]);
+            }
+            "a" => match attr(element, "href") {
+                Some(href) => {
+                    out.push('[');
+                    markdown_inline(children, out);
+                    out.push_str("](");
+                    out.push_str(&link_destination(href));
+                    out.push(')');
+                }
+                None => markdown_inline(children, out),
+            },
+            "img" => {
+                let src = attr(element, "src").unwrap_or_default();
+                out.push_str(&format!("![]({})", link_destination(src)));
+            }
+            "iframe" | "video" => {
+                let src = attr(element, "src").unwrap_or_default();
+                out.push_str(&embedded_url(src).unwrap_or_else(|| src.to_owned()));
+            }
+            _ => markdown_inline(children, out),
+        }
+    }
+}
+
+/// Wrap `inner` in `delimiter`, keeping surrounding spaces outside so the emphasis is recognized
+fn emphasis(delimiter: &str, inner: &str, out: &mut String) {
+    let is_space = |c: char| c.is_whitespace() && c != '\n';
+    let trimmed = inner.trim_matches(is_space);
+    if trimmed.is_empty() {
+        out.push_str(inner);
+        return;
+    }
+    let leading = inner.len() - inner.trim_start_matches(is_space).len();
+    let trailing = inner.len() - inner.trim_end_matches(is_space).len();
+    out.extend([
+        &inner[..leading],
+        delimiter,
+        trimmed,
+        delimiter,
+        &inner[inner.len() - trailing..],
+    ]);
+}
+
+/// Escape characters of `text` which would otherwise be parsed as Markdown
+fn escape_markdown(text: &str, out: &mut String) {
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut rest = line;
+        if out.is_empty() || out.ends_with('\n') {
+            // characters which start a heading, list or quote at the beginning of a line
+            let content = line.trim_start();
+            let digits = content.len()
+                - content
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+            let marker = match content[digits..].chars().next() {
+                Some('.' | ')') if digits > 0 => Some(digits),
+                Some('#' | '+' | '-' | '=') if digits == 0 => Some(0),
+                _ => None,
+            };
+            if let Some(marker) = marker {
+                let split = line.len() - content.len() + marker;
+                out.push_str(&line[..split]);
+                out.push('\\');
+                rest = &line[split..];
+            }
+        }
+        for c in rest.chars() {
+            if MARKDOWN_SPECIAL.contains(&c) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+}
+
+/// A link destination, wrapped in angle brackets if it contains spaces or parentheses
+fn link_destination(url: &str) -> String {
+    if url.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_owned()
+    }
+}
+
+/// The first image, video or embed in `nodes`, outside of captions
+fn find_media(nodes: &[Node]) -> Option<&NodeElement> {
+    nodes.iter().find_map(|node| match node {
+        Node::NodeElement(element) => match &*element.tag {
+            "img" | "video" | "iframe" => Some(element),
+            "figcaption" => None,
+            _ => find_media(element.children.as_deref().unwrap_or_default()),
+        },
+        Node::Text(_) => None,
+    })
+}
+
+fn attr<'a>(element: &'a NodeElement, name: &str) -> Option<&'a str> {
+    element.attrs.as_ref()?.get(name)
+}
+
+fn heading_level(tag: &str) -> Option<usize> {
+    match tag.strip_prefix('h')?.parse() {
+        Ok(level @ 1..=6) => Some(level),
+        _ => None,
+    }
+}
+
+/// Length of the longest run of `c` in `text`
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|ch| ch != c).map(str::len).max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NodeElement;
+
+    #[test]
+    fn inline_formatting() {
+        let nodes = vec![
+            NodeElement::new("h3").child("Title").into_node(),
+            NodeElement::new("p")
+                .child("Some ")
+                .child(
+                    NodeElement::new("b")
+                        .child("bold ")
+                        .child(NodeElement::new("i").child("italic")),
+                )
+                .child(" and a ")
+                .child(NodeElement::new("a").child(NodeElement::new("code").child("link")))
+                .child(".")
+                .child(NodeElement::new("br"))
+                .child("Next line")
+                .into_node(),
+            NodeElement::new("figure")
+                .child(NodeElement::new("img"))
+                .child(NodeElement::new("figcaption").child("Caption"))
+                .into_node(),
+            NodeElement::new("img").into_node(),
+        ];
+        assert_eq!(
+            node_to_text(&nodes),
+            "Title\nSome bold italic and a link.\nNext line\nCaption"
+        );
+    }
+
+    #[test]
+    fn list_items() {
+        let nodes = vec![
+            NodeElement::new("ul")
+                .child(NodeElement::new("li").child("one"))
+                .child(
+                    NodeElement::new("li")
+                        .child(NodeElement::new("em").child("two"))
+                        .child(NodeElement::new("ol").child(NodeElement::new("li").child("three"))),
+                )
+                .into_node(),
+            NodeElement::new("p").child("after").into_node(),
+        ];
+        assert_eq!(node_to_text(&nodes), "one\ntwo\nthree\nafter");
+    }
+
+    #[test]
+    fn display() {
+        let link = NodeElement::new("a")
+            .attr("title", "\"quoted\"")
+            .attr("href", "/a?b&c")
+            .child("a < b");
+        let node = NodeElement::new("p")
+            .child(link.clone())
+            .child(NodeElement::br())
+            .child("x".repeat(100))
+            .child(NodeElement::new("i"))
+            .into_node();
+        assert_eq!(
+            node.to_string(),
+            format!(
+                r#"<p><a href="/a?b&amp;c" title="&quot;quoted&quot;">a &lt; b</a><br>{}…<i></i></p>"#,
+                "x".repeat(80)
+            )
+        );
+        assert_eq!(link.to_string(), node_to_html(&[link.into()]));
+        assert_eq!(Node::text("é".repeat(80)).to_string(), "é".repeat(80));
+    }
+
+    #[test]
+    fn page_markdown() {
+        // a page as returned by `getPage` with `return_content`
+        let page: crate::Page = serde_json::from_str(
+            r##"{
+                "path": "Sample-Page-12-15",
+                "url": "https://telegra.ph/Sample-Page-12-15",
+                "title": "Sample Page",
+                "description": "",
+                "views": 0,
+                "content": [
+                    {"tag": "h3", "children": ["Getting started"]},
+                    {"tag": "p", "children": [
+                        "Use ", {"tag": "strong", "children": ["bold "]}, "and ",
+                        {"tag": "em", "children": ["italic"]}, " text, ",
+                        {"tag": "a", "attrs": {"href": "https://example.com/a_(b)"}, "children": ["links"]},
+                        " and ", {"tag": "code", "children": ["a `tick`"]}, ".",
+                        {"tag": "br"}, "1. not a list, *not* _emphasis_ [nor] <html>"
+                    ]},
+                    {"tag": "figure", "children": [
+                        {"tag": "img", "attrs": {"src": "/file/6a5b15e7eb4d7329ca7af.jpg"}},
+                        {"tag": "figcaption", "children": ["A photo"]}
+                    ]},
+                    {"tag": "h4", "children": ["Lists"]},
+                    {"tag": "ul", "children": [
+                        {"tag": "li", "children": ["one"]},
+                        {"tag": "li", "children": ["two", {"tag": "ol", "children": [
+                            {"tag": "li", "children": ["first"]},
+                            {"tag": "li", "children": ["second", {"tag": "br"}, "continued"]}
+                        ]}]}
+                    ]},
+                    {"tag": "blockquote", "children": ["A quote", {"tag": "br"}, "on two lines"]},
+                    {"tag": "pre", "children": ["fn main() {\n    println!(\"```\");\n}\n"]},
+                    {"tag": "hr"},
+                    {"tag": "figure", "children": [
+                        {"tag": "iframe", "attrs": {"src": "/embed/youtube?url=https%3A%2F%2Fwww.youtube.com%2Fwatch%3Fv%3DdQw4w9WgXcQ"}},
+                        {"tag": "figcaption", "children": []}
+                    ]},
+                    {"tag": "aside", "children": ["# Pull quote"]}
+                ]
+            }"##,
+        )
+        .unwrap();
+        let markdown = node_to_markdown(page.content.as_deref().unwrap());
+        assert_eq!(
+            markdown,
+            r#"### Getting started
+
+Use **bold** and *italic* text, [links](<https://example.com/a_(b)>) and `` a `tick` ``.\
+1\. not a list, \*not\* \_emphasis\_ \[nor\] \<html\>
+
+![A photo](/file/6a5b15e7eb4d7329ca7af.jpg)
+*A photo*
+
+#### Lists
+
+- one
+- two
+  1. first
+  2. second\
+     continued
+
+> A quote\
+> on two lines
+
+````
+fn main() {
+    println!("```");
+}
+````
+
+---
+
+https://www.youtube.com/watch?v=dQw4w9WgXcQ
+
+> \# Pull quote"#
+        );
+    }
+
+    #[cfg(all(feature = "arbitrary", feature = "html"))]
+    proptest::proptest! {
+        #[test]
+        fn html_round_trip(bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..4096)) {
+            let nodes: Vec<Node> = arbitrary::Unstructured::new(&bytes).arbitrary().unwrap();
+            let once = crate::html_to_nodes(&node_to_html(&nodes)).unwrap();
+            let twice = crate::html_to_nodes(&node_to_html(&once)).unwrap();
+            proptest::prop_assert_eq!(twice, once);
+        }
+    }
+}
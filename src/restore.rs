@@ -0,0 +1,377 @@
+//! Restore pages from a directory written by [`Telegraph::export_all`]
+use crate::{
+    extract_images, file::text_to_nodes, nodes_to_content_string, rewrite_links, Error, FileFormat,
+    FrontMatter, ManifestEntry, Page, Result, Telegraph, ASSETS_DIR, MANIFEST_FILE,
+    MAX_CONTENT_SIZE,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+/// Name of the manifest written by [`Telegraph::restore`]
+pub const RESTORE_MANIFEST_FILE: &str = "restored.json";
+
+/// Options for [`Telegraph::restore`]
+///
+/// ```
+/// use telegraph_rs::RestoreOptions;
+///
+/// let options = RestoreOptions::new().dry_run(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    dry_run: bool,
+}
+
+impl RestoreOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to only report the pages which would be created,
+    /// without uploading assets, creating pages or writing the manifest, false by default.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// A page restored by [`Telegraph::restore`]
+#[derive(Debug, Clone)]
+pub struct RestoredPage {
+    /// Path of the exported page
+    pub old_path: String,
+    pub title: String,
+    /// The created page, `None` in a dry run
+    pub page: Option<Page>,
+}
+
+/// Result of [`Telegraph::restore`]
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    /// Restored pages, in the order of their old paths
+    pub restored: Vec<RestoredPage>,
+    /// Uploaded asset files, relative to the backup directory
+    pub uploaded: Vec<String>,
+    /// Old paths of pages whose content is larger than [`MAX_CONTENT_SIZE`],
+    /// with their size
+    pub too_large: Vec<(String, usize)>,
+    /// Old paths of pages which failed to restore
+    pub errors: Vec<(String, Error)>,
+}
+
+/// A page in the [`RESTORE_MANIFEST_FILE`], keyed by the old page path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoredEntry {
+    pub path: String,
+    pub url: String,
+}
+
+impl Telegraph {
+    /// Create the pages of a backup written by [`Telegraph::export_all`] under this account
+    ///
+    /// Pages are read from the files listed in its [`MANIFEST_FILE`] and converted according to
+    /// [`FileFormat::from_path`]. Without the `markdown` or `html` feature matching the export format,
+    /// those pages fail with [`Error::FeatureDisabled`] in [`RestoreReport::errors`] and nothing is created for them.
+    /// Images in [`ASSETS_DIR`] are uploaded again and the pages point to the uploaded files.
+    /// The new path and url of every restored page are written to [`RESTORE_MANIFEST_FILE`].
+    ///
+    /// Failures of single pages are collected in the report instead of aborting the restore.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use std::path::Path;
+    /// use telegraph_rs::{RestoreOptions, Telegraph};
+    ///
+    /// let telegraph = Telegraph::new("author").access_token("token").create().await?;
+    /// let options = RestoreOptions::new().dry_run(true);
+    /// let report = telegraph.restore(Path::new("backup"), &options).await?;
+    /// for page in &report.restored {
+    ///     println!("would create {}", page.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restore(&self, dir: &Path, options: &RestoreOptions) -> Result<RestoreReport> {
+        self.restore_to("https://telegra.ph/upload", dir, options)
+            .await
+    }
+
+    pub(crate) async fn restore_to(
+        &self,
+        upload_url: &str,
+        dir: &Path,
+        options: &RestoreOptions,
+    ) -> Result<RestoreReport> {
+        let manifest_file = dir.join(MANIFEST_FILE);
+        let manifest = std::fs::read_to_string(&manifest_file)
+            .and_then(|manifest| {
+                serde_json::from_str::<BTreeMap<String, ManifestEntry>>(&manifest)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            })
+            .map_err(|source| Error::FileError {
+                path: manifest_file,
+                source,
+            })?;
+
+        let mut report = RestoreReport::default();
+        let mut uploaded = HashMap::new();
+        for (old_path, entry) in manifest {
+            let result = self
+                .restore_page(upload_url, dir, &entry, options, &mut uploaded, &mut report)
+                .await;
+            match result {
+                Ok(page) => report.restored.push(RestoredPage {
+                    old_path,
+                    title: entry.title,
+                    page,
+                }),
+                Err(Error::ContentTooLarge { size, .. }) => report.too_large.push((old_path, size)),
+                Err(e) => report.errors.push((old_path, e)),
+            }
+        }
+
+        if !options.dry_run {
+            let restored = report
+                .restored
+                .iter()
+                .filter_map(|restored| {
+                    let page = restored.page.as_ref()?;
+                    let entry = RestoredEntry {
+                        path: page.path.clone(),
+                        url: page.url.clone(),
+                    };
+                    Some((restored.old_path.clone(), entry))
+                })
+                .collect::<BTreeMap<_, _>>();
+            let restored = serde_json::to_string_pretty(&restored).unwrap();
+            crate::export::write_file(&dir.join(RESTORE_MANIFEST_FILE), restored.as_bytes(), true)?;
+        }
+        Ok(report)
+    }
+
+    /// Restore a single page for [`Telegraph::restore`], `uploaded` maps assets to their uploaded `src`
+    async fn restore_page(
+        &self,
+        upload_url: &str,
+        dir: &Path,
+        entry: &ManifestEntry,
+        options: &RestoreOptions,
+        uploaded: &mut HashMap<String, String>,
+        report: &mut RestoreReport,
+    ) -> Result<Option<Page>> {
+        let file = dir.join(&entry.file);
+        let text = std::fs::read_to_string(&file).map_err(|source| Error::FileError {
+            path: file.clone(),
+            source,
+        })?;
+        let body = FrontMatter::parse(&text).map_or(&*text, |(_, body)| body);
+        let mut nodes = text_to_nodes(body, FileFormat::from_path(&file))?;
+
+        if !options.dry_run {
+            let asset_prefix = format!("{}/", ASSETS_DIR);
+            for image in extract_images(&nodes) {
+                if !image.src.starts_with(&asset_prefix) || uploaded.contains_key(&image.src) {
+                    continue;
+                }
+                let files = [dir.join(&image.src)];
+                let src = Self::upload_to(upload_url, &files, &self.client)
+                    .await?
+                    .pop()
+                    .ok_or_else(|| Error::ApiError("empty upload result".to_owned()))?
+                    .src;
+                report.uploaded.push(image.src.clone());
+                uploaded.insert(image.src, src);
+            }
+            rewrite_links(&mut nodes, |src| uploaded.get(src).cloned());
+        }
+
+        let content = nodes_to_content_string(&nodes);
+        // reported even if the account doesn't check the size
+        if content.len() > MAX_CONTENT_SIZE {
+            return Err(Error::ContentTooLarge {
+                size: content.len(),
+                limit: MAX_CONTENT_SIZE,
+            });
+        }
+        if options.dry_run {
+            self.check_content(&content)?;
+            return Ok(None);
+        }
+        let page = self.create_page(&entry.title, &content, false).await?;
+        Ok(Some(page))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{client, mock_server};
+    use std::path::PathBuf;
+
+    /// A backup with a page referencing an asset and a page which is too large
+    #[cfg(feature = "html")]
+    fn fixture() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("telegraph-rs-restore-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join(ASSETS_DIR)).unwrap();
+        std::fs::write(dir.join("assets/cat.jpg"), b"cat").unwrap();
+        std::fs::write(
+            dir.join("Cat-01-01.html"),
+            "---\ntitle: \"Cat\"\nurl: \"https://telegra.ph/Cat-01-01\"\nviews: 1\npath: \"Cat-01-01\"\n---\n\n<p>A cat</p><img src=\"assets/cat.jpg\">\n",
+        )
+        .unwrap();
+        let big = format!("<p>{}</p>", "word ".repeat(20000));
+        std::fs::write(dir.join("Big-01-01.html"), big).unwrap();
+        let manifest = serde_json::json!({
+            "Big-01-01": {"title": "Big", "url": "https://telegra.ph/Big-01-01", "file": "Big-01-01.html"},
+            "Cat-01-01": {"title": "Cat", "url": "https://telegra.ph/Cat-01-01", "file": "Cat-01-01.html"},
+            "Gone-01-01": {"title": "Gone", "url": "https://telegra.ph/Gone-01-01", "file": "Gone-01-01.html"},
+        });
+        std::fs::write(dir.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+        dir
+    }
+
+    #[cfg(feature = "html")]
+    #[tokio::test]
+    async fn restore() {
+        let page = crate::tests::page_response("Cat-10-16", "Cat");
+        let (addr, requests) = mock_server(vec![
+            (
+                "POST /upload",
+                "application/json",
+                br#"[{"src":"/file/new.jpg"}]"#.to_vec(),
+            ),
+            ("POST /createPage", "application/json", page),
+        ])
+        .await;
+        let telegraph = client(&addr).await;
+        let dir = fixture();
+        let upload_url = format!("{}/upload", addr);
+
+        let options = RestoreOptions::new().dry_run(true);
+        let report = telegraph
+            .restore_to(&upload_url, &dir, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.restored.len(), 1);
+        assert_eq!(report.restored[0].title, "Cat");
+        assert!(report.restored[0].page.is_none());
+        assert!(report.uploaded.is_empty());
+        assert_eq!(report.too_large.len(), 1);
+        assert!(requests.lock().unwrap().is_empty());
+        assert!(!dir.join(RESTORE_MANIFEST_FILE).exists());
+
+        let unchecked = Telegraph::new("test")
+            .access_token("token")
+            .api_url(&addr)
+            .check_content_size(false)
+            .create()
+            .await
+            .unwrap();
+        let report = unchecked
+            .restore_to(&upload_url, &dir, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.too_large.len(), 1);
+        assert_eq!(report.too_large[0].0, "Big-01-01");
+        assert_eq!(report.errors.len(), 1);
+
+        let report = telegraph
+            .restore_to(&upload_url, &dir, &RestoreOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(report.uploaded, ["assets/cat.jpg"]);
+        assert_eq!(report.too_large.len(), 1);
+        assert_eq!(report.too_large[0].0, "Big-01-01");
+        let errors = report
+            .errors
+            .iter()
+            .map(|(path, _)| &**path)
+            .collect::<Vec<_>>();
+        assert_eq!(errors, ["Gone-01-01"]);
+        assert!(report.errors[0].1.to_string().contains("Gone-01-01.html"));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("%2Ffile%2Fnew.jpg"), "{}", requests[1]);
+        let restored = std::fs::read_to_string(dir.join(RESTORE_MANIFEST_FILE)).unwrap();
+        let restored: BTreeMap<String, RestoredEntry> = serde_json::from_str(&restored).unwrap();
+        assert_eq!(restored["Cat-01-01"].path, "Cat-10-16");
+        assert_eq!(restored.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    const NOTE: &str = "# Note\n\nSome *text* and a [link](https://example.com).\n";
+
+    /// A backup with a single markdown page
+    fn markdown_fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "telegraph-rs-restore-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("Note-01-01.md"),
+            format!(
+                "---\ntitle: \"Note\"\nurl: \"https://telegra.ph/Note-01-01\"\nviews: 1\npath: \"Note-01-01\"\n---\n\n{}",
+                NOTE
+            ),
+        )
+        .unwrap();
+        let manifest = serde_json::json!({
+            "Note-01-01": {"title": "Note", "url": "https://telegra.ph/Note-01-01", "file": "Note-01-01.md"},
+        });
+        std::fs::write(dir.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+        dir
+    }
+
+    #[cfg(feature = "markdown")]
+    #[tokio::test]
+    async fn restore_markdown() {
+        use crate::tests::{form_field, page_response};
+
+        let page = page_response("Note-10-16", "Note");
+        let (addr, requests) =
+            mock_server(vec![("POST /createPage", "application/json", page)]).await;
+        let telegraph = client(&addr).await;
+        let dir = markdown_fixture("markdown");
+
+        let report = telegraph
+            .restore_to(&format!("{}/upload", addr), &dir, &RestoreOptions::new())
+            .await
+            .unwrap();
+        assert!(report.errors.is_empty());
+        assert_eq!(report.restored.len(), 1);
+        assert_eq!(report.restored[0].page.as_ref().unwrap().path, "Note-10-16");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            form_field(&requests[0], "content").unwrap(),
+            crate::markdown_to_node(NOTE).unwrap()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(not(feature = "markdown"))]
+    #[tokio::test]
+    async fn restore_markdown_disabled() {
+        let (addr, requests) = mock_server(vec![]).await;
+        let telegraph = client(&addr).await;
+        let dir = markdown_fixture("markdown-disabled");
+
+        let report = telegraph
+            .restore_to(&format!("{}/upload", addr), &dir, &RestoreOptions::new())
+            .await
+            .unwrap();
+        assert!(report.restored.is_empty());
+        assert!(matches!(
+            &report.errors[..],
+            [(path, Error::FeatureDisabled("markdown"))] if path == "Note-01-01"
+        ));
+        assert!(requests.lock().unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+//! Statistics of node trees
+use crate::{Node, Page};
+use std::time::Duration;
+
+/// Elements whose text is separated from the surrounding text
+const WORD_BREAK_TAGS: &[&str] = &[
+    "p",
+    "li",
+    "h3",
+    "h4",
+    "blockquote",
+    "aside",
+    "pre",
+    "figure",
+    "figcaption",
+    "ul",
+    "ol",
+    "br",
+    "hr",
+];
+
+/// Options for [`word_count_with_options`] and [`reading_time_with_options`]
+///
+/// ```
+/// use telegraph_rs::ReadingOptions;
+///
+/// let options = ReadingOptions::new().cjk_factor(0.5).skip_code(true).image_secs(10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadingOptions {
+    cjk_factor: f64,
+    skip_code: bool,
+    image_secs: u64,
+}
+
+impl Default for ReadingOptions {
+    fn default() -> Self {
+        Self {
+            cjk_factor: 1.0,
+            skip_code: false,
+            image_secs: 12,
+        }
+    }
+}
+
+impl ReadingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of words a Chinese or Japanese character counts as, 1 by default.
+    pub fn cjk_factor(mut self, factor: f64) -> Self {
+        self.cjk_factor = factor;
+        self
+    }
+
+    /// Whether to ignore the text of `pre` and `code` elements, false by default.
+    pub fn skip_code(mut self, skip: bool) -> Self {
+        self.skip_code = skip;
+        self
+    }
+
+    /// Seconds added to the reading time for every image, 12 by default.
+    pub fn image_secs(mut self, secs: u64) -> Self {
+        self.image_secs = secs;
+        self
+    }
+}
+
+/// Number of words in the text of `nodes`, see [`word_count_with_options`]
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, word_count};
+///
+/// let nodes = html_to_nodes("<p>Hello, <b>wonderful</b> world!</p><p>你好世界</p>").unwrap();
+/// assert_eq!(word_count(&nodes), 7);
+/// ```
+pub fn word_count(nodes: &[Node]) -> usize {
+    word_count_with_options(nodes, &ReadingOptions::default())
+}
+
+/// Number of words in the text of `nodes`
+///
+/// Words are separated by whitespace and have to contain a letter or digit,
+/// Chinese and Japanese characters are counted one by one and multiplied by the `cjk_factor`.
+pub fn word_count_with_options(nodes: &[Node], options: &ReadingOptions) -> usize {
+    let mut text = String::new();
+    collect_text(nodes, options, &mut text);
+
+    let (mut words, mut cjk) = (0, 0);
+    let mut in_word = false;
+    for c in text.chars().chain([' ']) {
+        if is_cjk(c) || c.is_whitespace() {
+            words += usize::from(in_word);
+            cjk += usize::from(is_cjk(c));
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            in_word = true;
+        }
+    }
+    words + (cjk as f64 * options.cjk_factor).round() as usize
+}
+
+/// Estimated time to read `nodes` at `wpm` words per minute, see [`reading_time_with_options`]
+///
+/// ```
+/// use std::time::Duration;
+/// use telegraph_rs::{html_to_nodes, reading_time};
+///
+/// let nodes = html_to_nodes("<p>one two three</p><img src=\"/file/a.jpg\">").unwrap();
+/// assert_eq!(reading_time(&nodes, 60), Duration::from_secs(15));
+/// ```
+pub fn reading_time(nodes: &[Node], wpm: usize) -> Duration {
+    reading_time_with_options(nodes, wpm, &ReadingOptions::default())
+}
+
+/// Estimated time to read `nodes` at `wpm` words per minute, plus `image_secs` for every image
+pub fn reading_time_with_options(nodes: &[Node], wpm: usize, options: &ReadingOptions) -> Duration {
+    let words = word_count_with_options(nodes, options);
+    let reading = Duration::from_secs_f64(words as f64 * 60.0 / wpm.max(1) as f64);
+    reading + Duration::from_secs(options.image_secs * count_images(nodes) as u64)
+}
+
+impl Page {
+    /// Number of words of the page content, see [`word_count`]
+    pub fn word_count(&self) -> Option<usize> {
+        self.content.as_deref().map(word_count)
+    }
+
+    /// Estimated time to read the page content, see [`reading_time`]
+    pub fn reading_time(&self, wpm: usize) -> Option<Duration> {
+        self.content
+            .as_deref()
+            .map(|nodes| reading_time(nodes, wpm))
+    }
+}
+
+fn collect_text(nodes: &[Node], options: &ReadingOptions, text: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => text.push_str(t),
+            Node::NodeElement(element) => {
+                if options.skip_code && (element.tag == "pre" || element.tag == "code") {
+                    continue;
+                }
+                let separate = WORD_BREAK_TAGS.contains(&&*element.tag);
+                if separate {
+                    text.push(' ');
+                }
+                collect_text(
+                    element.children.as_deref().unwrap_or_default(),
+                    options,
+                    text,
+                );
+                if separate {
+                    text.push(' ');
+                }
+            }
+        }
+    }
+}
+
+fn count_images(nodes: &[Node]) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::NodeElement(element) => {
+                usize::from(element.tag == "img")
+                    + count_images(element.children.as_deref().unwrap_or_default())
+            }
+            Node::Text(_) => 0,
+        })
+        .sum()
+}
+
+/// Whether `c` is a Chinese character or Japanese kana, which are written without spaces
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{2fa1f}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes(json: serde_json::Value) -> Vec<Node> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn latin_words() {
+        let nodes = nodes(serde_json::json!([
+            {"tag": "h3", "children": ["Title"]},
+            {"tag": "p", "children": ["It's a ", {"tag": "b", "children": ["well"]}, "-known fact — really."]},
+            {"tag": "ul", "children": [{"tag": "li", "children": ["one"]}, {"tag": "li", "children": ["two"]}]},
+            {"tag": "pre", "children": ["let x = 1;"]},
+        ]));
+        assert_eq!(word_count(&nodes), 11);
+        let options = ReadingOptions::new().skip_code(true);
+        assert_eq!(word_count_with_options(&nodes, &options), 8);
+    }
+
+    #[test]
+    fn cjk_words() {
+        let nodes = nodes(serde_json::json!([
+            {"tag": "p", "children": ["日本語のテキスト、Rust 言語"]},
+        ]));
+        assert_eq!(word_count(&nodes), 11);
+        let options = ReadingOptions::new().cjk_factor(0.5);
+        assert_eq!(word_count_with_options(&nodes, &options), 6);
+    }
+
+    #[test]
+    fn images() {
+        let page: Page = serde_json::from_value(serde_json::json!({
+            "path": "Images-01-01",
+            "url": "https://telegra.ph/Images-01-01",
+            "title": "Images",
+            "description": "",
+            "views": 0,
+            "content": [
+                {"tag": "p", "children": ["word ".repeat(200)]},
+                {"tag": "figure", "children": [{"tag": "img", "attrs": {"src": "/file/a.jpg"}}]},
+                {"tag": "img", "attrs": {"src": "/file/b.jpg"}},
+                {"tag": "p", "children": [{"tag": "img", "attrs": {"src": "/file/c.jpg"}}]},
+            ],
+        }))
+        .unwrap();
+        assert_eq!(page.word_count(), Some(200));
+        assert_eq!(
+            page.reading_time(200),
+            Some(Duration::from_secs(60 + 3 * 12))
+        );
+        let options = ReadingOptions::new().image_secs(5);
+        let content = page.content.as_deref().unwrap();
+        assert_eq!(
+            reading_time_with_options(content, 100, &options),
+            Duration::from_secs(120 + 15)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Typed names of elements
+use crate::NodeElement;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::Infallible, fmt, str::FromStr};
+
+macro_rules! tags {
+    ($($variant:ident => $name:literal,)*) => {
+        /// Name of an element, see [`ALLOWED_TAGS`](crate::ALLOWED_TAGS)
+        ///
+        /// Serialized as the lowercase tag name, names not accepted by Telegraph become `Unknown`.
+        ///
+        /// ```
+        /// use telegraph_rs::Tag;
+        ///
+        /// assert_eq!("figcaption".parse(), Ok(Tag::Figcaption));
+        /// assert_eq!("figcap".parse(), Ok(Tag::Unknown("figcap".to_owned())));
+        /// assert_eq!(Tag::Blockquote.to_string(), "blockquote");
+        /// ```
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum Tag {
+            $($variant,)*
+            Unknown(String),
+        }
+
+        impl Tag {
+            /// Name of the tag
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Tag::$variant => $name,)*
+                    Tag::Unknown(name) => name,
+                }
+            }
+        }
+
+        impl From<&str> for Tag {
+            fn from(name: &str) -> Self {
+                match name {
+                    $($name => Tag::$variant,)*
+                    _ => Tag::Unknown(name.to_owned()),
+                }
+            }
+        }
+    };
+}
+
+tags! {
+    A => "a",
+    Aside => "aside",
+    B => "b",
+    Blockquote => "blockquote",
+    Br => "br",
+    Code => "code",
+    Em => "em",
+    Figcaption => "figcaption",
+    Figure => "figure",
+    H3 => "h3",
+    H4 => "h4",
+    Hr => "hr",
+    I => "i",
+    Iframe => "iframe",
+    Img => "img",
+    Li => "li",
+    Ol => "ol",
+    P => "p",
+    Pre => "pre",
+    S => "s",
+    Strong => "strong",
+    U => "u",
+    Ul => "ul",
+    Video => "video",
+}
+
+impl Tag {
+    /// Whether Telegraph accepts the tag
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Tag::Unknown(_))
+    }
+}
+
+impl FromStr for Tag {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(name.into())
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(String::deserialize(deserializer)?.as_str().into())
+    }
+}
+
+impl NodeElement {
+    /// The tag of the element
+    pub fn tag_enum(&self) -> Tag {
+        self.tag.as_str().into()
+    }
+
+    /// Replace the tag of the element
+    pub fn set_tag(&mut self, tag: Tag) {
+        self.tag = match tag {
+            Tag::Unknown(name) => name,
+            tag => tag.as_str().to_owned(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ALLOWED_TAGS;
+
+    #[test]
+    fn round_trip() {
+        for name in ALLOWED_TAGS {
+            let tag = name.parse::<Tag>().unwrap();
+            assert!(tag.is_known(), "{}", name);
+            assert_eq!(tag.to_string(), *name);
+            let json = serde_json::to_string(&tag).unwrap();
+            assert_eq!(json, format!("\"{}\"", name));
+            assert_eq!(serde_json::from_str::<Tag>(&json).unwrap(), tag);
+        }
+
+        let tag = serde_json::from_str::<Tag>(r#""DIV""#).unwrap();
+        assert_eq!(tag, Tag::Unknown("DIV".to_owned()));
+        assert_eq!(serde_json::to_string(&tag).unwrap(), r#""DIV""#);
+    }
+
+    #[test]
+    fn element_tag() {
+        let mut element = NodeElement::br();
+        assert_eq!(element.tag_enum(), Tag::Br);
+        element.set_tag(Tag::Hr);
+        assert_eq!(element.tag, "hr");
+        element.set_tag(Tag::Unknown("wbr".to_owned()));
+        assert_eq!(element.tag_enum(), Tag::Unknown("wbr".to_owned()));
+    }
+}
@@ -0,0 +1,1137 @@
+//! Helpers for working with node trees
+use crate::{Error, Node, NodeElement, Result};
+use std::collections::HashMap;
+
+/// Elements which are meaningful without any children
+const KEEP_EMPTY_TAGS: &[&str] = &["br", "hr", "img", "iframe", "video", "figcaption"];
+
+/// Maximum size of page content accepted by Telegraph, see [`content_size`]
+pub const MAX_CONTENT_SIZE: usize = 64 * 1024;
+
+/// Size of the content sent to Telegraph for `nodes`, which is the length of its JSON
+///
+/// ```
+/// use telegraph_rs::{content_size, Node};
+///
+/// assert_eq!(content_size(&[Node::text("Hello")]), r#"["Hello"]"#.len());
+/// ```
+pub fn content_size(nodes: &[Node]) -> usize {
+    serde_json::to_string(nodes).map_or(0, |json| json.len())
+}
+
+/// Serialize `nodes` into the content string accepted by [`Telegraph::create_page`](crate::Telegraph::create_page)
+pub fn nodes_to_content_string(nodes: &[Node]) -> String {
+    serde_json::to_string(nodes).expect("nodes always serialize")
+}
+
+/// Page content, either serialized JSON or nodes
+///
+/// Accepted by [`Telegraph::create_page_from`](crate::Telegraph::create_page_from),
+/// [`Telegraph::create_page_with`](crate::Telegraph::create_page_with) and their `edit_page` counterparts.
+pub trait IntoContent {
+    /// The content as a JSON string
+    fn into_content(self) -> String;
+}
+
+impl IntoContent for &str {
+    fn into_content(self) -> String {
+        self.to_owned()
+    }
+}
+
+impl IntoContent for String {
+    fn into_content(self) -> String {
+        self
+    }
+}
+
+impl IntoContent for &[Node] {
+    fn into_content(self) -> String {
+        nodes_to_content_string(self)
+    }
+}
+
+impl IntoContent for &Vec<Node> {
+    fn into_content(self) -> String {
+        nodes_to_content_string(self)
+    }
+}
+
+impl IntoContent for Vec<Node> {
+    fn into_content(self) -> String {
+        nodes_to_content_string(&self)
+    }
+}
+
+/// Clean up a node tree
+///
+/// - elements without meaningful children are removed, except `br`, `hr`, `img`, `iframe`, `video` and `figcaption`
+/// - runs of more than two consecutive `br` elements are shortened to two
+/// - trailing `br` elements at the end of the document are removed
+pub fn clean_nodes(nodes: &mut Vec<Node>) {
+    clean_children(nodes);
+    trim_trailing_breaks(nodes);
+}
+
+/// The anchor Telegraph generates for a heading with the given text
+///
+/// Telegraph replaces every run of whitespace (including non-breaking spaces and line breaks)
+/// with a single dash and keeps everything else as-is: case, punctuation,
+/// non-ASCII letters and emoji. Leading and trailing whitespace is dropped.
+/// The anchor is not percent-encoded, so it can be used in a `#fragment` href directly.
+///
+/// ```
+/// assert_eq!(telegraph_rs::anchor_slug(" Getting  started "), "Getting-started");
+/// assert_eq!(telegraph_rs::anchor_slug("Что такое Telegraph?"), "Что-такое-Telegraph?");
+/// ```
+pub fn anchor_slug(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+/// Point fragment links like `#getting-started` at the anchors Telegraph generates for
+/// `h3` and `h4` headings, e.g. `#Getting-Started`
+///
+/// A link is rewritten if its fragment matches the text of a heading,
+/// ignoring case and anything other than letters and digits.
+pub fn fix_fragment_links(nodes: &mut [Node]) {
+    fn rewrite_links(nodes: &mut [Node], anchors: &HashMap<String, String>) {
+        for node in nodes {
+            if let Node::NodeElement(element) = node {
+                if element.tag == "a" {
+                    if let Some(href) = element.attrs.as_mut().and_then(|a| a.href.as_mut()) {
+                        let anchor = href
+                            .strip_prefix('#')
+                            .filter(|fragment| !anchors.values().any(|anchor| anchor == fragment))
+                            .and_then(|fragment| anchors.get(&loose_key(fragment)));
+                        if let Some(anchor) = anchor {
+                            *href = format!("#{}", anchor);
+                        }
+                    }
+                }
+                rewrite_links(element.children.as_deref_mut().unwrap_or_default(), anchors);
+            }
+        }
+    }
+
+    let mut anchors = HashMap::new();
+    for (_, text) in headings(nodes) {
+        anchors
+            .entry(loose_key(&text))
+            .or_insert_with(|| anchor_slug(&text));
+    }
+    anchors.remove("");
+    rewrite_links(nodes, &anchors);
+}
+
+/// Attributes holding links
+const LINK_ATTRS: &[&str] = &["href", "src"];
+
+/// Call `f` with every `href` and `src` in `nodes` and replace the value if it returns `Some`
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, node_to_html, rewrite_links};
+///
+/// let mut nodes = html_to_nodes(r#"<p><a href="https://old.example/a">a</a></p>"#).unwrap();
+/// rewrite_links(&mut nodes, |url| {
+///     let path = url.strip_prefix("https://old.example/")?;
+///     Some(format!("https://new.example/{}", path))
+/// });
+/// assert_eq!(node_to_html(&nodes), r#"<p><a href="https://new.example/a">a</a></p>"#);
+/// ```
+pub fn rewrite_links(nodes: &mut [Node], mut f: impl FnMut(&str) -> Option<String>) {
+    fn rewrite(nodes: &mut [Node], f: &mut dyn FnMut(&str) -> Option<String>) {
+        for node in nodes {
+            if let Node::NodeElement(element) = node {
+                if let Some(attrs) = &mut element.attrs {
+                    for value in
+                        IntoIterator::into_iter([&mut attrs.href, &mut attrs.src]).flatten()
+                    {
+                        if let Some(new) = f(value) {
+                            *value = new;
+                        }
+                    }
+                }
+                rewrite(element.children.as_deref_mut().unwrap_or_default(), f);
+            }
+        }
+    }
+
+    rewrite(nodes, &mut f);
+}
+
+/// Every `href` and `src` in `nodes`, in document order
+pub fn collect_links(nodes: &[Node]) -> Vec<String> {
+    fn collect(nodes: &[Node], links: &mut Vec<String>) {
+        for node in nodes {
+            if let Node::NodeElement(element) = node {
+                if let Some(attrs) = &element.attrs {
+                    for name in LINK_ATTRS {
+                        if let Some(value) = attrs.get(name) {
+                            links.push(value.to_owned());
+                        }
+                    }
+                }
+                collect(element.children.as_deref().unwrap_or_default(), links);
+            }
+        }
+    }
+
+    let mut links = vec![];
+    collect(nodes, &mut links);
+    links
+}
+
+/// An image or video referenced by a node tree, see [`extract_images`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    /// `src` of the element
+    pub src: String,
+    /// Text of the `figcaption` of the enclosing `figure`, if any
+    pub caption: Option<String>,
+    /// Indices of the element and its ancestors, for [`node_at_mut`]
+    pub path: Vec<usize>,
+}
+
+/// Every `img` and `video` with a `src` in `nodes`, in document order
+///
+/// ```
+/// use telegraph_rs::{extract_images, html_to_nodes};
+///
+/// let html = r#"<figure><img src="/file/cat.jpg"><figcaption>A cat</figcaption></figure>"#;
+/// let images = extract_images(&html_to_nodes(html).unwrap());
+/// assert_eq!(images[0].src, "/file/cat.jpg");
+/// assert_eq!(images[0].caption.as_deref(), Some("A cat"));
+/// assert_eq!(images[0].path, [0, 0]);
+/// ```
+pub fn extract_images(nodes: &[Node]) -> Vec<ImageRef> {
+    fn collect(
+        nodes: &[Node],
+        caption: Option<&str>,
+        path: &mut Vec<usize>,
+        images: &mut Vec<ImageRef>,
+    ) {
+        for (i, node) in nodes.iter().enumerate() {
+            let element = match node {
+                Node::NodeElement(element) => element,
+                Node::Text(_) => continue,
+            };
+            path.push(i);
+            let children = element.children.as_deref().unwrap_or_default();
+            match &*element.tag {
+                "img" | "video" => {
+                    let src = element.attrs.as_ref().and_then(|attrs| attrs.src.as_ref());
+                    if let Some(src) = src {
+                        images.push(ImageRef {
+                            src: src.clone(),
+                            caption: caption.map(str::to_owned),
+                            path: path.clone(),
+                        });
+                    }
+                }
+                "figure" => {
+                    let mut text = String::new();
+                    for child in children {
+                        if let Node::NodeElement(child) = child {
+                            if child.tag == "figcaption" {
+                                text_content(
+                                    child.children.as_deref().unwrap_or_default(),
+                                    &mut text,
+                                );
+                            }
+                        }
+                    }
+                    let text = text.trim();
+                    collect(children, (!text.is_empty()).then_some(text), path, images);
+                }
+                _ => collect(children, caption, path, images),
+            }
+            path.pop();
+        }
+    }
+
+    let mut images = vec![];
+    collect(nodes, None, &mut vec![], &mut images);
+    images
+}
+
+/// The node at `path`, which are the indices of the node and its ancestors
+pub fn node_at<'a>(nodes: &'a [Node], path: &[usize]) -> Option<&'a Node> {
+    let (last, ancestors) = path.split_last()?;
+    let mut nodes = nodes;
+    for &i in ancestors {
+        match nodes.get(i)? {
+            Node::NodeElement(element) => nodes = element.children.as_deref()?,
+            Node::Text(_) => return None,
+        }
+    }
+    nodes.get(*last)
+}
+
+/// The node at `path`, see [`node_at`]
+pub fn node_at_mut<'a>(nodes: &'a mut [Node], path: &[usize]) -> Option<&'a mut Node> {
+    let (last, ancestors) = path.split_last()?;
+    let mut nodes = nodes;
+    for &i in ancestors {
+        match nodes.get_mut(i)? {
+            Node::NodeElement(element) => nodes = element.children.as_deref_mut()?,
+            Node::Text(_) => return None,
+        }
+    }
+    nodes.get_mut(*last)
+}
+
+/// Insert `node` at `path`, shifting the node there and its following siblings
+///
+/// The last index may be the number of siblings, to append `node`.
+/// Fails with [`Error::InvalidPath`] if the parent doesn't exist or is a text node,
+/// or the index is out of range.
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, insert_at, node_to_html, Node};
+///
+/// let mut nodes = html_to_nodes("<p>a</p>").unwrap();
+/// insert_at(&mut nodes, &[0, 1], Node::text("b")).unwrap();
+/// insert_at(&mut nodes, &[0], Node::code_block("c")).unwrap();
+/// assert_eq!(node_to_html(&nodes), "<pre><code>c</code></pre><p>ab</p>");
+/// assert!(insert_at(&mut nodes, &[3], Node::text("d")).is_err());
+/// ```
+pub fn insert_at(nodes: &mut Vec<Node>, path: &[usize], node: Node) -> Result<()> {
+    match path.split_last() {
+        Some((&last, [])) if last <= nodes.len() => nodes.insert(last, node),
+        Some((&last, ancestors)) => match node_at_mut(nodes, ancestors) {
+            Some(Node::NodeElement(element))
+                if last <= element.children.as_ref().map_or(0, Vec::len) =>
+            {
+                element
+                    .children
+                    .get_or_insert_with(Vec::new)
+                    .insert(last, node)
+            }
+            _ => return Err(Error::InvalidPath(path.to_vec())),
+        },
+        None => return Err(Error::InvalidPath(vec![])),
+    }
+    Ok(())
+}
+
+/// Remove the node at `path` and return it, shifting its following siblings
+///
+/// Fails with [`Error::InvalidPath`] if there is no node at `path`.
+pub fn remove_at(nodes: &mut Vec<Node>, path: &[usize]) -> Result<Node> {
+    let invalid = || Error::InvalidPath(path.to_vec());
+    match path.split_last() {
+        Some((&last, [])) if last < nodes.len() => Ok(nodes.remove(last)),
+        Some((&last, ancestors)) => match node_at_mut(nodes, ancestors) {
+            Some(Node::NodeElement(element)) => match &mut element.children {
+                Some(children) if last < children.len() => {
+                    let node = children.remove(last);
+                    if children.is_empty() {
+                        element.children = None;
+                    }
+                    Ok(node)
+                }
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        },
+        None => Err(invalid()),
+    }
+}
+
+/// Replace the node at `path` with `node` and return the old one
+///
+/// Fails with [`Error::InvalidPath`] if there is no node at `path`.
+pub fn replace_at(nodes: &mut [Node], path: &[usize], node: Node) -> Result<Node> {
+    let old = node_at_mut(nodes, path).ok_or_else(|| Error::InvalidPath(path.to_vec()))?;
+    Ok(std::mem::replace(old, node))
+}
+
+/// Indices of a node and its ancestors, starting at the root, see [`node_at`]
+pub type NodePath = Vec<usize>;
+
+/// Depth-first iterator over a node tree, see [`walk`]
+#[derive(Debug, Clone)]
+pub struct NodeIter<'a> {
+    /// Siblings of the visited node and its ancestors, with the index of the next one
+    stack: Vec<(&'a [Node], usize)>,
+}
+
+impl<'a> Iterator for NodeIter<'a> {
+    type Item = (&'a Node, NodePath);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (nodes, next) = self.stack.last_mut()?;
+            let nodes: &'a [Node] = nodes;
+            let node = match nodes.get(*next) {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            *next += 1;
+            let path = self.stack.iter().map(|(_, next)| next - 1).collect();
+            if let Node::NodeElement(element) = node {
+                if let Some(children) = &element.children {
+                    self.stack.push((children, 0));
+                }
+            }
+            return Some((node, path));
+        }
+    }
+}
+
+/// Every node in `nodes` with its path, in document order
+///
+/// Elements come before their children.
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, node_at, walk, Node};
+///
+/// let nodes = html_to_nodes("<p>Hello, <b>world</b></p>").unwrap();
+/// let texts = walk(&nodes)
+///     .filter_map(|(node, path)| match node {
+///         Node::Text(text) => Some((text.as_str(), path)),
+///         Node::NodeElement(_) => None,
+///     })
+///     .collect::<Vec<_>>();
+/// assert_eq!(texts, [("Hello, ", vec![0, 0]), ("world", vec![0, 1, 0])]);
+/// assert_eq!(node_at(&nodes, &texts[1].1), Some(&Node::text("world")));
+/// ```
+pub fn walk(nodes: &[Node]) -> NodeIter<'_> {
+    NodeIter {
+        stack: vec![(nodes, 0)],
+    }
+}
+
+/// What [`visit_mut`] does after visiting a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// Go on with the children of the node
+    Children,
+    /// Skip the children of the node
+    SkipChildren,
+}
+
+/// Call `f` on every node in `nodes` with its path, in document order
+///
+/// Elements are visited before their children, so changes to the children are seen when they are visited.
+///
+/// ```
+/// use telegraph_rs::{html_to_nodes, node_to_html, visit_mut, Node, Visit};
+///
+/// let mut nodes = html_to_nodes("<p>a <code>b</code></p>").unwrap();
+/// visit_mut(&mut nodes, |node, _| match node {
+///     Node::NodeElement(element) if element.tag == "code" => Visit::SkipChildren,
+///     Node::Text(text) => {
+///         *text = text.to_uppercase();
+///         Visit::Children
+///     }
+///     Node::NodeElement(_) => Visit::Children,
+/// });
+/// assert_eq!(node_to_html(&nodes), "<p>A <code>b</code></p>");
+/// ```
+pub fn visit_mut(nodes: &mut [Node], mut f: impl FnMut(&mut Node, &[usize]) -> Visit) {
+    fn visit(
+        nodes: &mut [Node],
+        path: &mut NodePath,
+        f: &mut impl FnMut(&mut Node, &[usize]) -> Visit,
+    ) {
+        for (i, node) in nodes.iter_mut().enumerate() {
+            path.push(i);
+            if f(node, path) == Visit::Children {
+                if let Node::NodeElement(element) = node {
+                    visit(element.children.as_deref_mut().unwrap_or_default(), path, f);
+                }
+            }
+            path.pop();
+        }
+    }
+
+    visit(nodes, &mut vec![], &mut f);
+}
+
+/// Build a table of contents from the `h3` and `h4` headings of `nodes`
+///
+/// It is a list of links to the heading anchors, `h4` headings are nested under the preceding `h3`.
+/// Headings with the same text link to the same anchor, since Telegraph gives them the same one.
+/// Returns nothing if there are no headings.
+///
+/// ```
+/// use telegraph_rs::{generate_toc, html_to_nodes, node_to_html};
+///
+/// let nodes = html_to_nodes("<h3>Intro</h3><h4>Getting started</h4><h3>Usage</h3>").unwrap();
+/// assert_eq!(
+///     node_to_html(&generate_toc(&nodes)),
+///     concat!(
+///         r##"<ul><li><a href="#Intro">Intro</a><ul><li><a href="#Getting-started">Getting started</a></li></ul></li>"##,
+///         r##"<li><a href="#Usage">Usage</a></li></ul>"##,
+///     )
+/// );
+/// ```
+pub fn generate_toc(nodes: &[Node]) -> Vec<Node> {
+    let mut items: Vec<(Node, Vec<Node>, bool)> = vec![];
+    for (tag, text) in headings(nodes) {
+        let slug = anchor_slug(&text);
+        if slug.is_empty() {
+            continue;
+        }
+        let link = NodeElement::new("a")
+            .with_href(&format!("#{}", slug))
+            .child(text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .into_node();
+        match items.last_mut() {
+            Some((_, children, true)) if tag == "h4" => {
+                children.push(NodeElement::new("li").child(link).into_node())
+            }
+            _ => items.push((link, vec![], tag == "h3")),
+        }
+    }
+    if items.is_empty() {
+        return vec![];
+    }
+    let items = items.into_iter().map(|(link, children, _)| {
+        let item = NodeElement::new("li").child(link);
+        match children.is_empty() {
+            true => item,
+            false => item.child(NodeElement::new("ul").children(children)),
+        }
+    });
+    vec![NodeElement::new("ul").children(items).into_node()]
+}
+
+/// Insert the table of contents built by [`generate_toc`] at the start of `nodes`,
+/// after the lead paragraph if the content starts with one
+pub fn insert_toc(nodes: &mut Vec<Node>) {
+    let toc = generate_toc(nodes);
+    let lead = matches!(nodes.first(), Some(Node::NodeElement(element)) if element.tag == "p");
+    let at = usize::from(lead);
+    nodes.splice(at..at, toc);
+}
+
+/// Tags and text of the `h3` and `h4` headings in `nodes`
+fn headings(nodes: &[Node]) -> Vec<(&str, String)> {
+    fn collect<'a>(nodes: &'a [Node], headings: &mut Vec<(&'a str, String)>) {
+        for node in nodes {
+            if let Node::NodeElement(element) = node {
+                let children = element.children.as_deref().unwrap_or_default();
+                if element.tag == "h3" || element.tag == "h4" {
+                    let mut text = String::new();
+                    text_content(children, &mut text);
+                    headings.push((&element.tag, text));
+                } else {
+                    collect(children, headings);
+                }
+            }
+        }
+    }
+
+    let mut headings = vec![];
+    collect(nodes, &mut headings);
+    headings
+}
+
+/// Lowercase letters and digits of `text`
+fn loose_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Concatenate the text of all nodes
+pub(crate) fn text_content(nodes: &[Node], text: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => text.push_str(t),
+            Node::NodeElement(element) => {
+                text_content(element.children.as_deref().unwrap_or_default(), text)
+            }
+        }
+    }
+}
+
+fn clean_children(nodes: &mut Vec<Node>) {
+    for node in nodes.iter_mut() {
+        if let Node::NodeElement(element) = node {
+            if let Some(children) = &mut element.children {
+                clean_children(children);
+                if children.is_empty() {
+                    element.children = None;
+                }
+            }
+        }
+    }
+    nodes.retain(|node| !is_empty_element(node));
+
+    let mut breaks = 0;
+    nodes.retain(|node| {
+        if is_break(node) {
+            breaks += 1;
+            breaks <= 2
+        } else {
+            breaks = 0;
+            true
+        }
+    });
+}
+
+fn trim_trailing_breaks(nodes: &mut Vec<Node>) {
+    loop {
+        while nodes
+            .last()
+            .is_some_and(|node| is_break(node) || is_blank(node))
+        {
+            nodes.pop();
+        }
+        match nodes.last_mut() {
+            Some(Node::NodeElement(element)) if element.children.is_some() => {
+                let children = element.children.as_mut().unwrap();
+                trim_trailing_breaks(children);
+                if children.is_empty() {
+                    element.children = None;
+                }
+            }
+            _ => return,
+        }
+        if !nodes.last().is_some_and(is_empty_element) {
+            return;
+        }
+        nodes.pop();
+    }
+}
+
+/// The `src` of the image Telegraph uses as the preview of a page with `nodes` as content,
+/// see [`set_cover_image`]
+pub fn cover_image(nodes: &[Node]) -> Option<&str> {
+    walk(nodes).find_map(|(node, _)| match node {
+        Node::NodeElement(element) if element.tag == "img" => {
+            element.attrs.as_ref()?.src.as_deref()
+        }
+        _ => None,
+    })
+}
+
+/// Make the image at `src` the preview of a page with `nodes` as content
+///
+/// Telegraph sets [`Page::image_url`](crate::Page::image_url) of a page from its content, as observed:
+///
+/// - it's the first `img` in document order, wherever it's nested, see [`cover_image`],
+/// - videos and embeds are ignored,
+/// - a relative `src` like `/file/cat.jpg` is made absolute, like `https://telegra.ph/file/cat.jpg`,
+/// - without any `img`, the page has no image.
+///
+/// A top-level `figure` or `img` showing `src` is moved before the first top-level node holding an image,
+/// otherwise a new figure is inserted there, or at the start if there are no images.
+///
+/// ```
+/// use telegraph_rs::{cover_image, set_cover_image, Node};
+///
+/// let mut nodes = vec![Node::text("Intro"), Node::figure("/file/a.jpg", None), Node::figure("/file/b.jpg", None)];
+/// set_cover_image(&mut nodes, "/file/b.jpg");
+/// assert_eq!(cover_image(&nodes), Some("/file/b.jpg"));
+/// assert_eq!(nodes[1], Node::figure("/file/b.jpg", None));
+/// ```
+pub fn set_cover_image(nodes: &mut Vec<Node>, src: &str) {
+    let cover = |node: &Node| cover_image(std::slice::from_ref(node)).map(str::to_owned);
+    let first = nodes.iter().position(|node| cover(node).is_some());
+    let existing = nodes.iter().position(|node| {
+        matches!(node, Node::NodeElement(element) if element.tag == "figure" || element.tag == "img")
+            && cover(node).as_deref() == Some(src)
+    });
+    match (first, existing) {
+        (Some(first), Some(existing)) => {
+            let node = nodes.remove(existing);
+            nodes.insert(first, node);
+        }
+        (first, _) => nodes.insert(first.unwrap_or(0), Node::figure(src, None)),
+    }
+}
+
+fn is_blank(node: &Node) -> bool {
+    matches!(node, Node::Text(text) if text.trim().is_empty())
+}
+
+fn is_break(node: &Node) -> bool {
+    matches!(node, Node::NodeElement(element) if element.tag == "br")
+}
+
+fn is_empty_element(node: &Node) -> bool {
+    match node {
+        Node::NodeElement(element) => {
+            !KEEP_EMPTY_TAGS.contains(&&*element.tag)
+                && element.children.iter().flatten().all(is_blank)
+        }
+        Node::Text(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::client;
+    use crate::NodeElement;
+
+    fn cleaned(mut nodes: Vec<Node>) -> String {
+        clean_nodes(&mut nodes);
+        serde_json::to_string(&nodes).unwrap()
+    }
+
+    #[test]
+    fn into_content() {
+        let nodes = vec![NodeElement::new("p").child("a").into_node(), "b".into()];
+        let json = r#"[{"tag":"p","children":["a"]},"b"]"#;
+        assert_eq!(nodes_to_content_string(&nodes), json);
+        assert_eq!(nodes_to_content_string(&[]), "[]");
+        assert_eq!(nodes[..].into_content(), json);
+        assert_eq!((&nodes).into_content(), json);
+        assert_eq!(nodes.into_content(), json);
+        assert_eq!(json.into_content(), json);
+        assert_eq!(json.to_owned().into_content(), json);
+    }
+
+    #[test]
+    fn remove_empty_elements() {
+        let nodes = vec![
+            NodeElement::new("p").into_node(),
+            NodeElement::new("p").child("\u{a0} ").into_node(),
+            NodeElement::new("p")
+                .child(NodeElement::new("b"))
+                .child(" ")
+                .into_node(),
+            NodeElement::new("p")
+                .child("a")
+                .child(" ")
+                .child(NodeElement::new("b").child("b"))
+                .into_node(),
+        ];
+        assert_eq!(
+            cleaned(nodes),
+            r#"[{"tag":"p","children":["a"," ",{"tag":"b","children":["b"]}]}]"#
+        );
+    }
+
+    #[test]
+    fn keep_hr_and_figcaption() {
+        let nodes = vec![
+            NodeElement::new("hr").into_node(),
+            NodeElement::new("figure")
+                .child(NodeElement::new("img"))
+                .child(NodeElement::new("figcaption"))
+                .into_node(),
+            NodeElement::new("p").child("end").into_node(),
+        ];
+        assert_eq!(
+            cleaned(nodes),
+            r#"[{"tag":"hr"},{"tag":"figure","children":[{"tag":"img"},{"tag":"figcaption"}]},{"tag":"p","children":["end"]}]"#
+        );
+    }
+
+    #[test]
+    fn collapse_breaks() {
+        let nodes = vec![NodeElement::new("p")
+            .child("a")
+            .child(NodeElement::new("br"))
+            .child(NodeElement::new("br"))
+            .child(NodeElement::new("br"))
+            .child(NodeElement::new("br"))
+            .child("b")
+            .child(NodeElement::new("br"))
+            .child("c")
+            .into_node()];
+        assert_eq!(
+            cleaned(nodes),
+            r#"[{"tag":"p","children":["a",{"tag":"br"},{"tag":"br"},"b",{"tag":"br"},"c"]}]"#
+        );
+    }
+
+    #[test]
+    fn trim_trailing_breaks() {
+        let nodes = vec![
+            NodeElement::new("p")
+                .child("a")
+                .child(NodeElement::new("br"))
+                .into_node(),
+            NodeElement::new("p")
+                .child("b")
+                .child(NodeElement::new("br"))
+                .child(NodeElement::new("br"))
+                .into_node(),
+            NodeElement::new("p")
+                .child(NodeElement::new("br"))
+                .into_node(),
+            NodeElement::new("br").into_node(),
+            " ".into(),
+        ];
+        assert_eq!(
+            cleaned(nodes),
+            r#"[{"tag":"p","children":["a",{"tag":"br"}]},{"tag":"p","children":["b"]}]"#
+        );
+    }
+
+    #[test]
+    fn fragment_links() {
+        let link = |href: &str| NodeElement::new("a").with_href(href).child("link");
+        let mut nodes = vec![
+            NodeElement::new("p")
+                .child(link("#getting-started"))
+                .child(link("#Whats-new"))
+                .child(link("#other"))
+                .child(link("https://example.com/#getting-started"))
+                .into_node(),
+            NodeElement::new("h3")
+                .child("Getting ")
+                .child(NodeElement::new("em").child("Started"))
+                .into_node(),
+            NodeElement::new("h4").child("What's new?").into_node(),
+        ];
+        fix_fragment_links(&mut nodes);
+        let json = serde_json::to_value(&nodes).unwrap();
+        let hrefs = json[0]["children"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["attrs"]["href"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hrefs,
+            [
+                "#Getting-Started",
+                "#What's-new?",
+                "#other",
+                "https://example.com/#getting-started"
+            ]
+        );
+    }
+
+    #[test]
+    fn table_of_contents() {
+        let mut nodes = vec![
+            NodeElement::new("p").child("Lead").into_node(),
+            NodeElement::new("h4").child("Before").into_node(),
+            NodeElement::new("h3")
+                .child("Getting ")
+                .child(NodeElement::new("em").child("started"))
+                .into_node(),
+            NodeElement::new("h4")
+                .child(NodeElement::new("code").child("cargo"))
+                .child(" add")
+                .into_node(),
+            NodeElement::new("p").child("...").into_node(),
+            NodeElement::new("h3").child("Usage").into_node(),
+            NodeElement::new("h3").child("Usage").into_node(),
+            NodeElement::new("h4").child(" ").into_node(),
+        ];
+        insert_toc(&mut nodes);
+        let link = |href: &str, text: &str| serde_json::json!({"tag": "a", "attrs": {"href": href}, "children": [text]});
+        assert_eq!(
+            serde_json::to_value(&nodes[..2]).unwrap(),
+            serde_json::json!([
+                {"tag": "p", "children": ["Lead"]},
+                {"tag": "ul", "children": [
+                    {"tag": "li", "children": [link("#Before", "Before")]},
+                    {"tag": "li", "children": [
+                        link("#Getting-started", "Getting started"),
+                        {"tag": "ul", "children": [
+                            {"tag": "li", "children": [link("#cargo-add", "cargo add")]},
+                        ]},
+                    ]},
+                    {"tag": "li", "children": [link("#Usage", "Usage")]},
+                    {"tag": "li", "children": [link("#Usage", "Usage")]},
+                ]},
+            ])
+        );
+        assert!(generate_toc(&nodes[..1]).is_empty());
+    }
+
+    #[test]
+    fn anchor_slugs() {
+        let examples = [
+            ("Getting started", "Getting-started"),
+            ("What's new in v0.6?", "What's-new-in-v0.6?"),
+            ("C++ & Rust: 100% safe", "C++-&-Rust:-100%-safe"),
+            ("Что такое Telegraph", "Что-такое-Telegraph"),
+            ("日本語 の 見出し", "日本語-の-見出し"),
+            ("🚀 Launch 🎉", "🚀-Launch-🎉"),
+            ("  leading and trailing  ", "leading-and-trailing"),
+            ("tabs\tand\nnew lines", "tabs-and-new-lines"),
+            ("non\u{a0}breaking", "non-breaking"),
+            ("already-dashed - text", "already-dashed---text"),
+            (" ", ""),
+        ];
+        for (text, slug) in examples {
+            assert_eq!(anchor_slug(text), slug, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn walk_order() {
+        let nodes = vec![
+            NodeElement::new("p")
+                .child("a")
+                .child(NodeElement::new("b").child("b"))
+                .child("c")
+                .into_node(),
+            NodeElement::new("hr").into_node(),
+            NodeElement::new("ul")
+                .child(NodeElement::new("li").child(NodeElement::new("i").child("d")))
+                .into_node(),
+        ];
+        let visited = walk(&nodes)
+            .map(|(node, path)| {
+                let name = match node {
+                    Node::Text(text) => text.clone(),
+                    Node::NodeElement(element) => element.tag.clone(),
+                };
+                assert_eq!(node_at(&nodes, &path), Some(node));
+                (name, path)
+            })
+            .collect::<Vec<_>>();
+        let expected = [
+            ("p", vec![0]),
+            ("a", vec![0, 0]),
+            ("b", vec![0, 1]),
+            ("b", vec![0, 1, 0]),
+            ("c", vec![0, 2]),
+            ("hr", vec![1]),
+            ("ul", vec![2]),
+            ("li", vec![2, 0]),
+            ("i", vec![2, 0, 0]),
+            ("d", vec![2, 0, 0, 0]),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(name, path)| (name.to_string(), path.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(visited, expected);
+        assert_eq!(walk(&[]).count(), 0);
+    }
+
+    #[test]
+    fn visit_skip_children() {
+        let mut nodes = vec![
+            NodeElement::new("p")
+                .child("a")
+                .child(NodeElement::new("code").child("b"))
+                .into_node(),
+            NodeElement::new("pre").child("c").into_node(),
+            "d".into(),
+        ];
+        let mut paths = vec![];
+        visit_mut(&mut nodes, |node, path| {
+            paths.push(path.to_vec());
+            match node {
+                Node::NodeElement(element) if element.tag == "code" || element.tag == "pre" => {
+                    Visit::SkipChildren
+                }
+                Node::NodeElement(_) => Visit::Children,
+                Node::Text(text) => {
+                    text.push('!');
+                    Visit::Children
+                }
+            }
+        });
+        assert_eq!(paths, [vec![0], vec![0, 0], vec![0, 1], vec![1], vec![2]]);
+        assert_eq!(
+            serde_json::to_string(&nodes).unwrap(),
+            r#"[{"tag":"p","children":["a!",{"tag":"code","children":["b"]}]},{"tag":"pre","children":["c"]},"d!"]"#
+        );
+    }
+
+    #[test]
+    fn edit_root() {
+        let mut nodes = vec![];
+        insert_at(&mut nodes, &[0], "b".into()).unwrap();
+        insert_at(&mut nodes, &[0], "a".into()).unwrap();
+        insert_at(&mut nodes, &[2], "c".into()).unwrap();
+        assert_eq!(nodes, ["a", "b", "c"].map(Node::from));
+        assert_eq!(remove_at(&mut nodes, &[1]).unwrap(), "b".into());
+        assert_eq!(
+            replace_at(&mut nodes, &[1], "d".into()).unwrap(),
+            "c".into()
+        );
+        assert_eq!(nodes, ["a", "d"].map(Node::from));
+    }
+
+    #[test]
+    fn edit_nested() {
+        let mut nodes = vec![NodeElement::new("ul")
+            .child(NodeElement::new("li").child("one"))
+            .into_node()];
+        insert_at(&mut nodes, &[0, 1], NodeElement::new("li").into_node()).unwrap();
+        insert_at(&mut nodes, &[0, 1, 0], "two".into()).unwrap();
+        let mut li = NodeElement::new("li");
+        li.push_child("three");
+        insert_at(&mut nodes, &[0, 2], li.into()).unwrap();
+        assert_eq!(
+            replace_at(
+                &mut nodes,
+                &[0, 0, 0],
+                NodeElement::new("b").child("1").into_node()
+            )
+            .unwrap(),
+            "one".into()
+        );
+        assert_eq!(
+            serde_json::to_string(&nodes).unwrap(),
+            r#"[{"tag":"ul","children":[{"tag":"li","children":[{"tag":"b","children":["1"]}]},{"tag":"li","children":["two"]},{"tag":"li","children":["three"]}]}]"#
+        );
+        assert_eq!(remove_at(&mut nodes, &[0, 1, 0]).unwrap(), "two".into());
+        assert_eq!(
+            node_at(&nodes, &[0, 1]),
+            Some(&NodeElement::new("li").into_node())
+        );
+    }
+
+    #[test]
+    fn edit_invalid_paths() {
+        let mut nodes = vec![
+            NodeElement::new("p").child("a").into_node(),
+            NodeElement::new("hr").into_node(),
+        ];
+        let original = nodes.clone();
+        let invalid = |result: Result<()>| match result {
+            Err(Error::InvalidPath(path)) => path,
+            result => panic!("{:?}", result),
+        };
+        assert!(invalid(insert_at(&mut nodes, &[], "x".into())).is_empty());
+        assert_eq!(invalid(insert_at(&mut nodes, &[3], "x".into())), [3]);
+        assert_eq!(invalid(insert_at(&mut nodes, &[0, 2], "x".into())), [0, 2]);
+        assert_eq!(invalid(insert_at(&mut nodes, &[1, 1], "x".into())), [1, 1]);
+        assert_eq!(
+            invalid(insert_at(&mut nodes, &[0, 0, 0], "x".into())),
+            [0, 0, 0]
+        );
+        assert_eq!(invalid(remove_at(&mut nodes, &[2]).map(drop)), [2]);
+        assert_eq!(invalid(remove_at(&mut nodes, &[1, 0]).map(drop)), [1, 0]);
+        assert_eq!(
+            invalid(replace_at(&mut nodes, &[0, 1], "x".into()).map(drop)),
+            [0, 1]
+        );
+        assert_eq!(nodes, original);
+        assert_eq!(
+            remove_at(&mut nodes, &[5, 1]).unwrap_err().to_string(),
+            "no node at path [5, 1]"
+        );
+    }
+
+    #[test]
+    fn links() {
+        let mut nodes: Vec<Node> = serde_json::from_value(serde_json::json!([
+            {"tag": "ul", "children": [
+                {"tag": "li", "children": [{"tag": "a", "attrs": {"href": "https://example.com/a"}, "children": ["a"]}]},
+                {"tag": "li", "children": [{"tag": "a", "attrs": {"href": null}, "children": ["no href"]}]},
+                {"tag": "li", "children": [{"tag": "a", "children": ["no attrs"]}]},
+            ]},
+            {"tag": "figure", "children": [
+                {"tag": "img", "attrs": {"src": "/file/a.jpg"}},
+                {"tag": "figcaption", "children": [
+                    {"tag": "a", "attrs": {"href": "https://other.org/"}, "children": ["source"]},
+                ]},
+            ]},
+        ]))
+        .unwrap();
+        assert_eq!(
+            collect_links(&nodes),
+            ["https://example.com/a", "/file/a.jpg", "https://other.org/"]
+        );
+
+        let mut seen = vec![];
+        rewrite_links(&mut nodes, |url| {
+            seen.push(url.to_owned());
+            url.starts_with("https://")
+                .then(|| format!("{}?utm_source=telegraph", url))
+        });
+        assert_eq!(seen.len(), 3);
+        assert_eq!(
+            collect_links(&nodes),
+            [
+                "https://example.com/a?utm_source=telegraph",
+                "/file/a.jpg",
+                "https://other.org/?utm_source=telegraph"
+            ]
+        );
+    }
+
+    #[test]
+    fn set_cover() {
+        let text = || Node::text("Intro");
+        let figure = |src: &str| Node::figure(src, None);
+        let nested = || {
+            NodeElement::new("p")
+                .child(NodeElement::img("/file/inline.png"))
+                .into_node()
+        };
+        let video = || Node::video_figure("/file/clip.mp4", None);
+        let cases = [
+            // an existing figure is moved up
+            (
+                vec![text(), figure("/file/a.jpg"), figure("/file/b.jpg")],
+                vec![text(), figure("/file/b.jpg"), figure("/file/a.jpg")],
+            ),
+            // already the cover
+            (
+                vec![text(), figure("/file/b.jpg"), figure("/file/a.jpg")],
+                vec![text(), figure("/file/b.jpg"), figure("/file/a.jpg")],
+            ),
+            // nested images count, videos don't
+            (
+                vec![video(), text(), nested(), figure("/file/b.jpg")],
+                vec![video(), text(), figure("/file/b.jpg"), nested()],
+            ),
+            (
+                vec![video(), text(), nested()],
+                vec![video(), text(), figure("/file/b.jpg"), nested()],
+            ),
+            (vec![text()], vec![figure("/file/b.jpg"), text()]),
+            (vec![], vec![figure("/file/b.jpg")]),
+            // a bare img is moved as is
+            (
+                vec![
+                    figure("/file/a.jpg"),
+                    NodeElement::img("/file/b.jpg").into(),
+                ],
+                vec![
+                    NodeElement::img("/file/b.jpg").into(),
+                    figure("/file/a.jpg"),
+                ],
+            ),
+        ];
+        for (mut nodes, expected) in cases {
+            set_cover_image(&mut nodes, "/file/b.jpg");
+            assert_eq!(nodes, expected);
+            assert_eq!(cover_image(&nodes), Some("/file/b.jpg"));
+        }
+    }
+
+    #[tokio::test]
+    async fn cover_image_url() {
+        let page = crate::tests::page_response("Hi-10-16", "Hi");
+        let (addr, requests) =
+            crate::tests::mock_server(vec![("POST /createPage", "application/json", page)]).await;
+        let telegraph = client(&addr).await;
+
+        let mut nodes = vec![
+            Node::text("Intro"),
+            Node::figure("/file/a.jpg", None),
+            Node::figure("/file/b.jpg", None),
+        ];
+        set_cover_image(&mut nodes, "/file/b.jpg");
+        telegraph
+            .create_page_nodes("Hi", &nodes, false)
+            .await
+            .unwrap();
+
+        let request = requests.lock().unwrap().remove(0);
+        let content = crate::tests::form_field(&request, "content").unwrap();
+        let sent: Vec<Node> = serde_json::from_str(&content).unwrap();
+        let cover = Node::figure("/file/b.jpg", None);
+        let first = sent
+            .iter()
+            .find(|node| cover_image(std::slice::from_ref(node)).is_some());
+        assert_eq!(first, Some(&cover));
+        assert_eq!(sent.iter().filter(|node| **node == cover).count(), 1);
+        assert_eq!(sent.len(), 3);
+    }
+}
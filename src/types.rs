@@ -1,35 +1,43 @@
-use super::{error::Error, utils::*};
+use super::{error::Error, tag::Tag, utils::*};
 use reqwest::multipart::Part;
-use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::Path};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::HashMap, iter::FromIterator, path::Path};
 
 /// This object represents a Telegraph account.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Account {
     /// Account name, helps users with several accounts remember which they are currently using.
     ///
     /// Displayed to the user above the "Edit/Publish" button on Telegra.ph, other users don't see this name.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub short_name: Option<String>,
     /// Default author name used when creating new articles.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author_name: Option<String>,
     /// Profile link, opened when users click on the author's name below the title.
     ///
     /// Can be any link, not necessarily to a Telegram profile or channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author_url: Option<String>,
     /// Optional. Only returned by the createAccount and revokeAccessToken method.
     ///
     /// Access token of the Telegraph account.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub access_token: Option<String>,
     /// Optional. URL to authorize a browser on telegra.ph and connect it to a Telegraph account.
     ///
     /// This URL is valid for only one use and for 5 minutes only.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub auth_url: Option<String>,
     /// Optional. Number of pages belonging to the Telegraph account.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub page_count: Option<i32>,
 }
 
 /// This object represents a list of Telegraph articles belonging to an account. Most recently created articles first.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PageList {
     /// Total number of pages belonging to the target Telegraph account.
     pub total_count: i32,
@@ -38,7 +46,8 @@ pub struct PageList {
 }
 
 /// This object represents a page on Telegraph.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Page {
     /// Path to the page.
     pub path: String,
@@ -49,25 +58,120 @@ pub struct Page {
     /// Description of the page.
     pub description: String,
     /// Optional. Name of the author, displayed below the title.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author_name: Option<String>,
     /// Optional. Profile link, opened when users click on the author's name below the title.
     ///
     /// Can be any link, not necessarily to a Telegram profile or channel.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author_url: Option<String>,
     /// Optional. Image URL of the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub image_url: Option<String>,
     /// Optional. Content of the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<Vec<Node>>,
     /// Number of page views for the page.
     pub views: i32,
     /// Optional. Only returned if access_token passed.
     ///
     /// True, if the target Telegraph account can edit the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub can_edit: Option<bool>,
 }
 
+impl Page {
+    /// Plain text of the page content, see [`Page::content_text`]
+    pub fn text(&self) -> Option<String> {
+        self.content_text()
+    }
+
+    /// Plain text of the page content, see [`node_to_text`](crate::node_to_text)
+    ///
+    /// `None` if the page was returned without content.
+    pub fn content_text(&self) -> Option<String> {
+        self.content.as_deref().map(crate::node_to_text)
+    }
+
+    /// HTML of the page content, see [`node_to_html`](crate::node_to_html)
+    ///
+    /// Text and attribute values are escaped, so the HTML can be embedded in other pages.
+    /// `None` if the page was returned without content.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), telegraph_rs::Error> {
+    /// use telegraph_rs::Telegraph;
+    ///
+    /// let page = Telegraph::get_page("Sample-Page-12-15", true).await?;
+    /// println!("<article>{}</article>", page.content_html().unwrap_or_default());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn content_html(&self) -> Option<String> {
+        self.content.as_deref().map(crate::node_to_html)
+    }
+
+    /// Month and day the page was created, parsed from its path like `Sample-Page-12-15`
+    ///
+    /// `None` if the path doesn't end with a date.
+    pub fn created_date(&self) -> Option<(u32, u32)> {
+        parse_page_path(&self.path).map(|(_, date, _)| date)
+    }
+
+    /// Title part of the path, like `Sample-Page` for `Sample-Page-12-15-2`
+    ///
+    /// The slug is returned as it is in the path, still percent-encoded if the path is.
+    /// The whole path if it doesn't end with a date.
+    pub fn slug(&self) -> &str {
+        parse_page_path(&self.path).map_or(&self.path, |(slug, _, _)| slug)
+    }
+
+    /// Suffix added by Telegraph to the path of a page created on the same day
+    /// with the same title as another one, like `2` for `Sample-Page-12-15-2`
+    pub fn collision_index(&self) -> Option<u32> {
+        parse_page_path(&self.path).and_then(|(_, _, index)| index)
+    }
+}
+
+/// Split a page path like `Sample-Page-12-15-2` into its slug, month and day, and collision index
+///
+/// A path like `Page-01-02-10` could also be the page `Page-01` created on the 10th of February,
+/// it's read as a collision since titles rarely end with numbers looking like a month.
+fn parse_page_path(path: &str) -> Option<(&str, (u32, u32), Option<u32>)> {
+    fn date(month: &str, day: &str) -> Option<(u32, u32)> {
+        const DAYS: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+        if month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+        let month = month.parse::<u32>().ok().filter(|m| (1..=12).contains(m))?;
+        let day = day.parse::<u32>().ok()?;
+        (1..=DAYS[month as usize - 1])
+            .contains(&day)
+            .then_some((month, day))
+    }
+
+    let parts = path.rsplitn(4, '-').collect::<Vec<_>>();
+    if let [index, day, month, slug] = parts[..] {
+        let index = Some(index)
+            .filter(|index| !index.starts_with('0') && index.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|index| index.parse::<u32>().ok())
+            .filter(|&index| index >= 2);
+        if let (Some(index), Some(date)) = (index, date(month, day)) {
+            if !slug.is_empty() {
+                return Some((slug, date, Some(index)));
+            }
+        }
+    }
+    let parts = path.rsplitn(3, '-').collect::<Vec<_>>();
+    match parts[..] {
+        [day, month, slug] if !slug.is_empty() => Some((slug, date(month, day)?, None)),
+        _ => None,
+    }
+}
+
 /// This object represents the number of page views for a Telegraph article.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PageViews {
     /// Number of page views for the target page.
     pub views: i32,
@@ -76,31 +180,629 @@ pub struct PageViews {
 /// This abstract object represents a DOM Node.
 ///
 /// It can be a String which represents a DOM text node or a NodeElement object.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Node {
     Text(String),
     NodeElement(NodeElement),
 }
 
+impl Node {
+    /// A text node
+    ///
+    /// Text and elements can be mixed in one `Vec` with `into`:
+    ///
+    /// ```
+    /// use telegraph_rs::{Node, NodeElement};
+    ///
+    /// let children: Vec<Node> = vec![
+    ///     Node::text("Hello, "),
+    ///     NodeElement::img("/file/wave.png").into(),
+    ///     "world".into(),
+    ///     String::from("!").into(),
+    /// ];
+    /// let json = serde_json::to_string(&children).unwrap();
+    /// assert_eq!(json, r#"["Hello, ",{"tag":"img","attrs":{"src":"/file/wave.png"}},"world","!"]"#);
+    /// ```
+    pub fn text(text: impl Into<String>) -> Self {
+        Node::Text(text.into())
+    }
+
+    /// A `blockquote` of `children`
+    pub fn blockquote<I>(children: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Node>,
+    {
+        NodeElement::new("blockquote")
+            .children(children)
+            .into_node()
+    }
+
+    /// Inline `code`, for spans inside paragraphs
+    pub fn code(text: impl Into<String>) -> Self {
+        NodeElement::new("code").child(Node::text(text)).into_node()
+    }
+
+    /// A code block, a `pre` wrapping a `code` element whose only child is `text`
+    ///
+    /// `text` is kept as is, newlines included, it is not parsed as HTML.
+    ///
+    /// ```
+    /// use telegraph_rs::Node;
+    ///
+    /// let json = serde_json::to_string(&Node::code_block("fn main() {\n    println!(\"<hi>\");\n}")).unwrap();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"tag":"pre","children":[{"tag":"code","children":["fn main() {\n    println!(\"<hi>\");\n}"]}]}"#
+    /// );
+    /// ```
+    pub fn code_block(text: impl Into<String>) -> Self {
+        NodeElement::new("pre").child(Self::code(text)).into_node()
+    }
+
+    /// A `video` playing `src`, usually an uploaded mp4 like `/file/clip.mp4`
+    pub fn video(src: &str) -> Self {
+        NodeElement::new("video").with_src(src).into_node()
+    }
+
+    /// A video playing `src` in a `figure`, with a `figcaption` holding `caption` if any
+    ///
+    /// This is the layout of videos uploaded in the Telegraph editor.
+    ///
+    /// ```
+    /// use telegraph_rs::Node;
+    ///
+    /// let json = serde_json::to_string(&Node::video_figure("/file/clip.mp4", Some("A clip"))).unwrap();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"tag":"figure","children":[{"tag":"video","attrs":{"src":"/file/clip.mp4"}},{"tag":"figcaption","children":["A clip"]}]}"#
+    /// );
+    /// ```
+    pub fn video_figure(src: &str, caption: Option<&str>) -> Self {
+        Self::media_figure(Self::video(src), caption.map(Node::text))
+    }
+
+    /// An `iframe` showing `url` with the Telegraph embed of `service`, in a `figure`
+    ///
+    /// `url` is percent-encoded into the `src`, so query strings like the one of YouTube links survive.
+    ///
+    /// ```
+    /// use telegraph_rs::Node;
+    ///
+    /// let json = serde_json::to_string(&Node::embed("youtube", "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42")).unwrap();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"tag":"figure","children":[{"tag":"iframe","attrs":{"src":"/embed/youtube?url=https%3A%2F%2Fwww.youtube.com%2Fwatch%3Fv%3DdQw4w9WgXcQ%26t%3D42"}}]}"#
+    /// );
+    /// ```
+    pub fn embed(service: &str, url: &str) -> Self {
+        Self::media_figure(Self::iframe(service, url), None::<Node>)
+    }
+
+    /// Like [`Node::embed`], with a `figcaption` holding `caption`
+    pub fn embed_captioned(service: &str, url: &str, caption: &str) -> Self {
+        Self::media_figure(Self::iframe(service, url), Some(caption))
+    }
+
+    /// A YouTube video, see [`Node::embed`]
+    pub fn youtube(url: &str) -> Self {
+        Self::embed("youtube", url)
+    }
+
+    /// A Vimeo video, see [`Node::embed`]
+    pub fn vimeo(url: &str) -> Self {
+        Self::embed("vimeo", url)
+    }
+
+    /// A tweet, see [`Node::embed`]
+    pub fn twitter(url: &str) -> Self {
+        Self::embed("twitter", url)
+    }
+
+    fn iframe(service: &str, url: &str) -> Self {
+        NodeElement::new("iframe")
+            .with_src(&embed_src(service, url))
+            .into_node()
+    }
+
+    /// An image showing `src` in a `figure`, with a `figcaption` holding `caption` if any
+    ///
+    /// ```
+    /// use telegraph_rs::Node;
+    ///
+    /// let json = serde_json::to_string(&Node::figure("/file/cat.jpg", Some("A cat"))).unwrap();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/cat.jpg"}},{"tag":"figcaption","children":["A cat"]}]}"#
+    /// );
+    /// ```
+    pub fn figure(src: &str, caption: Option<&str>) -> Self {
+        Self::figure_with(src, caption.map(Node::text))
+    }
+
+    /// An image showing `src` in a `figure`, with a `figcaption` holding the formatted `caption`
+    ///
+    /// The `figcaption` is left out if `caption` is empty.
+    ///
+    /// ```
+    /// use telegraph_rs::{Node, NodeElement};
+    ///
+    /// let caption = vec![Node::text("By "), NodeElement::new("a").attr("href", "https://example.com/").child("someone").into()];
+    /// let figure = Node::figure_with("/file/cat.jpg", caption);
+    /// ```
+    pub fn figure_with<I>(src: &str, caption: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Node>,
+    {
+        Self::media_figure(NodeElement::img(src).into_node(), caption)
+    }
+
+    fn media_figure<I>(media: Node, caption: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Node>,
+    {
+        let caption = NodeElement::new("figcaption").children(caption);
+        NodeElement::new("figure")
+            .child(media)
+            .children(caption.children.is_some().then_some(caption))
+            .into_node()
+    }
+}
+
+impl From<&str> for Node {
+    fn from(text: &str) -> Self {
+        Node::Text(text.to_owned())
+    }
+}
+
+impl From<String> for Node {
+    fn from(text: String) -> Self {
+        Node::Text(text)
+    }
+}
+
+impl From<NodeElement> for Node {
+    fn from(element: NodeElement) -> Self {
+        Node::NodeElement(element)
+    }
+}
+
+/// Tags accepted by Telegraph.
+pub const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "aside",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "em",
+    "figcaption",
+    "figure",
+    "h3",
+    "h4",
+    "hr",
+    "i",
+    "iframe",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "s",
+    "strong",
+    "u",
+    "ul",
+    "video",
+];
+
 /// This object represents a DOM element node.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+///
+/// `None` and empty `attrs` and `children` are both left out when serialized,
+/// so every way of building an element produces the same JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NodeElement {
     /// Name of the DOM element.
     /// Available tags: a, aside, b, blockquote, br, code, em, figcaption, figure, h3, h4, hr, i, iframe, img, li, ol, p, pre, s, strong, u, ul, video.
     pub tag: String,
     /// Optional. Attributes of the DOM element.
     ///
-    /// Key of object represents name of attribute, value represents value of attribute.
-    ///
     /// Available attributes: href, src.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub attrs: Option<HashMap<String, Option<String>>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_attrs",
+        skip_serializing_if = "attrs_are_empty"
+    )]
+    pub attrs: Option<NodeAttrs>,
     /// Optional. List of child nodes for the DOM element.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "is_none_or_empty")]
     pub children: Option<Vec<Node>>,
 }
 
+impl NodeElement {
+    /// An element without attributes and children, the start of building one
+    ///
+    /// This is the recommended way to build content by hand:
+    ///
+    /// ```
+    /// use telegraph_rs::{Node, NodeElement};
+    ///
+    /// let paragraph = NodeElement::new("p")
+    ///     .child("Read ")
+    ///     .child(NodeElement::new("a").attr("href", "https://telegra.ph/").child("the docs"))
+    ///     .children(vec![Node::text(" or "), NodeElement::new("b").child("not").into()])
+    ///     .into_node();
+    /// let json = serde_json::to_string(&paragraph).unwrap();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"tag":"p","children":["Read ",{"tag":"a","attrs":{"href":"https://telegra.ph/"},"children":["the docs"]}," or ",{"tag":"b","children":["not"]}]}"#
+    /// );
+    /// ```
+    pub fn new(tag: &str) -> Self {
+        NodeElement {
+            tag: tag.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the attribute `name` to `value`
+    pub fn attr(mut self, name: &str, value: &str) -> Self {
+        let attrs = self.attrs.get_or_insert_with(Default::default);
+        attrs.insert(name.to_owned(), Some(value.to_owned()));
+        self
+    }
+
+    /// Append a child
+    pub fn child(mut self, child: impl Into<Node>) -> Self {
+        self.children
+            .get_or_insert_with(Vec::new)
+            .push(child.into());
+        self
+    }
+
+    /// Append children, `children` stays `None` if there are none
+    pub fn children<I>(mut self, children: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Node>,
+    {
+        let mut children = children.into_iter().map(Into::into).peekable();
+        if children.peek().is_some() {
+            self.children.get_or_insert_with(Vec::new).extend(children);
+        }
+        self
+    }
+
+    /// Append a child to an existing element, like [`NodeElement::child`]
+    pub fn push_child(&mut self, child: impl Into<Node>) {
+        self.children
+            .get_or_insert_with(Vec::new)
+            .push(child.into());
+    }
+
+    /// Finish building, wrapping the element into a [`Node`]
+    pub fn into_node(self) -> Node {
+        Node::NodeElement(self)
+    }
+
+    /// A `br` element
+    pub fn br() -> Self {
+        Self::void(Tag::Br, NodeAttrs::default())
+    }
+
+    /// A `hr` element
+    pub fn hr() -> Self {
+        Self::void(Tag::Hr, NodeAttrs::default())
+    }
+
+    /// An `img` element showing `src`
+    ///
+    /// ```
+    /// use telegraph_rs::NodeElement;
+    ///
+    /// let json = serde_json::to_string(&NodeElement::img("/file/cat.jpg")).unwrap();
+    /// assert_eq!(json, r#"{"tag":"img","attrs":{"src":"/file/cat.jpg"}}"#);
+    /// ```
+    pub fn img(src: &str) -> Self {
+        Self::void(Tag::Img, NodeAttrs::default()).with_src(src)
+    }
+
+    /// Set the `href` attribute
+    ///
+    /// ```
+    /// use telegraph_rs::NodeElement;
+    ///
+    /// let link = NodeElement::new("a").with_href("https://telegra.ph/");
+    /// assert_eq!(link.attrs.unwrap().href.as_deref(), Some("https://telegra.ph/"));
+    /// ```
+    pub fn with_href(mut self, href: &str) -> Self {
+        self.attrs.get_or_insert_with(Default::default).href = Some(href.to_owned());
+        self
+    }
+
+    /// Set the `src` attribute
+    pub fn with_src(mut self, src: &str) -> Self {
+        self.attrs.get_or_insert_with(Default::default).src = Some(src.to_owned());
+        self
+    }
+
+    fn void(tag: Tag, attrs: NodeAttrs) -> Self {
+        NodeElement {
+            tag: tag.to_string(),
+            attrs: Some(attrs).filter(|attrs| !attrs.is_empty()),
+            children: None,
+        }
+    }
+}
+
+/// Attributes of a [`NodeElement`]
+///
+/// Serialized as a JSON object, like `{"href": "https://telegra.ph/"}`.
+/// Numbers, booleans and arrays written by other tools are read as strings.
+///
+/// More fields may be added, so it's built with [`NodeAttrs::from_href`], [`NodeAttrs::from_src`],
+/// [`Default`] or from a map instead of a struct literal.
+///
+/// ```
+/// use telegraph_rs::NodeAttrs;
+///
+/// let mut attrs = NodeAttrs::from_href("https://telegra.ph/");
+/// attrs.insert("target".to_owned(), Some("_blank".to_owned()));
+/// assert_eq!(
+///     serde_json::to_string(&attrs).unwrap(),
+///     r#"{"href":"https://telegra.ph/","target":"_blank"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NodeAttrs {
+    /// Link target of an `a` element
+    pub href: Option<String>,
+    /// Source of an `img`, `iframe` or `video` element
+    pub src: Option<String>,
+    /// Other attributes, which Telegraph doesn't accept, only serialized if there are any
+    pub extra: HashMap<String, Option<String>>,
+}
+
+impl NodeAttrs {
+    /// Attributes with only `href`
+    pub fn from_href(href: impl Into<String>) -> Self {
+        Self {
+            href: Some(href.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Attributes with only `src`
+    pub fn from_src(src: impl Into<String>) -> Self {
+        Self {
+            src: Some(src.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Whether there are no attributes
+    pub fn is_empty(&self) -> bool {
+        self.href.is_none() && self.src.is_none() && self.extra.is_empty()
+    }
+
+    /// Value of the attribute `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "href" => self.href.as_deref(),
+            "src" => self.src.as_deref(),
+            _ => self.extra.get(name)?.as_deref(),
+        }
+    }
+
+    /// Set the attribute `name`
+    pub fn insert(&mut self, name: String, value: Option<String>) {
+        match &*name {
+            "href" => self.href = value,
+            "src" => self.src = value,
+            _ => {
+                self.extra.insert(name, value);
+            }
+        }
+    }
+
+    /// Names and values of the attributes, `href` and `src` first
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        let known = IntoIterator::into_iter([("href", &self.href), ("src", &self.src)])
+            .filter(|(_, value)| value.is_some());
+        let extra = self.extra.iter().map(|(name, value)| (&**name, value));
+        known
+            .chain(extra)
+            .map(|(name, value)| (name, value.as_deref()))
+    }
+}
+
+impl From<HashMap<String, Option<String>>> for NodeAttrs {
+    fn from(map: HashMap<String, Option<String>>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl From<NodeAttrs> for HashMap<String, Option<String>> {
+    fn from(attrs: NodeAttrs) -> Self {
+        let mut map = attrs.extra;
+        map.extend(attrs.href.map(|href| ("href".to_owned(), Some(href))));
+        map.extend(attrs.src.map(|src| ("src".to_owned(), Some(src))));
+        map
+    }
+}
+
+impl FromIterator<(String, Option<String>)> for NodeAttrs {
+    fn from_iter<I: IntoIterator<Item = (String, Option<String>)>>(iter: I) -> Self {
+        let mut attrs = Self::default();
+        for (name, value) in iter {
+            attrs.insert(name, value);
+        }
+        attrs
+    }
+}
+
+impl Serialize for NodeAttrs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+/// Also accepts non-string values and `[]` for no attributes
+impl<'de> Deserialize<'de> for NodeAttrs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected};
+        use serde_json::Value;
+
+        fn to_string(value: Value) -> Option<String> {
+            match value {
+                Value::Null => None,
+                Value::String(s) => Some(s),
+                Value::Array(values) => Some(
+                    values
+                        .into_iter()
+                        .filter_map(to_string)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+                value => Some(value.to_string()),
+            }
+        }
+
+        match Value::deserialize(deserializer)? {
+            Value::Array(values) if values.is_empty() => Ok(Self::default()),
+            Value::Object(attrs) => Ok(attrs
+                .into_iter()
+                .map(|(name, value)| (name, to_string(value)))
+                .collect()),
+            Value::Array(_) => Err(D::Error::invalid_type(Unexpected::Seq, &"a map")),
+            _ => Err(D::Error::invalid_type(Unexpected::Other("value"), &"a map")),
+        }
+    }
+}
+
+/// Deserialize attributes, treating empty ones as absent
+fn deserialize_attrs<'de, D>(deserializer: D) -> Result<Option<NodeAttrs>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let attrs = Option::<NodeAttrs>::deserialize(deserializer)?;
+    Ok(attrs.filter(|attrs| !attrs.is_empty()))
+}
+
+fn attrs_are_empty(attrs: &Option<NodeAttrs>) -> bool {
+    attrs.as_ref().is_none_or(NodeAttrs::is_empty)
+}
+
+fn is_none_or_empty<T>(value: &Option<Vec<T>>) -> bool {
+    value.as_ref().is_none_or(Vec::is_empty)
+}
+
+/// Either a string or a [`NodeElement`], which the derive can't express for an untagged enum
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Node {
+    fn schema_name() -> String {
+        "Node".to_owned()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{Metadata, SchemaObject, SubschemaValidation};
+
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some("A DOM text node or a DOM element node".to_owned()),
+                ..Default::default()
+            })),
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    gen.subschema_for::<String>(),
+                    gen.subschema_for::<NodeElement>(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// An object of string or null values, like it is serialized
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for NodeAttrs {
+    fn schema_name() -> String {
+        "NodeAttrs".to_owned()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <HashMap<String, Option<String>>>::json_schema(gen)
+    }
+}
+
+/// Maximum nesting depth of generated node trees
+#[cfg(feature = "arbitrary")]
+const ARBITRARY_MAX_DEPTH: usize = 8;
+
+/// Generates trees of [`ALLOWED_TAGS`] with `href` on links and `src` on media
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Node {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_node(u, 0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for NodeElement {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_element(u, 0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+fn arbitrary_node(u: &mut arbitrary::Unstructured<'_>, depth: usize) -> arbitrary::Result<Node> {
+    if depth >= ARBITRARY_MAX_DEPTH || u.ratio(1, 3)? {
+        Ok(Node::Text(u.arbitrary()?))
+    } else {
+        Ok(Node::NodeElement(arbitrary_element(u, depth)?))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+fn arbitrary_element(
+    u: &mut arbitrary::Unstructured<'_>,
+    depth: usize,
+) -> arbitrary::Result<NodeElement> {
+    let tag = *u.choose(ALLOWED_TAGS)?;
+    let attr = match tag {
+        "a" => Some("href"),
+        "iframe" | "img" | "video" => Some("src"),
+        _ => None,
+    };
+    let attrs = match attr {
+        Some(name) if u.arbitrary()? => {
+            Some(std::iter::once((name.to_owned(), Some(u.arbitrary()?))).collect())
+        }
+        _ => None,
+    };
+    let children = match tag {
+        "br" | "hr" | "img" => None,
+        _ => {
+            let len = u.int_in_range(0..=4)?;
+            let children = (0..len)
+                .map(|_| arbitrary_node(u, depth + 1))
+                .collect::<arbitrary::Result<Vec<_>>>()?;
+            (!children.is_empty()).then_some(children)
+        }
+    };
+    Ok(NodeElement {
+        tag: tag.to_owned(),
+        attrs,
+        children,
+    })
+}
+
 /// This object represents the upload result
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
@@ -109,12 +811,37 @@ pub enum UploadResult {
     Source(Vec<ImageInfo>),
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ImageInfo {
     /// Path of the file uploaded.
     pub src: String,
 }
 
+impl ImageInfo {
+    /// Whether the uploaded file is a video, Telegraph only accepts mp4 videos
+    pub fn is_video(&self) -> bool {
+        self.src.to_ascii_lowercase().ends_with(".mp4")
+    }
+
+    /// The uploaded file in a `figure`, see [`Node::figure`] and [`Node::video_figure`]
+    pub fn figure(&self, caption: Option<&str>) -> Node {
+        match self.is_video() {
+            true => Node::video_figure(&self.src, caption),
+            false => Node::figure(&self.src, caption),
+        }
+    }
+}
+
+/// An `img` or `video` element showing the uploaded file
+impl From<ImageInfo> for Node {
+    fn from(info: ImageInfo) -> Self {
+        match info.is_video() {
+            true => Node::video(&info.src),
+            false => NodeElement::img(&info.src).into_node(),
+        }
+    }
+}
+
 #[cfg(feature = "upload")]
 pub trait Uploadable {
     fn part(&self) -> Result<Part, Error>;
@@ -134,3 +861,533 @@ where
         Ok(part)
     }
 }
+
+/// An inline `data:` URI, e.g. `data:image/png;base64,...`
+///
+/// Only base64 encoded data is supported.
+#[cfg(feature = "upload")]
+#[derive(Debug, Clone)]
+pub struct DataUri {
+    /// Mime type from the header, `text/plain` if absent
+    pub mime: String,
+    /// Decoded data
+    pub bytes: Vec<u8>,
+}
+
+#[cfg(feature = "upload")]
+impl DataUri {
+    /// Parse a base64 encoded `data:` URI, returns `None` if it's malformed
+    pub fn parse(uri: &str) -> Option<Self> {
+        use base64::{
+            alphabet::STANDARD,
+            engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+            Engine,
+        };
+        const ENGINE: GeneralPurpose = GeneralPurpose::new(
+            &STANDARD,
+            GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+
+        let uri = uri.trim();
+        if !uri.get(..5)?.eq_ignore_ascii_case("data:") {
+            return None;
+        }
+        let (header, data) = uri[5..].split_once(',')?;
+        let mut params = header.split(';');
+        let mime = params.next().unwrap_or_default().trim();
+        if !params.any(|param| param.trim().eq_ignore_ascii_case("base64")) {
+            return None;
+        }
+        let data = data
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>();
+        let bytes = ENGINE.decode(data).ok()?;
+        Some(Self {
+            mime: if mime.is_empty() { "text/plain" } else { mime }.to_ascii_lowercase(),
+            bytes,
+        })
+    }
+}
+
+#[cfg(feature = "upload")]
+impl Uploadable for DataUri {
+    fn part(&self) -> Result<Part, Error> {
+        bytes_part(self.bytes.clone(), &self.mime)
+    }
+}
+
+/// Build an upload part from raw bytes, naming the file after the mime type
+#[cfg(feature = "upload")]
+pub(crate) fn bytes_part(bytes: Vec<u8>, mime: &str) -> Result<Part, Error> {
+    let extension = mime_guess::get_mime_extensions_str(mime)
+        .and_then(|extensions| extensions.first())
+        .unwrap_or(&"bin");
+    let part = Part::bytes(bytes)
+        .file_name(format!("file.{}", extension))
+        .mime_str(mime)?;
+    Ok(part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "arbitrary")]
+    use proptest::prelude::*;
+
+    #[test]
+    fn void_elements() {
+        let json =
+            |element: NodeElement| serde_json::to_string(&Node::NodeElement(element)).unwrap();
+        assert_eq!(json(NodeElement::br()), r#"{"tag":"br"}"#);
+        assert_eq!(json(NodeElement::hr()), r#"{"tag":"hr"}"#);
+        assert_eq!(
+            json(NodeElement::img("/file/a.jpg")),
+            r#"{"tag":"img","attrs":{"src":"/file/a.jpg"}}"#
+        );
+
+        let empty = NodeElement {
+            tag: "br".to_owned(),
+            attrs: Some(NodeAttrs::default()),
+            children: Some(vec![]),
+        };
+        assert_eq!(json(empty), r#"{"tag":"br"}"#);
+    }
+
+    #[test]
+    fn canonical_json() {
+        let json = |element: NodeElement| serde_json::to_string(&element).unwrap();
+        let p = NodeElement {
+            tag: "p".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(json(p.clone()), r#"{"tag":"p"}"#);
+        let empty = NodeElement {
+            attrs: Some(NodeAttrs::default()),
+            children: Some(vec![]),
+            ..p.clone()
+        };
+        assert_eq!(json(empty), r#"{"tag":"p"}"#);
+        let text = NodeElement {
+            attrs: Some(NodeAttrs::default()),
+            children: Some(vec!["a".into()]),
+            ..p.clone()
+        };
+        assert_eq!(json(text), r#"{"tag":"p","children":["a"]}"#);
+        let link = NodeElement {
+            tag: "a".to_owned(),
+            attrs: Some(NodeAttrs::from_iter([(
+                "href".to_owned(),
+                Some("/a".to_owned()),
+            )])),
+            children: Some(vec![]),
+        };
+        assert_eq!(json(link), r#"{"tag":"a","attrs":{"href":"/a"}}"#);
+        let nested = NodeElement {
+            children: Some(vec![NodeElement {
+                tag: "b".to_owned(),
+                attrs: Some(NodeAttrs::default()),
+                children: None,
+            }
+            .into()]),
+            ..p
+        };
+        assert_eq!(json(nested), r#"{"tag":"p","children":[{"tag":"b"}]}"#);
+        assert_eq!(NodeElement::default().tag, "");
+    }
+
+    #[test]
+    fn builder() {
+        let json = |node: Node| serde_json::to_string(&node).unwrap();
+        let bare = NodeElement::new("hr");
+        assert_eq!(bare.attrs, None);
+        assert_eq!(bare.children, None);
+        assert_eq!(json(bare.into_node()), r#"{"tag":"hr"}"#);
+
+        let empty = NodeElement::new("p").children(Vec::<Node>::new());
+        assert_eq!(empty.children, None);
+        assert_eq!(json(empty.into_node()), r#"{"tag":"p"}"#);
+
+        let link = NodeElement::new("a")
+            .attr("href", "https://telegra.ph/")
+            .attr("target", "_blank")
+            .child("link");
+        let attrs = link.attrs.as_ref().unwrap();
+        assert_eq!(attrs.href.as_deref(), Some("https://telegra.ph/"));
+        assert_eq!(attrs.get("target"), Some("_blank"));
+
+        let list = NodeElement::new("ul")
+            .children(
+                ["one", "two"]
+                    .iter()
+                    .map(|item| NodeElement::new("li").child(*item)),
+            )
+            .child(NodeElement::new("li").child(NodeElement::new("b").child("three")));
+        assert_eq!(
+            json(list.into_node()),
+            r#"{"tag":"ul","children":[{"tag":"li","children":["one"]},{"tag":"li","children":["two"]},{"tag":"li","children":[{"tag":"b","children":["three"]}]}]}"#
+        );
+    }
+
+    #[test]
+    fn figures() {
+        let json = |node: Node| serde_json::to_string(&node).unwrap();
+        assert_eq!(
+            json(Node::figure("/file/a.jpg", None)),
+            r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/a.jpg"}}]}"#
+        );
+        assert_eq!(
+            json(Node::figure_with("/file/a.jpg", Vec::<Node>::new())),
+            json(Node::figure("/file/a.jpg", None))
+        );
+        let caption = vec![
+            Node::text("Photo by "),
+            NodeElement::new("a")
+                .attr("href", "https://example.com/")
+                .child("someone")
+                .into_node(),
+        ];
+        assert_eq!(
+            json(Node::figure_with("/file/a.jpg", caption)),
+            r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/a.jpg"}},{"tag":"figcaption","children":["Photo by ",{"tag":"a","attrs":{"href":"https://example.com/"},"children":["someone"]}]}]}"#
+        );
+        let image = ImageInfo {
+            src: "/file/b.png".to_owned(),
+        };
+        assert_eq!(
+            json(image.figure(Some("B"))),
+            r#"{"tag":"figure","children":[{"tag":"img","attrs":{"src":"/file/b.png"}},{"tag":"figcaption","children":["B"]}]}"#
+        );
+    }
+
+    #[test]
+    fn embeds() {
+        let json = |node: Node| serde_json::to_string(&node).unwrap();
+        assert_eq!(
+            json(Node::vimeo("https://vimeo.com/76979871")),
+            r#"{"tag":"figure","children":[{"tag":"iframe","attrs":{"src":"/embed/vimeo?url=https%3A%2F%2Fvimeo.com%2F76979871"}}]}"#
+        );
+        assert_eq!(
+            json(Node::twitter("https://twitter.com/durov/status/1")),
+            json(Node::embed("twitter", "https://twitter.com/durov/status/1"))
+        );
+        assert_eq!(
+            json(Node::embed_captioned(
+                "youtube",
+                "https://youtu.be/x?a=1&b=2",
+                "A talk"
+            )),
+            r#"{"tag":"figure","children":[{"tag":"iframe","attrs":{"src":"/embed/youtube?url=https%3A%2F%2Fyoutu.be%2Fx%3Fa%3D1%26b%3D2"}},{"tag":"figcaption","children":["A talk"]}]}"#
+        );
+        let src = match &Node::youtube("https://youtu.be/x?a=1&b=2") {
+            Node::NodeElement(figure) => match &figure.children.as_ref().unwrap()[0] {
+                Node::NodeElement(iframe) => iframe.attrs.as_ref().unwrap().src.clone().unwrap(),
+                Node::Text(_) => unreachable!(),
+            },
+            Node::Text(_) => unreachable!(),
+        };
+        assert_eq!(
+            crate::utils::embedded_url(&src).as_deref(),
+            Some("https://youtu.be/x?a=1&b=2")
+        );
+    }
+
+    /// Response of `getPage` for a page created with the content built in `code_blocks`
+    const CODE_PAGE: &str = r#"{"path":"Code-10-16","url":"https://telegra.ph/Code-10-16","title":"Code","description":"","content":[{"tag":"p","children":["Call ",{"tag":"code","children":["main()"]},":"]},{"tag":"pre","children":[{"tag":"code","children":["fn main() {\n\tlet a = 1 < 2 && true;\n}\n"]}]},{"tag":"blockquote","children":["<b>not bold</b> ",{"tag":"code","children":["&amp;"]}]}],"views":0}"#;
+
+    #[test]
+    fn code_blocks() {
+        let content = vec![
+            NodeElement::new("p")
+                .child("Call ")
+                .child(Node::code("main()"))
+                .child(":")
+                .into_node(),
+            Node::code_block("fn main() {\n\tlet a = 1 < 2 && true;\n}\n"),
+            Node::blockquote(vec![Node::text("<b>not bold</b> "), Node::code("&amp;")]),
+        ];
+        let json = serde_json::to_string(&content).unwrap();
+        let page: Page = serde_json::from_str(CODE_PAGE).unwrap();
+        assert_eq!(page.content.as_ref(), Some(&content));
+        assert!(CODE_PAGE.contains(&format!(r#""content":{},"#, json)));
+        assert_eq!(serde_json::to_string(&page.content.unwrap()).unwrap(), json);
+    }
+
+    #[test]
+    fn content_html() {
+        let mut page: Page = serde_json::from_str(CODE_PAGE).unwrap();
+        page.content.as_mut().unwrap().extend([
+            NodeElement::new("p")
+                .child(
+                    NodeElement::new("a")
+                        .with_href("/a?b=1&c=\"2\"")
+                        .child("A & B"),
+                )
+                .into_node(),
+            NodeElement::new("figure")
+                .child(NodeElement::img("/file/a.jpg"))
+                .child(NodeElement::new("figcaption").child("<cat>"))
+                .into_node(),
+        ]);
+        assert_eq!(
+            page.content_html().unwrap(),
+            concat!(
+                "<p>Call <code>main()</code>:</p>",
+                "<pre><code>fn main() {\n\tlet a = 1 &lt; 2 &amp;&amp; true;\n}\n</code></pre>",
+                "<blockquote>&lt;b&gt;not bold&lt;/b&gt; <code>&amp;amp;</code></blockquote>",
+                r#"<p><a href="/a?b=1&amp;c=&quot;2&quot;">A &amp; B</a></p>"#,
+                r#"<figure><img src="/file/a.jpg"><figcaption>&lt;cat&gt;</figcaption></figure>"#,
+            )
+        );
+        assert_eq!(
+            page.content_text().unwrap(),
+            "Call main():\nfn main() {\n\tlet a = 1 < 2 && true;\n}\n<b>not bold</b> &amp;\nA & B\n<cat>"
+        );
+        assert_eq!(page.text(), page.content_text());
+
+        page.content = None;
+        assert_eq!(page.content_html(), None);
+        assert_eq!(page.content_text(), None);
+        assert_eq!(page.text(), None);
+    }
+
+    #[test]
+    fn page_path() {
+        let cases = [
+            ("Sample-Page-12-15", Some(("Sample-Page", (12, 15), None))),
+            (
+                "Sample-Page-12-15-2",
+                Some(("Sample-Page", (12, 15), Some(2))),
+            ),
+            ("Hi-10-16", Some(("Hi", (10, 16), None))),
+            ("Hi-10-16-117", Some(("Hi", (10, 16), Some(117)))),
+            (
+                "Top-10-Movies-2024-03-01",
+                Some(("Top-10-Movies-2024", (3, 1), None)),
+            ),
+            ("Chapter-3-07-21-4", Some(("Chapter-3", (7, 21), Some(4)))),
+            ("Chapter-12-01-05", Some(("Chapter-12", (1, 5), None))),
+            ("Part-2-11-30-12", Some(("Part-2", (11, 30), Some(12)))),
+            ("1984-02-29", Some(("1984", (2, 29), None))),
+            ("Leap-Day-02-29-3", Some(("Leap-Day", (2, 29), Some(3)))),
+            ("Version-1-0-12-31", Some(("Version-1-0", (12, 31), None))),
+            (
+                "%D0%9F%D1%80%D0%B8%D0%B2%D0%B5%D1%82-05-09",
+                Some(("%D0%9F%D1%80%D0%B8%D0%B2%D0%B5%D1%82", (5, 9), None)),
+            ),
+            ("你好-世界-05-09-3", Some(("你好-世界", (5, 9), Some(3)))),
+            ("Sample-Page-12-15-0", None),
+            ("Sample-Page-12-15-02", None),
+            ("Sample-Page-12-15-1", None),
+            ("Sample-13-15", None),
+            ("Sample-02-30", None),
+            ("Sample-1-5", None),
+            ("Sample-Page", None),
+            ("12-15", None),
+            ("-12-15", None),
+            ("", None),
+        ];
+        let mut page: Page = serde_json::from_str(CODE_PAGE).unwrap();
+        for (path, expected) in cases {
+            page.path = path.to_owned();
+            assert_eq!(parse_page_path(path), expected, "{}", path);
+            assert_eq!(page.created_date(), expected.map(|(_, date, _)| date));
+            assert_eq!(page.slug(), expected.map_or(path, |(slug, _, _)| slug));
+            assert_eq!(
+                page.collision_index(),
+                expected.and_then(|(_, _, index)| index)
+            );
+        }
+    }
+
+    #[test]
+    fn response_round_trip() {
+        fn round_trip<T>(fixture: &str) -> String
+        where
+            T: serde::de::DeserializeOwned + Serialize + PartialEq + std::fmt::Debug,
+        {
+            let value: T = serde_json::from_str(fixture).unwrap();
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value);
+            json
+        }
+
+        let account =
+            r#"{"short_name":"Sandbox","author_name":"Anonymous","author_url":"","page_count":2}"#;
+        assert_eq!(round_trip::<Account>(account), account);
+        let list = format!(
+            r#"{{"total_count":2,"pages":[{},{{"path":"Hi-10-16","url":"https://telegra.ph/Hi-10-16","title":"Hi","description":"","views":1,"can_edit":true}}]}}"#,
+            CODE_PAGE
+        );
+        assert_eq!(round_trip::<PageList>(&list), list);
+        let page = r#"{"path":"A-10-16","url":"https://telegra.ph/A-10-16","title":"A","description":"","author_name":null,"content":[{"tag":"p","attrs":{},"children":["a"]}],"views":0}"#;
+        assert_eq!(
+            round_trip::<Page>(page),
+            r#"{"path":"A-10-16","url":"https://telegra.ph/A-10-16","title":"A","description":"","content":[{"tag":"p","children":["a"]}],"views":0}"#
+        );
+        assert_eq!(
+            round_trip::<PageViews>(r#"{"views":42}"#),
+            r#"{"views":42}"#
+        );
+        let image = r#"{"src":"/file/a.jpg"}"#;
+        assert_eq!(round_trip::<ImageInfo>(image), image);
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Page)).unwrap();
+        let schema = jsonschema::JSONSchema::compile(&schema).unwrap();
+        let page: serde_json::Value = serde_json::from_str(CODE_PAGE).unwrap();
+        assert!(schema.is_valid(&page));
+        let page = serde_json::json!({
+            "path": "A-10-16",
+            "url": "https://telegra.ph/A-10-16",
+            "title": "A",
+            "description": "",
+            "author_name": null,
+            "content": ["text", {"tag": "a", "attrs": {"href": "/a", "target": null}, "children": [{"tag": "br"}]}],
+            "views": 1,
+        });
+        assert!(schema.is_valid(&page));
+
+        let invalid = [
+            serde_json::json!({"path": "A-10-16"}),
+            serde_json::json!({"path": "A", "url": "", "title": "", "description": "", "views": 0, "content": [1]}),
+            serde_json::json!({"path": "A", "url": "", "title": "", "description": "", "views": 0, "content": [{"children": []}]}),
+            serde_json::json!({"path": "A", "url": "", "title": "", "description": "", "views": 0, "content": [{"tag": "a", "attrs": {"href": 1}}]}),
+        ];
+        for page in &invalid {
+            assert!(!schema.is_valid(page), "{}", page);
+        }
+
+        let schema = serde_json::to_value(schemars::schema_for!(PageList)).unwrap();
+        assert!(schema["definitions"]["Node"]["anyOf"].is_array());
+        for schema in [
+            schemars::schema_for!(Account),
+            schemars::schema_for!(PageViews),
+            schemars::schema_for!(Node),
+        ] {
+            let schema = serde_json::to_value(schema).unwrap();
+            assert!(jsonschema::JSONSchema::compile(&schema).is_ok());
+        }
+    }
+
+    #[test]
+    fn videos() {
+        let json = |node: Node| serde_json::to_string(&node).unwrap();
+        assert_eq!(
+            json(Node::video("/file/clip.mp4")),
+            r#"{"tag":"video","attrs":{"src":"/file/clip.mp4"}}"#
+        );
+        let upload = ImageInfo {
+            src: "/file/0b5a2f1e8c.mp4".to_owned(),
+        };
+        assert!(upload.is_video());
+        assert_eq!(
+            json(upload.figure(None)),
+            r#"{"tag":"figure","children":[{"tag":"video","attrs":{"src":"/file/0b5a2f1e8c.mp4"}}]}"#
+        );
+        assert_eq!(
+            json(upload.into()),
+            json(Node::video("/file/0b5a2f1e8c.mp4"))
+        );
+        let image = ImageInfo {
+            src: "/file/b.png".to_owned(),
+        };
+        assert_eq!(
+            json(image.into()),
+            r#"{"tag":"img","attrs":{"src":"/file/b.png"}}"#
+        );
+    }
+
+    #[test]
+    fn typed_attrs() {
+        let json = r#"{"tag":"a","attrs":{"href":"https://telegra.ph/"},"children":["link"]}"#;
+        let element: NodeElement = serde_json::from_str(json).unwrap();
+        let attrs = element.attrs.clone().unwrap();
+        assert_eq!(attrs.href.as_deref(), Some("https://telegra.ph/"));
+        assert_eq!(attrs.src, None);
+        assert!(attrs.extra.is_empty());
+        assert_eq!(serde_json::to_string(&element).unwrap(), json);
+
+        let map = HashMap::from([
+            ("src".to_owned(), Some("/file/a.jpg".to_owned())),
+            ("alt".to_owned(), None),
+        ]);
+        let attrs = NodeAttrs::from(map.clone());
+        assert_eq!(attrs.src.as_deref(), Some("/file/a.jpg"));
+        assert_eq!(attrs.extra, HashMap::from([("alt".to_owned(), None)]));
+        assert_eq!(attrs.get("src"), Some("/file/a.jpg"));
+        assert_eq!(HashMap::from(attrs), map);
+    }
+
+    #[test]
+    fn foreign_attrs() {
+        let page: Page = serde_json::from_str(
+            r#"{
+                "path": "Imported-10-16",
+                "url": "https://telegra.ph/Imported-10-16",
+                "title": "Imported",
+                "description": "",
+                "views": 3,
+                "content": [
+                    {"tag": "p", "attrs": [], "children": ["text"]},
+                    {"tag": "figure", "children": [
+                        {"tag": "img", "attrs": {"src": "/file/a.jpg", "width": 640, "height": 480.5}},
+                        {"tag": "video", "attrs": {"src": "/file/b.mp4", "autoplay": true, "loop": null}}
+                    ]},
+                    {"tag": "a", "attrs": {"href": "/x", "rel": ["nofollow", "noopener"]}, "children": ["link"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let content = page.content.unwrap();
+        let attrs = |path: &[usize]| match crate::node_at(&content, path) {
+            Some(Node::NodeElement(element)) => element.attrs.clone(),
+            node => panic!("{:?}", node),
+        };
+        let map = |attrs: &[(&str, Option<&str>)]| {
+            Some(
+                attrs
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.map(str::to_owned)))
+                    .collect::<NodeAttrs>(),
+            )
+        };
+        assert_eq!(attrs(&[0]), None);
+        assert_eq!(
+            attrs(&[1, 0]),
+            map(&[
+                ("src", Some("/file/a.jpg")),
+                ("width", Some("640")),
+                ("height", Some("480.5"))
+            ])
+        );
+        assert_eq!(
+            attrs(&[1, 1]),
+            map(&[
+                ("src", Some("/file/b.mp4")),
+                ("autoplay", Some("true")),
+                ("loop", None)
+            ])
+        );
+        assert_eq!(
+            attrs(&[2]),
+            map(&[("href", Some("/x")), ("rel", Some("nofollow noopener"))])
+        );
+
+        assert!(serde_json::from_str::<NodeElement>(r#"{"tag": "p", "attrs": [1]}"#).is_err());
+        assert!(serde_json::from_str::<NodeElement>(r#"{"tag": "p", "attrs": 1}"#).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    proptest! {
+        #[test]
+        fn serde_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..4096)) {
+            let nodes: Vec<Node> = arbitrary::Unstructured::new(&bytes).arbitrary().unwrap();
+            let json = serde_json::to_string(&nodes).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Vec<Node>>(&json).unwrap(), nodes);
+        }
+    }
+}
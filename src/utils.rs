@@ -20,22 +20,92 @@ pub fn read_to_bytes<P: AsRef<Path>>(path: P) -> crate::Result<Vec<u8>> {
     Ok(bytes)
 }
 
-/// Parse html to node string
-///
-/// ```rust
-/// use telegraph_rs::html_to_node;
-///
-/// let node = html_to_node("<p>Hello, world</p>");
-/// assert_eq!(node, r#"[{"tag":"p","children":["Hello, world"]}]"#);
-/// ```
-#[cfg(feature = "html")]
-pub fn html_to_node(html: &str) -> String {
-    use html_parser::Dom;
-
-    let dom = Dom::parse(html).unwrap();
-    let nodes = dom.children
-        .into_iter()
-        .map(|node| crate::html_to_node_inner(&node))
-        .collect::<Vec<_>>();
-    serde_json::to_string(&nodes).unwrap()
+/// The path of a file hosted by Telegraph, like `/file/cat.jpg`
+pub(crate) fn hosted_file(src: &str) -> Option<&str> {
+    let path = ["https://telegra.ph", "https://graph.org"]
+        .iter()
+        .find_map(|host| src.strip_prefix(host))
+        .unwrap_or(src);
+    path.starts_with("/file/").then_some(path)
+}
+
+/// Build the `src` of a Telegraph embed iframe, e.g. `/embed/youtube?url=...`
+pub(crate) fn embed_src(service: &str, url: &str) -> String {
+    let query = reqwest::Url::parse_with_params("https://telegra.ph/", &[("url", url)]).unwrap();
+    format!("/embed/{}?{}", service, query.query().unwrap_or_default())
+}
+
+/// The embedded URL of a Telegraph embed iframe `src`, the inverse of `embed_src`
+pub(crate) fn embedded_url(src: &str) -> Option<String> {
+    let url = reqwest::Url::parse("https://telegra.ph/")
+        .ok()?
+        .join(src)
+        .ok()?;
+    if !url.path().starts_with("/embed/") {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "url")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Hosts serving telegraph pages
+const PAGE_HOSTS: &[&str] = &["telegra.ph", "graph.org", "te.legra.ph"];
+
+/// The percent-decoded path of a telegraph page url like `https://telegra.ph/Sample-Page-12-15`,
+/// ignoring the query and fragment
+pub(crate) fn page_url_path(url: &reqwest::Url) -> Option<String> {
+    if !matches!(url.scheme(), "http" | "https") || !PAGE_HOSTS.contains(&url.host_str()?) {
+        return None;
+    }
+    let path = url.path().trim_matches('/');
+    if path.is_empty() || path.contains('/') {
+        return None;
+    }
+    let path = percent_encoding::percent_decode_str(path)
+        .decode_utf8()
+        .ok()?;
+    Some(path.into_owned())
+}
+
+/// The path of the page at `url`, failing with [`Error::InvalidPageUrl`](crate::Error::InvalidPageUrl)
+/// if it isn't a telegraph page url
+pub(crate) fn parse_page_url(url: &str) -> crate::Result<String> {
+    reqwest::Url::parse(url)
+        .ok()
+        .as_ref()
+        .and_then(page_url_path)
+        .ok_or_else(|| crate::Error::InvalidPageUrl(url.to_owned()))
+}
+
+/// Render rows of cells as column-aligned plain text,
+/// the first row is separated from the others by a line of dashes if `header` is set
+#[cfg(any(feature = "html", feature = "markdown"))]
+pub(crate) fn format_table(rows: &[Vec<String>], header: bool) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    for (i, row) in rows.iter().enumerate() {
+        let mut line = String::new();
+        for (j, width) in widths.iter().enumerate() {
+            let cell = row.get(j).map(String::as_str).unwrap_or_default();
+            if j > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            line.extend(std::iter::repeat_n(' ', width - cell.chars().count()));
+        }
+        lines.push(line.trim_end().to_owned());
+        if i == 0 && header {
+            let total = widths.iter().sum::<usize>() + 2 * columns.saturating_sub(1);
+            lines.push("-".repeat(total));
+        }
+    }
+    lines.join("\n")
 }
@@ -0,0 +1,267 @@
+//! Check node trees against the content accepted by Telegraph
+use crate::{utils::hosted_file, Node, NodeAttrs, Tag};
+use std::fmt;
+
+/// A problem found by [`validate_nodes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Indices of the offending node and its ancestors, see [`node_at`](crate::node_at)
+    pub path: Vec<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Options for [`validate_nodes_with_options`]
+///
+/// ```
+/// use telegraph_rs::ValidationOptions;
+///
+/// let options = ValidationOptions::new().strict_video_src(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ValidationOptions {
+    strict_video_src: bool,
+}
+
+impl ValidationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `video` elements must play files hosted by Telegraph, false by default.
+    ///
+    /// Hosted files are relative paths like `/file/clip.mp4` or urls on `telegra.ph`.
+    /// External videos are allowed otherwise, since some of them play.
+    pub fn strict_video_src(mut self, strict: bool) -> Self {
+        self.strict_video_src = strict;
+        self
+    }
+}
+
+/// Check that `nodes` only use features Telegraph accepts
+///
+/// - tags are in [`ALLOWED_TAGS`](crate::ALLOWED_TAGS)
+/// - `href` is only set on `a`, `src` only on `img`, `iframe` and `video`, and there are no other attributes
+/// - `figcaption` is inside `figure`, and `li` inside `ol` or `ul`
+/// - text is not empty
+///
+/// ```
+/// use telegraph_rs::{validate_nodes, NodeElement};
+///
+/// let nodes = vec![NodeElement::new("li").child("item").into_node()];
+/// let errors = validate_nodes(&nodes).unwrap_err();
+/// assert_eq!(errors[0].to_string(), "[0]: `li` must be inside `ol` or `ul`");
+/// ```
+pub fn validate_nodes(nodes: &[Node]) -> Result<(), Vec<ValidationError>> {
+    validate_nodes_with_options(nodes, &ValidationOptions::default())
+}
+
+/// Check that `nodes` only use features Telegraph accepts, see [`validate_nodes`]
+///
+/// ```
+/// use telegraph_rs::{validate_nodes_with_options, Node, ValidationOptions};
+///
+/// let options = ValidationOptions::new().strict_video_src(true);
+/// let nodes = [Node::video("/file/clip.mp4"), Node::video("https://example.com/clip.mp4")];
+/// let errors = validate_nodes_with_options(&nodes, &options).unwrap_err();
+/// assert_eq!(
+///     errors[0].to_string(),
+///     "[1]: `video` src `https://example.com/clip.mp4` is not hosted by Telegraph"
+/// );
+/// ```
+pub fn validate_nodes_with_options(
+    nodes: &[Node],
+    options: &ValidationOptions,
+) -> Result<(), Vec<ValidationError>> {
+    let mut errors = vec![];
+    validate(nodes, None, options, &mut vec![], &mut errors);
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+fn validate(
+    nodes: &[Node],
+    parent: Option<&Tag>,
+    options: &ValidationOptions,
+    path: &mut Vec<usize>,
+    errors: &mut Vec<ValidationError>,
+) {
+    for (i, node) in nodes.iter().enumerate() {
+        path.push(i);
+        let mut error = |message: String| {
+            errors.push(ValidationError {
+                path: path.clone(),
+                message,
+            })
+        };
+        match node {
+            Node::Text(text) if text.is_empty() => error("text is empty".to_owned()),
+            Node::Text(_) => {}
+            Node::NodeElement(element) => {
+                let tag = element.tag_enum();
+                if !tag.is_known() {
+                    error(format!("tag `{}` is not allowed", tag));
+                }
+                let mut names = element
+                    .attrs
+                    .iter()
+                    .flat_map(NodeAttrs::iter)
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>();
+                names.sort_unstable();
+                for name in names {
+                    let allowed = match name {
+                        "href" => tag == Tag::A,
+                        "src" => matches!(tag, Tag::Img | Tag::Iframe | Tag::Video),
+                        _ => false,
+                    };
+                    if !allowed {
+                        error(format!("attribute `{}` is not allowed on `{}`", name, tag));
+                    }
+                }
+                let src = element
+                    .attrs
+                    .as_ref()
+                    .and_then(|attrs| attrs.src.as_deref());
+                if let (Tag::Video, Some(src), true) = (&tag, src, options.strict_video_src) {
+                    if hosted_file(src).is_none() {
+                        error(format!("`video` src `{}` is not hosted by Telegraph", src));
+                    }
+                }
+                match tag {
+                    Tag::Figcaption if parent != Some(&Tag::Figure) => {
+                        error("`figcaption` must be inside `figure`".to_owned())
+                    }
+                    Tag::Li if !matches!(parent, Some(Tag::Ol | Tag::Ul)) => {
+                        error("`li` must be inside `ol` or `ul`".to_owned())
+                    }
+                    _ => {}
+                }
+                let children = element.children.as_deref().unwrap_or_default();
+                validate(children, Some(&tag), options, path, errors);
+            }
+        }
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(json: serde_json::Value) -> Vec<String> {
+        let nodes: Vec<Node> = serde_json::from_value(json).unwrap();
+        match validate_nodes(&nodes) {
+            Ok(()) => vec![],
+            Err(errors) => errors.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn valid_document() {
+        let errors = errors(serde_json::json!([
+            {"tag": "h3", "children": ["Title"]},
+            {"tag": "p", "children": [
+                "Text with ", {"tag": "a", "attrs": {"href": "https://example.com/"}, "children": [{"tag": "b", "children": ["a link"]}]},
+                {"tag": "br"},
+            ]},
+            {"tag": "figure", "children": [
+                {"tag": "img", "attrs": {"src": "/file/a.jpg"}},
+                {"tag": "figcaption", "children": ["Caption"]},
+            ]},
+            {"tag": "ul", "children": [
+                {"tag": "li", "children": ["one", {"tag": "ol", "children": [{"tag": "li", "children": ["nested"]}]}]},
+            ]},
+            {"tag": "figure", "children": [
+                {"tag": "iframe", "attrs": {"src": "/embed/youtube?url=https%3A%2F%2Fyoutu.be%2Fx"}},
+            ]},
+            {"tag": "pre", "children": ["code"]},
+        ]));
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn unknown_tags() {
+        let errors = errors(serde_json::json!([
+            {"tag": "div", "children": [{"tag": "p", "children": [{"tag": "span", "children": ["x"]}]}]},
+        ]));
+        assert_eq!(
+            errors,
+            [
+                "[0]: tag `div` is not allowed",
+                "[0, 0, 0]: tag `span` is not allowed"
+            ]
+        );
+    }
+
+    #[test]
+    fn misplaced_attributes() {
+        let errors = errors(serde_json::json!([
+            {"tag": "p", "attrs": {"href": "https://example.com/"}, "children": ["x"]},
+            {"tag": "a", "attrs": {"src": "/file/a.jpg", "target": "_blank"}, "children": ["x"]},
+            {"tag": "img", "attrs": {"src": "/file/a.jpg", "alt": null}},
+        ]));
+        assert_eq!(
+            errors,
+            [
+                "[0]: attribute `href` is not allowed on `p`",
+                "[1]: attribute `src` is not allowed on `a`",
+                "[1]: attribute `target` is not allowed on `a`",
+                "[2]: attribute `alt` is not allowed on `img`",
+            ]
+        );
+    }
+
+    #[test]
+    fn misplaced_elements() {
+        let errors = errors(serde_json::json!([
+            {"tag": "figcaption", "children": ["caption"]},
+            {"tag": "p", "children": [{"tag": "li", "children": ["item"]}]},
+            {"tag": "figure", "children": [{"tag": "p", "children": [{"tag": "figcaption", "children": ["x"]}]}]},
+        ]));
+        assert_eq!(
+            errors,
+            [
+                "[0]: `figcaption` must be inside `figure`",
+                "[1, 0]: `li` must be inside `ol` or `ul`",
+                "[2, 0, 0]: `figcaption` must be inside `figure`",
+            ]
+        );
+    }
+
+    #[test]
+    fn video_sources() {
+        let nodes = [
+            Node::video("/file/a.mp4"),
+            Node::video("https://telegra.ph/file/b.mp4"),
+            Node::video("https://example.com/c.mp4"),
+            Node::video_figure("//example.com/d.mp4", None),
+        ];
+        assert_eq!(validate_nodes(&nodes), Ok(()));
+        let options = ValidationOptions::new().strict_video_src(true);
+        let errors = validate_nodes_with_options(&nodes, &options).unwrap_err();
+        let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "[2]: `video` src `https://example.com/c.mp4` is not hosted by Telegraph",
+                "[3, 0]: `video` src `//example.com/d.mp4` is not hosted by Telegraph",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_text() {
+        let errors = errors(serde_json::json!(["", {"tag": "p", "children": ["x", ""]}]));
+        assert_eq!(errors, ["[0]: text is empty", "[1, 1]: text is empty"]);
+    }
+}